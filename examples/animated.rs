@@ -3,8 +3,8 @@ use std::collections::VecDeque;
 use bevy::color::palettes::css::WHITE;
 use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
 use bevy::prelude::*;
+use neko_maid::animation::{AnimateVariable, AnimationRepeat};
 use neko_maid::components::NekoUITree;
-use neko_maid::parse::value::PropertyValue;
 
 #[derive(Resource, Clone)]
 pub struct FpsSettings {
@@ -159,7 +159,13 @@ fn setup(asset_server: Res<AssetServer>, mut commands: Commands) {
     commands.spawn(Camera2d);
 
     let handle = asset_server.load("animated.neko_ui");
-    commands.spawn(NekoUITree::new(handle));
+    let tree = commands.spawn(NekoUITree::new(handle)).id();
+
+    commands.spawn(
+        AnimateVariable::new(tree, "random-num", 15.0, 25.0, std::f32::consts::PI / 5.0)
+            .with_easing(EaseFunction::SineInOut)
+            .with_repeat(AnimationRepeat::PingPong),
+    );
 }
 
 pub fn update_animation(
@@ -174,11 +180,7 @@ pub fn update_animation(
     let h = (time.elapsed_secs_f64() % 4.0) / 4.0 * 360.0;
     let color = Color::hsl(h as f32, 0.5, 0.3);
 
-    let num = 20.0 + f64::sin(time.elapsed_secs_f64() * 5.0) * 5.0;
-    let num = (num * 20.0).round() / 20.0;
-
     for mut root in &mut q {
-        root.set_variable("random-color", PropertyValue::Color(color));
-        root.set_variable("random-num", PropertyValue::Number(num));
+        root.set_color("random-color", color);
     }
 }
@@ -19,6 +19,6 @@ fn setup(asset_server: Res<AssetServer>, mut commands: Commands) {
 
 pub fn update_tree(q: Query<&mut NekoUITree>) {
     for mut root in q {
-        root.mark_dirty();
+        root.reload();
     }
 }
@@ -0,0 +1,172 @@
+//! Helpers for animating the variables of a NekoMaid UI tree over time.
+
+use bevy::prelude::*;
+
+use crate::components::NekoUITree;
+use crate::parse::value::PropertyValue;
+
+/// How an [`AnimateVariable`] behaves once it reaches the end of its duration.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum AnimationRepeat {
+    /// The animation plays once, then its entity is despawned.
+    #[default]
+    Once,
+
+    /// The animation restarts from the beginning.
+    Loop,
+
+    /// The animation plays back and forth between the two values.
+    PingPong,
+}
+
+/// A component that drives a variable of a [`NekoUITree`] from one value to
+/// another over time.
+///
+/// The animation lives on its own entity and targets the tree entity given by
+/// [`tree`](Self::tree). Number, percentage, pixel and color values can be
+/// interpolated. The variable is set every frame while the animation runs.
+#[derive(Debug, Clone, Component)]
+pub struct AnimateVariable {
+    /// The entity with the [`NekoUITree`] component to animate.
+    pub tree: Entity,
+
+    /// The name of the variable to animate.
+    pub name: String,
+
+    /// The value at the start of the animation.
+    pub from: PropertyValue,
+
+    /// The value at the end of the animation.
+    pub to: PropertyValue,
+
+    /// The duration of the animation, in seconds.
+    pub duration: f32,
+
+    /// The easing function applied to the progress of the animation.
+    pub easing: EaseFunction,
+
+    /// What happens once the animation is complete.
+    pub repeat: AnimationRepeat,
+
+    /// The time elapsed since the start of the animation, in seconds.
+    elapsed: f32,
+}
+
+impl AnimateVariable {
+    /// Creates a new linear animation of the variable `name` of the given tree.
+    pub fn new(
+        tree: Entity,
+        name: impl Into<String>,
+        from: impl Into<PropertyValue>,
+        to: impl Into<PropertyValue>,
+        duration: f32,
+    ) -> Self {
+        Self {
+            tree,
+            name: name.into(),
+            from: from.into(),
+            to: to.into(),
+            duration,
+            easing: EaseFunction::Linear,
+            repeat: AnimationRepeat::Once,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Sets the easing function of this animation.
+    pub fn with_easing(mut self, easing: EaseFunction) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// Sets the repeat behavior of this animation.
+    pub fn with_repeat(mut self, repeat: AnimationRepeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Returns the progress of this animation, between `0.0` and `1.0`, before
+    /// easing is applied.
+    pub fn progress(&self) -> f32 {
+        if self.duration <= 0.0 {
+            return 1.0;
+        }
+
+        let t = self.elapsed / self.duration;
+        match self.repeat {
+            AnimationRepeat::Once => t.min(1.0),
+            AnimationRepeat::Loop => t.fract(),
+            AnimationRepeat::PingPong => {
+                let t = t % 2.0;
+                if t > 1.0 { 2.0 - t } else { t }
+            }
+        }
+    }
+
+    /// Returns whether this animation has finished playing.
+    pub fn is_finished(&self) -> bool {
+        self.repeat == AnimationRepeat::Once && self.elapsed >= self.duration
+    }
+
+    /// Returns the current value of the animated variable.
+    ///
+    /// If the two values cannot be interpolated, the value snaps from `from`
+    /// to `to` once the animation is complete.
+    pub fn value(&self) -> PropertyValue {
+        let t = self.easing.sample_clamped(self.progress());
+        interpolate(&self.from, &self.to, t).unwrap_or_else(|| {
+            if t >= 1.0 {
+                self.to.clone()
+            } else {
+                self.from.clone()
+            }
+        })
+    }
+}
+
+/// Interpolates between two property values of the same type.
+///
+/// Returns `None` if the values have different types or if their type cannot
+/// be interpolated.
+fn interpolate(from: &PropertyValue, to: &PropertyValue, t: f32) -> Option<PropertyValue> {
+    let lerp = |a: f64, b: f64| a + (b - a) * t as f64;
+
+    match (from, to) {
+        (PropertyValue::Number(a), PropertyValue::Number(b)) => {
+            Some(PropertyValue::Number(lerp(*a, *b)))
+        }
+        (PropertyValue::Percent(a), PropertyValue::Percent(b)) => {
+            Some(PropertyValue::Percent(lerp(*a, *b)))
+        }
+        (PropertyValue::Pixels(a), PropertyValue::Pixels(b)) => {
+            Some(PropertyValue::Pixels(lerp(*a, *b)))
+        }
+        (PropertyValue::Color(a), PropertyValue::Color(b)) => {
+            Some(PropertyValue::Color(a.mix(b, t)))
+        }
+        _ => None,
+    }
+}
+
+/// Advances all [`AnimateVariable`] components and writes their current value
+/// to the targeted trees.
+pub(crate) fn animate_variables(
+    time: Res<Time>,
+    animations: Query<(Entity, &mut AnimateVariable)>,
+    mut trees: Query<&mut NekoUITree>,
+    mut commands: Commands,
+) {
+    for (entity, mut animation) in animations {
+        let Ok(mut tree) = trees.get_mut(animation.tree) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+
+        animation.elapsed += time.delta_secs();
+        tree.set_variable(&animation.name, animation.value());
+
+        if animation.is_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
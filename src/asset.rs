@@ -1,31 +1,121 @@
 //! The NekoMaid style asset, and asset loader for NekoMaid ui files.
 
-use std::time::Instant;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
 
 use bevy::asset::io::Reader;
-use bevy::asset::{AssetLoader, LoadContext, LoadDirectError};
+use bevy::asset::{AssetLoader, AssetPath, LoadContext, LoadDirectError};
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::native::NATIVE_WIDGETS;
 use crate::parse::module::Module;
 use crate::parse::{NekoMaidParseError, NekoMaidParser};
+use crate::render::interaction::InteractionClasses;
 
 /// A NekoMaid UI asset.
 #[derive(Debug, Asset, TypePath, Deref)]
-pub struct NekoMaidUI(Module);
+pub struct NekoMaidUI {
+    /// The parsed module.
+    #[deref]
+    pub(crate) module: Module,
+
+    /// The time taken to parse the module, including its imports.
+    pub(crate) parse_time: Duration,
+}
+
+impl NekoMaidUI {
+    /// Returns the time taken to parse this asset, including its imports.
+    pub fn parse_time(&self) -> Duration {
+        self.parse_time
+    }
+}
+
+/// A message sent when a [`NekoMaidUI`] asset and all of its imports have
+/// finished loading.
+///
+/// This can be used to react to a user interface becoming available, such as
+/// dismissing a splash screen.
+#[derive(Debug, Clone, PartialEq, Eq, Message)]
+pub struct NekoUILoaded {
+    /// The id of the loaded asset.
+    pub id: AssetId<NekoMaidUI>,
+
+    /// The number of widgets defined by the module, not including native
+    /// widgets.
+    pub widget_count: usize,
+
+    /// The number of styles defined by the module.
+    pub style_count: usize,
+}
+
+/// The settings of the [`NekoMaidAssetLoader`], to load the same file with
+/// different initial values, such as themes, with
+/// [`AssetServer::load_with_settings`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NekoMaidSettings {
+    /// The global variables overriding those declared by the loaded file, by
+    /// name. Values are written like in a NekoMaid UI file, such as `#ff0000`,
+    /// `10px` or `"Hello"`. Variables imported from other modules can be
+    /// overridden as well. Overriding a constant, or with a value referencing
+    /// another variable, fails to load the file, and variables the file does
+    /// not declare are ignored with a warning.
+    ///
+    /// The overridden values also select the conditional imports, such as
+    /// `import "ui/mobile" if $platform == "mobile";`.
+    #[serde(default)]
+    pub variables: BTreeMap<String, String>,
+
+    /// The classes added to elements at runtime by the app, such as with
+    /// [`NekoUINode::add_class`](crate::components::NekoUINode::add_class).
+    ///
+    /// Style selectors referencing them are not reported as unused.
+    #[serde(default)]
+    pub runtime_classes: Vec<String>,
+
+    /// The maximum number of nested elements in the element trees of the
+    /// loaded file, or `None` for the
+    /// [`DEFAULT_MAX_DEPTH`](crate::parse::element::DEFAULT_MAX_DEPTH).
+    #[serde(default)]
+    pub max_depth: Option<usize>,
+}
 
 /// The asset loader for NekoMaid ui files.
-#[derive(Debug, Default)]
-pub struct NekoMaidAssetLoader;
+///
+/// The hovered and pressed classes of the [`InteractionClasses`] are read when
+/// the loader is created, so the resource should be inserted before adding the
+/// [`NekoMaidPlugin`](crate::NekoMaidPlugin).
+#[derive(Debug)]
+pub struct NekoMaidAssetLoader {
+    /// The classes NekoMaid adds to interactable elements.
+    interaction_classes: Vec<String>,
+}
+
+impl FromWorld for NekoMaidAssetLoader {
+    fn from_world(world: &mut World) -> Self {
+        let classes = world
+            .get_resource::<InteractionClasses>()
+            .cloned()
+            .unwrap_or_default();
+
+        Self {
+            interaction_classes: [classes.hovered, classes.pressed]
+                .into_iter()
+                .flatten()
+                .collect(),
+        }
+    }
+}
+
 impl AssetLoader for NekoMaidAssetLoader {
     type Asset = NekoMaidUI;
-    type Settings = ();
+    type Settings = NekoMaidSettings;
     type Error = NekoMaidAssetLoaderError;
 
     async fn load(
         &self,
         reader: &mut dyn Reader,
-        _: &Self::Settings,
+        settings: &Self::Settings,
         load_context: &mut LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         let now = Instant::now();
@@ -40,9 +130,24 @@ impl AssetLoader for NekoMaidAssetLoader {
             parser.register_native_widget(native.clone());
         }
 
-        for import in parser.predict_imports().clone() {
-            let path = load_context.asset_path();
-            let Ok(module_path) = path.resolve(&format!("../{}.neko_ui", import)) else {
+        if let Some(max_depth) = settings.max_depth {
+            parser.set_max_depth(max_depth);
+        }
+
+        for class in self
+            .interaction_classes
+            .iter()
+            .chain(&settings.runtime_classes)
+        {
+            parser.add_runtime_class(class.clone());
+        }
+
+        for (name, value) in &settings.variables {
+            parser.override_variable(name, value)?;
+        }
+
+        for import in parser.predict_imports() {
+            let Some(module_path) = resolve_import(load_context.asset_path(), &import) else {
                 continue;
             };
 
@@ -52,20 +157,23 @@ impl AssetLoader for NekoMaidAssetLoader {
                 .load::<NekoMaidUI>(&module_path)
                 .await?;
 
-            let module = asset.get().0.clone();
+            let module = asset.get().module.clone();
             parser.add_module(import.clone(), module);
         }
 
         let module = parser.finish()?;
+        for warning in module.warnings() {
+            warn!("{}: {warning}", load_context.path().display());
+        }
 
-        let elapsed = now.elapsed().as_millis();
+        let parse_time = now.elapsed();
         debug!(
             "Loaded NekoMaid UI asset {} in {} ms.",
             load_context.path().display(),
-            elapsed,
+            parse_time.as_millis(),
         );
 
-        Ok(NekoMaidUI(module))
+        Ok(NekoMaidUI { module, parse_time })
     }
 
     fn extensions(&self) -> &[&str] {
@@ -73,6 +181,21 @@ impl AssetLoader for NekoMaidAssetLoader {
     }
 }
 
+/// Resolves the asset path of an imported module.
+///
+/// Imports starting with `/` are resolved from the root of the asset source,
+/// such as `import "/ui/common";`. Other imports are resolved relative to the
+/// directory of the importing file. In both cases, `.` and `..` segments are
+/// normalized.
+pub(crate) fn resolve_import(path: &AssetPath, import: &str) -> Option<AssetPath<'static>> {
+    let import = match import.starts_with('/') {
+        true => format!("{import}.neko_ui"),
+        false => format!("../{import}.neko_ui"),
+    };
+
+    path.resolve(&import).ok()
+}
+
 /// Errors that can occur while loading a NekoMaid asset.
 #[derive(Debug, thiserror::Error)]
 pub enum NekoMaidAssetLoaderError {
@@ -0,0 +1,202 @@
+//! Binds reflected Rust values to the variables of NekoMaid UI trees.
+//!
+//! Every field of a bound value is exposed as a global variable named after
+//! the binding and the path to the field, such as `$player.health` or
+//! `$player.stats.speed`. The variables are updated whenever the value
+//! changes.
+//!
+//! The bound fields used by a module are created when it is parsed and hold an
+//! empty string until the binding is applied, so they are not declared.
+//!
+//! ```ignore
+//! #[derive(Resource, Reflect)]
+//! struct Player {
+//!     health: f32,
+//! }
+//!
+//! app.bind_reflect::<Player, ()>("player");
+//! ```
+//!
+//! ```text
+//! layout p {
+//!     text: $player.health;
+//! }
+//! ```
+
+use std::any::Any;
+
+use bevy::ecs::query::QueryFilter;
+use bevy::prelude::*;
+use bevy::reflect::{PartialReflect, ReflectRef};
+
+use crate::NekoMaidSystems;
+use crate::components::NekoUITree;
+use crate::parse::value::PropertyValue;
+
+/// Returns the variables exposing the given reflected value under the given
+/// name.
+///
+/// Structs and tuple structs are expanded into one variable per field, named
+/// `name.field` or `name.0`, recursively. Values that cannot be represented by
+/// a [`PropertyValue`] are skipped.
+pub fn reflect_variables(name: &str, value: &dyn PartialReflect) -> Vec<(String, PropertyValue)> {
+    let mut variables = vec![];
+    collect_variables(name, value, &mut variables);
+    variables
+}
+
+/// Appends the variables exposing the given reflected value to `variables`.
+fn collect_variables(
+    name: &str,
+    value: &dyn PartialReflect,
+    variables: &mut Vec<(String, PropertyValue)>,
+) {
+    if let Some(value) = property_value(value) {
+        variables.push((name.to_string(), value));
+        return;
+    }
+
+    match value.reflect_ref() {
+        ReflectRef::Struct(value) => {
+            for (i, field) in value.iter_fields().enumerate() {
+                let Some(field_name) = value.name_at(i) else {
+                    continue;
+                };
+                collect_variables(&format!("{name}.{field_name}"), field, variables);
+            }
+        }
+        ReflectRef::TupleStruct(value) => {
+            for (i, field) in value.iter_fields().enumerate() {
+                collect_variables(&format!("{name}.{i}"), field, variables);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Converts a reflected value of a primitive type into a [`PropertyValue`].
+///
+/// Numbers, booleans, strings, colors and UI lengths are supported.
+fn property_value(value: &dyn PartialReflect) -> Option<PropertyValue> {
+    let value = value.try_as_reflect()?.as_any();
+
+    if let Some(number) = number(value) {
+        return Some(PropertyValue::Number(number));
+    }
+
+    if let Some(value) = value.downcast_ref::<bool>() {
+        return Some(PropertyValue::Bool(*value));
+    }
+
+    if let Some(value) = value.downcast_ref::<String>() {
+        return Some(PropertyValue::String(value.clone()));
+    }
+
+    if let Some(value) = value.downcast_ref::<Color>() {
+        return Some(PropertyValue::Color(*value));
+    }
+
+    match value.downcast_ref::<Val>()? {
+        Val::Px(px) => Some(PropertyValue::Pixels(*px as f64)),
+        Val::Percent(percent) => Some(PropertyValue::Percent(*percent as f64)),
+        Val::Auto => Some(PropertyValue::String("auto".to_string())),
+        _ => None,
+    }
+}
+
+/// Converts a value of a primitive numeric type into a number.
+fn number(value: &dyn Any) -> Option<f64> {
+    value
+        .downcast_ref::<f64>()
+        .copied()
+        .or_else(|| value.downcast_ref::<f32>().map(|n| *n as f64))
+        .or_else(|| value.downcast_ref::<i8>().map(|n| *n as f64))
+        .or_else(|| value.downcast_ref::<i16>().map(|n| *n as f64))
+        .or_else(|| value.downcast_ref::<i32>().map(|n| *n as f64))
+        .or_else(|| value.downcast_ref::<i64>().map(|n| *n as f64))
+        .or_else(|| value.downcast_ref::<isize>().map(|n| *n as f64))
+        .or_else(|| value.downcast_ref::<u8>().map(|n| *n as f64))
+        .or_else(|| value.downcast_ref::<u16>().map(|n| *n as f64))
+        .or_else(|| value.downcast_ref::<u32>().map(|n| *n as f64))
+        .or_else(|| value.downcast_ref::<u64>().map(|n| *n as f64))
+        .or_else(|| value.downcast_ref::<usize>().map(|n| *n as f64))
+}
+
+/// Sets the variables exposing the given reflected value on a tree.
+fn set_variables(tree: &mut NekoUITree, name: &str, value: &dyn PartialReflect) {
+    for (name, value) in reflect_variables(name, value) {
+        tree.set_variable(&name, value);
+    }
+}
+
+/// Creates a system that binds the resource `T` to the trees matching `F`.
+fn bind_resource<T: Resource + Reflect, F: QueryFilter + 'static>(
+    name: String,
+) -> impl FnMut(Res<T>, Query<&mut NekoUITree, F>) {
+    move |resource, mut trees| {
+        for mut tree in trees.iter_mut() {
+            if resource.is_changed() || tree.is_added() {
+                set_variables(&mut tree, &name, resource.as_partial_reflect());
+            }
+        }
+    }
+}
+
+/// Creates a system that binds the component `T` of each tree entity matching
+/// `F` to that tree.
+fn bind_component<T: Component + Reflect, F: QueryFilter + 'static>(
+    name: String,
+) -> impl FnMut(Query<(Ref<T>, &mut NekoUITree), F>) {
+    move |mut trees| {
+        for (component, mut tree) in trees.iter_mut() {
+            if component.is_changed() || tree.is_added() {
+                set_variables(&mut tree, &name, component.as_partial_reflect());
+            }
+        }
+    }
+}
+
+/// A trait to easily bind reflected values to the variables of UI trees.
+///
+/// ```ignore
+/// app.bind_reflect::<Player, ()>("player");
+/// app.bind_reflect_component::<Inventory, With<InventoryMenu>>("inventory");
+/// ```
+pub trait BindAppExt {
+    /// Binds the resource `T` to the trees whose entity matches the query
+    /// filter `F`, exposing its fields as variables under the given name.
+    fn bind_reflect<T: Resource + Reflect, F: QueryFilter + 'static>(
+        &mut self,
+        name: impl Into<String>,
+    ) -> &mut Self;
+
+    /// Binds the component `T` of each tree entity matching the query filter
+    /// `F` to that tree, exposing its fields as variables under the given
+    /// name.
+    fn bind_reflect_component<T: Component + Reflect, F: QueryFilter + 'static>(
+        &mut self,
+        name: impl Into<String>,
+    ) -> &mut Self;
+}
+
+impl BindAppExt for App {
+    fn bind_reflect<T: Resource + Reflect, F: QueryFilter + 'static>(
+        &mut self,
+        name: impl Into<String>,
+    ) -> &mut Self {
+        self.add_systems(
+            Update,
+            bind_resource::<T, F>(name.into()).before(NekoMaidSystems::UpdateTree),
+        )
+    }
+
+    fn bind_reflect_component<T: Component + Reflect, F: QueryFilter + 'static>(
+        &mut self,
+        name: impl Into<String>,
+    ) -> &mut Self {
+        self.add_systems(
+            Update,
+            bind_component::<T, F>(name.into()).before(NekoMaidSystems::UpdateTree),
+        )
+    }
+}
@@ -1,7 +1,9 @@
 //! Components used for the NekoMaid plugin.
 
+use bevy::input_focus::tab_navigation::TabGroup;
 use bevy::platform::collections::{HashMap, HashSet};
 use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
 
 use crate::asset::NekoMaidUI;
 use crate::parse::element::NekoElement;
@@ -17,6 +19,9 @@ pub struct NekoUINode {
     pub(crate) element: NekoElement,
     /// A list of properties that changed and need to be re-rendered.
     pub(crate) updated_properties: Vec<String>,
+    /// The classes added by `class-if` statements. Only these are removed
+    /// again when their condition becomes false.
+    pub(crate) conditional_classes: HashSet<String>,
 }
 
 impl NekoUINode {
@@ -26,15 +31,30 @@ impl NekoUINode {
     }
 
     /// Adds the specified class to this element.
+    ///
+    /// This can be called from any system. The class markers, the class paths
+    /// of the descendants and the active styles of this element are updated
+    /// automatically.
     pub fn add_class(&mut self, class: String) {
         self.element.add_class(class);
     }
 
     /// Removes the specified class from this element.
+    ///
+    /// Like [`add_class`](Self::add_class), all dependent state is updated
+    /// automatically.
     pub fn remove_class(&mut self, class: &str) {
         self.element.remove_class(class);
     }
 
+    /// Replaces all classes of this element with the given set.
+    ///
+    /// Only the classes that actually differ from the current set are added or
+    /// removed, and all of them are applied within the same update.
+    pub fn set_classes(&mut self, classes: HashSet<String>) {
+        self.element.set_classes(classes);
+    }
+
     /// Toggles the specified class in this element.
     pub fn toggle_class(&mut self, class: &str) {
         if self.has_class(class) {
@@ -43,18 +63,67 @@ impl NekoUINode {
             self.element.add_class(class.to_owned());
         }
     }
+
+    /// Sets a property directly on this element, overriding the value defined
+    /// by the layout or by any style. The node is updated right away.
+    pub fn set_property(&mut self, name: &str, value: PropertyValue) {
+        self.element.set_property(name, value);
+        self.updated_properties.push(name.to_owned());
+    }
+
+    /// Removes a property previously set with
+    /// [`set_property`](Self::set_property), restoring the value defined by the
+    /// layout or by the active styles.
+    pub fn clear_property(&mut self, name: &str) {
+        if self.element.clear_property(name).is_some() {
+            self.updated_properties.push(name.to_owned());
+        }
+    }
 }
 
 /// A component representing the root of a NekoMaid UI tree.
+///
+/// Every top-level `layout` block of the asset is spawned as a child of the
+/// root, in the order the layouts are written. Layouts from imported modules
+/// are spawned before those of the importing module.
+///
+/// The root is a [`TabGroup`], so nodes with a `tab-index` property can be
+/// navigated with the keyboard once the
+/// [`TabNavigationPlugin`](bevy::input_focus::tab_navigation::TabNavigationPlugin)
+/// is added.
 #[derive(Debug, Component)]
-#[require(Node)]
+#[require(Node, TabGroup)]
 pub struct NekoUITree {
     /// The NekoMaid UI asset associated with this tree.
     asset: Handle<NekoMaidUI>,
 
+    /// The NekoMaid UI asset spawned instead of `asset` if it fails to load.
+    fallback: Option<Handle<NekoMaidUI>>,
+
+    /// The error `asset` failed to load with, if any.
+    load_error: Option<String>,
+
+    /// Whether the tree is re-spawned when `asset` fails to load.
+    clear_on_failure: bool,
+
+    /// Whether the tree is static, meaning its nodes are never updated after
+    /// being spawned.
+    static_mode: bool,
+
     /// Whether the tree needs to be re-spawned.
     dirty: bool,
 
+    /// Whether the tree is shown.
+    visible: bool,
+
+    /// The width and height applied to the root node when the tree is spawned,
+    /// or `None` to leave the root node untouched.
+    root_size: Option<(Val, Val)>,
+
+    /// The focus policy applied to the root node when the tree is spawned, or
+    /// `None` to leave it untouched.
+    root_focus_policy: Option<FocusPolicy>,
+
     /// Variables that should be inserted into the global context.
     pub(crate) variables: HashMap<String, PropertyValue>,
 
@@ -66,6 +135,23 @@ pub struct NekoUITree {
 
     /// A map to trigger node updates when a targetted scope changes.
     pub(crate) scope_notification: ScopeNotificationMap,
+
+    /// Maps the `name` property of nodes to the nodes with that name.
+    pub(crate) ids: HashMap<String, Vec<Entity>>,
+
+    /// Maps the `key` property of nodes to the node with that key.
+    pub(crate) keys: HashMap<String, Entity>,
+
+    /// The `name` of each node in [`ids`](Self::ids), to unindex it in
+    /// constant time.
+    node_ids: HashMap<Entity, String>,
+
+    /// The `key` of each node in [`keys`](Self::keys).
+    node_keys: HashMap<Entity, String>,
+
+    /// The logical size of the viewport the nodes of this tree were last
+    /// updated for, used to activate the styles declared in `@when` blocks.
+    pub(crate) viewport: Option<Vec2>,
 }
 
 impl NekoUITree {
@@ -73,11 +159,23 @@ impl NekoUITree {
     pub fn new(asset: Handle<NekoMaidUI>) -> Self {
         Self {
             asset,
+            fallback: None,
+            load_error: None,
+            clear_on_failure: true,
+            static_mode: false,
             variables: HashMap::new(),
             dirty: true,
+            visible: true,
+            root_size: Some((Val::Percent(100.0), Val::Percent(100.0))),
+            root_focus_policy: None,
             scope: ScopeTree::default(),
             update_names: HashSet::new(),
             scope_notification: ScopeNotificationMap::default(),
+            ids: HashMap::new(),
+            keys: HashMap::new(),
+            node_ids: HashMap::new(),
+            node_keys: HashMap::new(),
+            viewport: None,
         }
     }
 
@@ -86,6 +184,77 @@ impl NekoUITree {
         &self.asset
     }
 
+    /// Sets a NekoMaid UI asset to spawn instead of the main asset of this
+    /// tree if the main asset fails to load or parse.
+    ///
+    /// The main asset is spawned again as soon as it is successfully reloaded.
+    pub fn with_fallback(mut self, fallback: Handle<NekoMaidUI>) -> Self {
+        self.fallback = Some(fallback);
+        self
+    }
+
+    /// Returns a reference to the fallback asset handle of this tree, if any.
+    pub fn fallback(&self) -> Option<&Handle<NekoMaidUI>> {
+        self.fallback.as_ref()
+    }
+
+    /// Returns the asset handle currently spawned by this tree.
+    ///
+    /// This is the fallback asset if the main asset failed to load and a
+    /// fallback was set, and the main asset otherwise.
+    pub fn active_asset(&self) -> &Handle<NekoMaidUI> {
+        match &self.fallback {
+            Some(fallback) if self.load_error.is_some() => fallback,
+            _ => &self.asset,
+        }
+    }
+
+    /// Returns the error the main asset of this tree last failed to load or
+    /// parse with, or `None` if it loaded successfully.
+    pub fn load_error(&self) -> Option<&str> {
+        self.load_error.as_deref()
+    }
+
+    /// Records the error the main asset of this tree failed to load with, or
+    /// clears it with `None` once the asset loads successfully.
+    pub(crate) fn set_load_error(&mut self, error: Option<String>) {
+        self.load_error = error;
+    }
+
+    /// Sets whether this tree is cleared when its main asset fails to load or
+    /// parse, which is the default.
+    ///
+    /// When disabled, the nodes spawned from the last successful load are kept
+    /// on screen instead, and the error is only recorded. The fallback asset,
+    /// if any, is then spawned the next time the tree is re-spawned.
+    pub fn with_clear_on_failure(mut self, clear: bool) -> Self {
+        self.clear_on_failure = clear;
+        self
+    }
+
+    /// Returns whether this tree is cleared when its main asset fails to load.
+    pub fn clear_on_failure(&self) -> bool {
+        self.clear_on_failure
+    }
+
+    /// Makes this tree static, for user interfaces that never change once
+    /// spawned.
+    ///
+    /// The nodes of a static tree are spawned with all of their properties
+    /// applied, but they do not track changes to variables or classes, and
+    /// their [`NekoUINode`] component is removed afterwards. This saves memory
+    /// and per-frame work. The tree is still spawned again when its asset is
+    /// reloaded.
+    pub fn static_mode(mut self) -> Self {
+        self.static_mode = true;
+        self
+    }
+
+    /// Returns whether this tree is static.
+    pub fn is_static(&self) -> bool {
+        self.static_mode
+    }
+
     /// Returns a reference to the variable map.
     pub fn variables(&self) -> &HashMap<String, PropertyValue> {
         &self.variables
@@ -99,6 +268,49 @@ impl NekoUITree {
         self
     }
 
+    /// Sets the size of the root node of this tree.
+    ///
+    /// By default, the root node fills its parent, or the whole window for a
+    /// top-level tree. Other fields of the root [`Node`], such as its position,
+    /// are never modified by the tree.
+    pub fn with_root_size(mut self, width: Val, height: Val) -> Self {
+        self.root_size = Some((width, height));
+        self
+    }
+
+    /// Leaves the root [`Node`] of this tree exactly as it was inserted,
+    /// including its size.
+    ///
+    /// This is useful to size and position the tree from Rust, for example to
+    /// display it as an overlay.
+    pub fn with_custom_root(mut self) -> Self {
+        self.root_size = None;
+        self
+    }
+
+    /// Returns the size applied to the root node of this tree, if any.
+    pub fn root_size(&self) -> Option<(Val, Val)> {
+        self.root_size
+    }
+
+    /// Sets the [`FocusPolicy`] of the root node of this tree.
+    ///
+    /// With [`FocusPolicy::Pass`], interactions go through the parts of the
+    /// root node not covered by its children, so the tree does not intercept
+    /// the pointer for the game world behind it. Combine it with
+    /// [`with_root_size`](Self::with_root_size) or
+    /// [`with_custom_root`](Self::with_custom_root) to keep a small panel from
+    /// covering the whole screen.
+    pub fn with_root_focus_policy(mut self, policy: FocusPolicy) -> Self {
+        self.root_focus_policy = Some(policy);
+        self
+    }
+
+    /// Returns the focus policy applied to the root node of this tree, if any.
+    pub fn root_focus_policy(&self) -> Option<FocusPolicy> {
+        self.root_focus_policy
+    }
+
     /// Sets a variable to the specified value.
     pub fn set_variable(&mut self, name: &str, value: PropertyValue) {
         self.variables.insert(name.to_owned(), value);
@@ -106,11 +318,160 @@ impl NekoUITree {
             .insert(ScopeName::Variable(name.to_owned(), ScopeId(0)));
     }
 
+    /// Sets a variable to the specified number.
+    pub fn set_number(&mut self, name: &str, value: f64) {
+        self.set_variable(name, PropertyValue::Number(value));
+    }
+
+    /// Sets a variable to the specified length, in pixels.
+    pub fn set_pixels(&mut self, name: &str, value: f64) {
+        self.set_variable(name, PropertyValue::Pixels(value));
+    }
+
+    /// Sets a variable to the specified percentage.
+    pub fn set_percent(&mut self, name: &str, value: f64) {
+        self.set_variable(name, PropertyValue::Percent(value));
+    }
+
+    /// Sets a variable to the specified color.
+    pub fn set_color(&mut self, name: &str, value: Color) {
+        self.set_variable(name, PropertyValue::Color(value));
+    }
+
+    /// Sets a variable to the specified boolean.
+    pub fn set_bool(&mut self, name: &str, value: bool) {
+        self.set_variable(name, PropertyValue::Bool(value));
+    }
+
+    /// Sets a variable to the specified string.
+    pub fn set_string(&mut self, name: &str, value: impl Into<String>) {
+        self.set_variable(name, PropertyValue::String(value.into()));
+    }
+
+    /// Returns the current value of a global variable.
+    ///
+    /// Values set with [`set_variable`](Self::set_variable) are returned right
+    /// away, even before the tree has been updated. Otherwise, the value
+    /// evaluated from the UI asset is returned.
+    pub fn get_variable(&self, name: &str) -> Option<&PropertyValue> {
+        self.variables
+            .get(name)
+            .or_else(|| self.get_variable_resolved(name))
+    }
+
+    /// Returns the evaluated value of a global variable, as seen by the nodes
+    /// of this tree.
+    ///
+    /// Unlike [`get_variable`](Self::get_variable), this includes the values of
+    /// variables derived from other variables, but it is only updated once the
+    /// scope of the tree has been evaluated.
+    pub fn get_variable_resolved(&self, name: &str) -> Option<&PropertyValue> {
+        self.scope.get(ScopeId(0))?.get_variable(name)
+    }
+
+    /// Returns the node of this tree whose `name` property is `id`.
+    ///
+    /// Returns `None` if no node has that name, or if more than one does. The
+    /// names are indexed as nodes are updated, so nodes can only be found once
+    /// their properties have been applied.
+    pub fn find_by_id(&self, id: &str) -> Option<Entity> {
+        match self.ids.get(id)?.as_slice() {
+            [entity] => Some(*entity),
+            _ => None,
+        }
+    }
+
+    /// Updates the id index after the `name` property of `entity` changed.
+    pub(crate) fn set_id(&mut self, entity: Entity, id: Option<String>) {
+        if let Some(previous) = self.node_ids.remove(&entity)
+            && let Some(entities) = self.ids.get_mut(&previous)
+        {
+            entities.retain(|e| *e != entity);
+            if entities.is_empty() {
+                self.ids.remove(&previous);
+            }
+        }
+
+        if let Some(id) = id {
+            self.ids.entry(id.clone()).or_default().push(entity);
+            self.node_ids.insert(entity, id);
+        }
+    }
+
+    /// Returns the node of this tree whose `key` property is `key`.
+    ///
+    /// Unlike entities, keys survive the tree being re-spawned: after a
+    /// rebuild, the key maps to the node spawned in place of the previous one.
+    /// Keys are indexed as nodes are spawned, so they can be looked up as soon
+    /// as the tree is rebuilt.
+    pub fn find_by_key(&self, key: &str) -> Option<Entity> {
+        self.keys.get(key).copied()
+    }
+
+    /// Updates the key index after the `key` property of `entity` changed.
+    ///
+    /// Keys are expected to be unique within a tree, so a key already used by
+    /// another node is reassigned to `entity` with a warning.
+    pub(crate) fn set_key(&mut self, entity: Entity, key: Option<String>) {
+        if let Some(previous) = self.node_keys.remove(&entity)
+            && self.keys.get(&previous) == Some(&entity)
+        {
+            self.keys.remove(&previous);
+        }
+
+        let Some(key) = key else {
+            return;
+        };
+
+        if let Some(previous) = self.keys.insert(key.clone(), entity) {
+            self.node_keys.remove(&previous);
+            warn!("Key `{key}` is used by both {previous} and {entity}");
+        }
+        self.node_keys.insert(entity, key);
+    }
+
+    /// Clears the id and key indices, before the nodes of the tree are
+    /// spawned again.
+    pub(crate) fn clear_indices(&mut self) {
+        self.ids.clear();
+        self.keys.clear();
+        self.node_ids.clear();
+        self.node_keys.clear();
+    }
+
+    /// Shows or hides the whole tree.
+    ///
+    /// This sets the [`Visibility`] of the root node, so the nodes of the tree
+    /// are kept and their state is preserved while hidden.
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    /// Returns whether the tree is shown.
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
     /// Marks the tree as dirty, indicating that it needs to be re-spawned.
     pub fn mark_dirty(&mut self) {
         self.dirty = true;
     }
 
+    /// Forces the tree to be fully rebuilt from its asset.
+    ///
+    /// Unlike [`mark_dirty`](Self::mark_dirty), this also discards the
+    /// evaluated scope and the scope notification state of the tree. This is
+    /// useful after changing something the tree depends on outside of its
+    /// asset, such as the marker registry. Variables set from Rust are kept,
+    /// and the nodes can still be found by name or key until they are
+    /// respawned.
+    pub fn reload(&mut self) {
+        self.scope = ScopeTree::default();
+        self.update_names.clear();
+        self.scope_notification.clear();
+        self.mark_dirty();
+    }
+
     /// Clears the dirty flag.
     pub fn clear_dirty(&mut self) {
         self.dirty = false;
@@ -0,0 +1,30 @@
+//! Timings and counts collected while loading and updating NekoMaid UI trees.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+/// A resource recording the cost of the most recent NekoMaid UI operations.
+///
+/// The same timings are logged at the debug level, but this resource makes
+/// them available to the application, for example to profile large user
+/// interfaces. Each field is overwritten every time the matching operation
+/// runs.
+#[derive(Debug, Default, Clone, PartialEq, Resource)]
+pub struct NekoDiagnostics {
+    /// The time taken to parse the asset of the last spawned tree.
+    pub parse_time: Duration,
+
+    /// The number of elements spawned by the last spawned tree.
+    pub element_count: usize,
+
+    /// The time taken to spawn the last spawned tree.
+    pub spawn_time: Duration,
+
+    /// The time taken by the last update of the variables and properties of a
+    /// tree.
+    pub scope_update_time: Duration,
+
+    /// The time taken by the last update of the node components.
+    pub node_update_time: Duration,
+}
@@ -0,0 +1,139 @@
+//! This module implements font families.
+//!
+//! A font family groups the faces of a font under a single name, so layouts
+//! can select a face with properties such as `font-style` instead of loading
+//! each face by path. Given the family registered below,
+//!
+//! ```ignore
+//! app.add_font_family(
+//!     "body",
+//!     FontFamily::new(asset_server.load("fonts/body.ttf"))
+//!         .with_italic(asset_server.load("fonts/body-italic.ttf")),
+//! );
+//! ```
+//!
+//! text nodes can use the family by name.
+//!
+//! ```text
+//! layout p {
+//!     font: "body";
+//!     font-style: italic;
+//! }
+//! ```
+
+use bevy::app::App;
+use bevy::asset::{AssetServer, Handle};
+use bevy::ecs::resource::Resource;
+use bevy::log::warn;
+use bevy::platform::collections::HashMap;
+use bevy::text::Font;
+
+use crate::parse::value::FontStyle;
+
+/// The faces of a registered font family.
+#[derive(Debug, Clone)]
+pub struct FontFamily {
+    /// The regular face of the family.
+    pub normal: Handle<Font>,
+
+    /// The italic face of the family, if any.
+    pub italic: Option<Handle<Font>>,
+}
+
+impl FontFamily {
+    /// Creates a new font family with the given regular face.
+    pub fn new(normal: Handle<Font>) -> Self {
+        Self {
+            normal,
+            italic: None,
+        }
+    }
+
+    /// Sets the italic face of this family.
+    pub fn with_italic(mut self, italic: Handle<Font>) -> Self {
+        self.italic = Some(italic);
+        self
+    }
+
+    /// Returns the face of this family for the given style, or `None` if the
+    /// family has no such face.
+    pub fn face(&self, style: FontStyle) -> Option<&Handle<Font>> {
+        match style {
+            FontStyle::Normal => Some(&self.normal),
+            FontStyle::Italic => self.italic.as_ref(),
+        }
+    }
+}
+
+/// A resource holding the font selected by `font: auto`.
+///
+/// This is Bevy's built-in font unless set to another handle.
+#[derive(Debug, Clone, Default, Resource)]
+pub struct DefaultFont(pub Handle<Font>);
+
+/// A resource for managing registered font families.
+#[derive(Debug, Default, Resource)]
+pub struct FontFamilyRegistry {
+    /// Maps family names to their faces.
+    families: HashMap<String, FontFamily>,
+}
+
+impl FontFamilyRegistry {
+    /// Registers a font family under the given name, replacing any family
+    /// previously registered with that name.
+    pub fn add_family(&mut self, name: impl Into<String>, family: FontFamily) {
+        self.families.insert(name.into(), family);
+    }
+
+    /// Returns the font family registered under the given name.
+    pub fn get(&self, name: &str) -> Option<&FontFamily> {
+        self.families.get(name)
+    }
+}
+
+/// Returns the font selected by the `font` and `font-style` properties.
+///
+/// A registered family provides its face of the given style, while `auto`, or
+/// no font at all, selects the [`DefaultFont`]. Any other font is loaded as an
+/// asset.
+pub(crate) fn select_font(
+    font: Option<&str>,
+    style: FontStyle,
+    families: &FontFamilyRegistry,
+    default_font: &DefaultFont,
+    asset_server: &AssetServer,
+) -> Handle<Font> {
+    let font = font.filter(|font| !font.is_empty()).unwrap_or("auto");
+
+    match (font, families.get(font)) {
+        (_, Some(family)) => match family.face(style) {
+            Some(face) => face.clone(),
+            None => {
+                warn!("Font family '{font}' has no {style:?} face, using its normal face");
+                family.normal.clone()
+            }
+        },
+        ("auto", None) => default_font.0.clone(),
+        (_, None) => asset_server.load(font.to_owned()),
+    }
+}
+
+/// A trait to easily register font families.
+///
+/// ```ignore
+/// app.add_font_family("body", FontFamily::new(handle));
+/// ```
+pub trait FontAppExt {
+    /// Registers a font family.
+    fn add_font_family(&mut self, name: impl Into<String>, family: FontFamily) -> &mut Self;
+}
+
+impl FontAppExt for App {
+    fn add_font_family(&mut self, name: impl Into<String>, family: FontFamily) -> &mut Self {
+        self.init_resource::<FontFamilyRegistry>()
+            .world_mut()
+            .resource_mut::<FontFamilyRegistry>()
+            .add_family(name, family);
+        self
+    }
+}
@@ -2,14 +2,46 @@
 #![warn(missing_docs)]
 #![warn(clippy::missing_docs_in_private_items)]
 
+use bevy::camera::visibility::VisibilitySystems;
+use bevy::input::mouse::MouseWheel;
 use bevy::prelude::*;
 
-use crate::asset::{NekoMaidAssetLoader, NekoMaidUI};
+use crate::animation::animate_variables;
+use crate::asset::{NekoMaidAssetLoader, NekoMaidUI, NekoUILoaded};
+use crate::diagnostics::NekoDiagnostics;
+use crate::font::{DefaultFont, FontFamilyRegistry};
 use crate::marker::{MarkerAppExt, MarkerRegistry};
+use crate::render::debug::{
+    NekoDebug,
+    removed_debug_outline,
+    update_debug_labels,
+    update_debug_outlines,
+};
+use crate::render::font_size::resolve_relative_font_sizes;
+use crate::render::grid_area::place_grid_areas;
+use crate::render::inherit::{inherit_text_properties, resolve_current_color};
+use crate::render::interaction::InteractionClasses;
+use crate::render::line_clamp::clamp_text_lines;
+use crate::render::link::{NekoLinkClicked, added_link, handle_links};
+use crate::render::max_lines::scroll_max_lines;
+use crate::render::order::{child_inserted, child_replaced, order_children};
+use crate::render::scroll::scroll_with_wheel;
+use crate::render::selection::{
+    NekoTextCopied,
+    copy_text_selection,
+    replaced_text_selection,
+    update_selection_highlights,
+    update_text_selection,
+};
 use crate::render::systems::{self, removed_interactable};
+use crate::render::viewport::update_viewport;
 
+pub mod animation;
 pub mod asset;
+pub mod bind;
 pub mod components;
+pub mod diagnostics;
+pub mod font;
 pub mod marker;
 pub mod native;
 pub mod parse;
@@ -23,14 +55,31 @@ pub struct NekoMaidPlugin;
 impl Plugin for NekoMaidPlugin {
     fn build(&self, app_: &mut App) {
         app_.init_asset::<NekoMaidUI>()
+            .init_resource::<InteractionClasses>()
             .init_asset_loader::<NekoMaidAssetLoader>()
             .init_resource::<MarkerRegistry>()
+            .init_resource::<FontFamilyRegistry>()
+            .init_resource::<DefaultFont>()
+            .init_resource::<NekoDiagnostics>()
+            .init_resource::<NekoDebug>()
             .add_marker::<Interaction>()
+            .add_message::<NekoLinkClicked>()
+            .add_message::<NekoUILoaded>()
+            .add_message::<NekoTextCopied>()
+            // also registered by `InputPlugin`; adding it here lets the scroll
+            // system run in apps without input, such as headless ones
+            .add_message::<MouseWheel>()
             .add_observer(removed_interactable)
+            .add_observer(added_link)
+            .add_observer(removed_debug_outline)
+            .add_observer(child_inserted)
+            .add_observer(child_replaced)
+            .add_observer(replaced_text_selection)
             .add_systems(
                 Update,
                 (
                     (
+                        update_viewport,
                         systems::spawn_tree,
                         systems::handle_interactions,
                         systems::handle_class_changes,
@@ -40,13 +89,43 @@ impl Plugin for NekoMaidPlugin {
                     )
                         .chain()
                         .in_set(NekoMaidSystems::UpdateTree),
+                    animate_variables.before(NekoMaidSystems::UpdateTree),
+                    update_text_selection,
+                    copy_text_selection.after(update_text_selection),
+                    scroll_with_wheel,
+                    systems::update_tree_visibility.in_set(NekoMaidSystems::PropertiesApplied),
+                    inherit_text_properties.in_set(NekoMaidSystems::PropertiesApplied),
+                    resolve_relative_font_sizes
+                        .after(inherit_text_properties)
+                        .in_set(NekoMaidSystems::PropertiesApplied),
+                    resolve_current_color.in_set(NekoMaidSystems::PropertiesApplied),
+                    place_grid_areas.in_set(NekoMaidSystems::PropertiesApplied),
+                    order_children.in_set(NekoMaidSystems::PropertiesApplied),
+                    update_debug_outlines.in_set(NekoMaidSystems::PropertiesApplied),
+                    systems::strip_static_nodes.after(NekoMaidSystems::PropertiesApplied),
+                    handle_links.before(NekoMaidSystems::UpdateTree),
                     systems::update_tree.in_set(NekoMaidSystems::AssetListener),
                     systems::asset_failure.in_set(NekoMaidSystems::AssetListener),
+                    systems::asset_loaded.in_set(NekoMaidSystems::AssetListener),
                 ),
             )
+            .add_systems(
+                PostUpdate,
+                (
+                    clamp_text_lines,
+                    scroll_max_lines,
+                    update_debug_labels,
+                    update_selection_highlights,
+                )
+                    .after(bevy::ui::UiSystems::PostLayout)
+                    .after(VisibilitySystems::VisibilityPropagate),
+            )
             .configure_sets(
                 Update,
-                NekoMaidSystems::AssetListener.before(NekoMaidSystems::UpdateTree),
+                (
+                    NekoMaidSystems::AssetListener.before(NekoMaidSystems::UpdateTree),
+                    NekoMaidSystems::PropertiesApplied.after(NekoMaidSystems::UpdateTree),
+                ),
             );
     }
 }
@@ -59,4 +138,12 @@ pub enum NekoMaidSystems {
 
     /// System for listening for asset changes.
     AssetListener,
+
+    /// Systems that finish applying node properties after
+    /// [`UpdateTree`](Self::UpdateTree), such as inherited text properties,
+    /// relative font sizes, grid areas and the order of siblings.
+    ///
+    /// Systems ordered after this set observe the final components of the
+    /// nodes for the current frame, short of the computed layout.
+    PropertiesApplied,
 }
@@ -24,15 +24,39 @@
 //!     class my_marker;
 //! }
 //! ```
+//!
+//! Components built from a value are registered under a name with a
+//! constructor instead, and attached with a `component` statement. The
+//! component is rebuilt whenever its value changes, and removed if the
+//! constructor returns `None`.
+//!
+//! ```ignore
+//! #[derive(Component)]
+//! pub struct Health(f32);
+//!
+//! app.add_component("health", |value| value.as_f32().map(Health));
+//! ```
+//!
+//! ```text
+//! layout div {
+//!     component health: 100;
+//! }
+//! ```
+
+use std::sync::Arc;
 
 use bevy::app::App;
 use bevy::ecs::bundle::Bundle;
 use bevy::ecs::resource::Resource;
 use bevy::ecs::system::EntityCommands;
+use bevy::ecs::world::EntityWorldMut;
+use bevy::log::warn_once;
 use bevy::platform::collections::HashMap;
 use bevy::ui::Interaction;
 pub use neko_derive::NekoMarker;
 
+use crate::parse::value::PropertyValue;
+
 /// The marker trait. It can easily be implemented with derive.
 ///
 /// ```
@@ -70,7 +94,11 @@ impl NekoMarker for Interaction {
 }
 
 /// The marker insert/remove function.
-pub type MarkerFunction = Box<dyn Fn(&mut EntityCommands) + Send + Sync>;
+pub type MarkerFunction = Arc<dyn Fn(&mut EntityWorldMut) + Send + Sync>;
+
+/// The component function, inserting the component built from a value, or
+/// removing it if there is no value or it cannot be built from it.
+pub type ComponentFunction = Arc<dyn Fn(&mut EntityWorldMut, Option<&PropertyValue>) + Send + Sync>;
 
 /// A resource for managing registered marker types.
 #[derive(Default, Resource)]
@@ -79,6 +107,8 @@ pub struct MarkerRegistry {
     inserters: HashMap<String, Vec<MarkerFunction>>,
     /// Maps marker names to marker removers.
     removers: HashMap<String, Vec<MarkerFunction>>,
+    /// Maps component names to component functions.
+    components: HashMap<String, Vec<ComponentFunction>>,
 }
 
 impl MarkerRegistry {
@@ -87,35 +117,98 @@ impl MarkerRegistry {
         self.inserters
             .entry(T::id().to_owned())
             .or_default()
-            .push(Box::new(|entity| {
+            .push(Arc::new(|entity| {
                 entity.insert(T::new());
             }));
         self.removers
             .entry(T::id().to_owned())
             .or_default()
-            .push(Box::new(|entity| {
+            .push(Arc::new(|entity| {
                 entity.remove::<T>();
             }));
     }
 
-    /// Inserts the associated class marker components to the node entity.
-    pub fn insert(&self, mut entity: EntityCommands, class: &str) {
-        let Some(inserters) = self.inserters.get(class) else {
+    /// Registers a component built from a value, attached to the nodes with a
+    /// `component name: value;` statement.
+    ///
+    /// The component is removed from the node if `constructor` returns `None`.
+    pub fn add_component<T: Bundle>(
+        &mut self,
+        name: impl Into<String>,
+        constructor: impl Fn(&PropertyValue) -> Option<T> + Send + Sync + 'static,
+    ) {
+        self.components
+            .entry(name.into())
+            .or_default()
+            .push(Arc::new(move |entity, value| {
+                match value.and_then(&constructor) {
+                    Some(component) => entity.insert(component),
+                    None => entity.remove::<T>(),
+                };
+            }));
+    }
+
+    /// Inserts the component registered under the given name to the node
+    /// entity, built from the given value, or removes it if there is no value.
+    ///
+    /// A warning is logged once if no component is registered under the name.
+    pub fn apply_component(
+        &self,
+        mut entity: EntityCommands,
+        name: &str,
+        value: Option<PropertyValue>,
+    ) {
+        let Some(functions) = self.components.get(name).cloned() else {
+            warn_once!("No component registered under the name `{name}`");
             return;
         };
-        for f in inserters {
-            f(&mut entity);
-        }
+
+        entity.queue(move |mut entity: EntityWorldMut| {
+            for f in &functions {
+                f(&mut entity, value.as_ref());
+            }
+        });
+    }
+
+    /// Inserts the associated class marker components to the node entity.
+    pub fn insert(&self, entity: EntityCommands, class: &str) {
+        self.apply(entity, [(class, true)]);
     }
 
     /// Removes the associated class marker components from the node entity.
-    pub fn remove(&self, mut entity: EntityCommands, class: &str) {
-        let Some(removers) = self.removers.get(class) else {
+    pub fn remove(&self, entity: EntityCommands, class: &str) {
+        self.apply(entity, [(class, false)]);
+    }
+
+    /// Inserts or removes the class marker components of several classes at
+    /// once, depending on whether each class is present.
+    ///
+    /// All changes are queued as a single command on the node entity.
+    pub fn apply<'a>(
+        &self,
+        mut entity: EntityCommands,
+        classes: impl IntoIterator<Item = (&'a str, bool)>,
+    ) {
+        let functions = classes
+            .into_iter()
+            .flat_map(|(class, present)| {
+                let functions = match present {
+                    true => &self.inserters,
+                    false => &self.removers,
+                };
+                functions.get(class).into_iter().flatten().cloned()
+            })
+            .collect::<Vec<_>>();
+
+        if functions.is_empty() {
             return;
-        };
-        for f in removers {
-            f(&mut entity);
         }
+
+        entity.queue(move |mut entity: EntityWorldMut| {
+            for f in &functions {
+                f(&mut entity);
+            }
+        });
     }
 }
 
@@ -127,6 +220,14 @@ impl MarkerRegistry {
 pub trait MarkerAppExt {
     /// Registers a marker type.
     fn add_marker<T: NekoMarker + Bundle>(&mut self) -> &mut Self;
+
+    /// Registers a component built from a value, see
+    /// [`MarkerRegistry::add_component`].
+    fn add_component<T: Bundle>(
+        &mut self,
+        name: impl Into<String>,
+        constructor: impl Fn(&PropertyValue) -> Option<T> + Send + Sync + 'static,
+    ) -> &mut Self;
 }
 
 impl MarkerAppExt for App {
@@ -137,4 +238,16 @@ impl MarkerAppExt for App {
             .add_marker::<T>();
         self
     }
+
+    fn add_component<T: Bundle>(
+        &mut self,
+        name: impl Into<String>,
+        constructor: impl Fn(&PropertyValue) -> Option<T> + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.init_resource::<MarkerRegistry>()
+            .world_mut()
+            .resource_mut::<MarkerRegistry>()
+            .add_component(name, constructor);
+        self
+    }
 }
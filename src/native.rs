@@ -1,9 +1,11 @@
 //! A module that defines the native widgets.
 
+use bevy::platform::collections::HashMap;
 use bevy::prelude::*;
 use lazy_static::lazy_static;
 
 use crate::parse::widget::NativeWidget;
+use crate::render::inherit::INHERITED_PROPERTIES;
 use crate::render::spawn::{spawn_div, spawn_img, spawn_p, spawn_span};
 
 lazy_static! {
@@ -12,18 +14,57 @@ lazy_static! {
         NativeWidget {
             name: String::from("div"),
             spawn_func: spawn_div,
+            default_style: HashMap::new(),
         },
         NativeWidget {
             name: String::from("img"),
             spawn_func: spawn_img,
+            default_style: HashMap::new(),
         },
         NativeWidget {
             name: String::from("p"),
             spawn_func: spawn_p,
+            default_style: HashMap::new(),
         },
         NativeWidget {
             name: String::from("span"),
             spawn_func: spawn_span,
+            default_style: HashMap::new(),
         }
     ];
 }
+
+/// Returns the native widgets a property applies to, or `None` if the property
+/// applies to all widgets.
+///
+/// Properties set on other widgets have no effect, except for the
+/// [`INHERITED_PROPERTIES`], which any widget may set for its text children.
+pub(crate) fn property_widgets(property: &str) -> Option<&'static [&'static str]> {
+    if INHERITED_PROPERTIES.contains(&property) {
+        return None;
+    }
+
+    match property {
+        "text" | "white-space" | "line-height" | "font-smoothing" | "link" => Some(&["p", "span"]),
+        "line-clamp" | "text-overflow" | "justify" | "line-break" | "user-select" => Some(&["p"]),
+        "src"
+        | "tint"
+        | "flip-x"
+        | "flip-y"
+        | "mode"
+        | "slice-size"
+        | "slice-size-top"
+        | "slice-size-left"
+        | "slice-size-right"
+        | "slice-size-bottom"
+        | "center-scale-mode"
+        | "center-scale-stretch"
+        | "sides-scale-mode"
+        | "sides-scale-stretch"
+        | "max-corner-scale"
+        | "tile-x"
+        | "tile-y"
+        | "stretch-value" => Some(&["img"]),
+        _ => None,
+    }
+}
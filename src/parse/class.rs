@@ -3,9 +3,25 @@
 use bevy::platform::collections::HashSet;
 
 use crate::parse::context::{NekoResult, ParseContext};
+use crate::parse::property::{UnresolvedProperty, parse_unresolved_property};
 use crate::parse::style::{Selector, SelectorPart};
 use crate::parse::token::TokenType;
 
+/// The prefix of the property names under which conditional classes are stored.
+///
+/// A `class-if active: $selected;` statement is stored as the property
+/// `class-if active`, so it is re-evaluated like any other property when the
+/// variables it depends on change. The space ensures it cannot collide with a
+/// regular property.
+pub(crate) const CLASS_IF_PREFIX: &str = "class-if ";
+
+/// The classes NekoMaid applies to elements at runtime, by default.
+///
+/// Style selectors referencing them are not reported as unused, even though no
+/// layout applies them. More classes can be registered with
+/// [`NekoMaidParser::add_runtime_class`](crate::parse::NekoMaidParser::add_runtime_class).
+pub(crate) const RUNTIME_CLASSES: [&str; 3] = ["hovered", "pressed", "debug"];
+
 /// Represents a path of classes applied to a widget hierarchy.
 #[derive(Debug, Clone, PartialEq)]
 pub struct ClassPath {
@@ -38,41 +54,17 @@ impl ClassPath {
 
     /// Checks if this [`ClassPath`] matches the given [`Selector`].
     pub fn matches(&self, selector: &Selector) -> bool {
-        if self.hierarchy.len() < selector.hierarchy.len() {
-            return false;
-        }
-
-        let offset = self.hierarchy.len() - selector.hierarchy.len();
-        for depth in 0 .. selector.hierarchy.len() {
-            let class_set = &self.hierarchy[depth + offset];
-            let selector = &selector.hierarchy[depth];
-
-            if !class_set.matches(selector) {
-                return false;
-            }
-        }
-
-        true
+        match_suffix(&self.hierarchy, &selector.hierarchy, ClassSet::matches)
     }
 
     /// Checks if this [`ClassPath`] partially matches the given
     /// [`Selector`].
     pub fn partial_matches(&self, selector: &Selector) -> bool {
-        if self.hierarchy.len() < selector.hierarchy.len() {
-            return false;
-        }
-
-        let offset = self.hierarchy.len() - selector.hierarchy.len();
-        for depth in 0 .. selector.hierarchy.len() {
-            let class_set = &self.hierarchy[depth + offset];
-            let selector = &selector.hierarchy[depth];
-
-            if !class_set.partial_matches(selector) {
-                return false;
-            }
-        }
-
-        true
+        match_suffix(
+            &self.hierarchy,
+            &selector.hierarchy,
+            ClassSet::partial_matches,
+        )
     }
 
     /// Returns a reference to the i-th [`ClassSet`] in relation to the path's
@@ -138,11 +130,55 @@ impl ClassSet {
     }
 }
 
+/// Checks if the end of the given class hierarchy matches the given selector
+/// parts, using `test` to compare each individual level.
+///
+/// Selector parts marked as descendants may skip over any number of levels in
+/// the hierarchy, while all other parts must be direct children of the part
+/// before them.
+fn match_suffix(
+    hierarchy: &[ClassSet],
+    parts: &[SelectorPart],
+    test: fn(&ClassSet, &SelectorPart) -> bool,
+) -> bool {
+    let Some((part, parts)) = parts.split_last() else {
+        return true;
+    };
+
+    let Some((class_set, hierarchy)) = hierarchy.split_last() else {
+        return false;
+    };
+
+    if !test(class_set, part) {
+        return false;
+    }
+
+    if part.descendant {
+        (0 ..= hierarchy.len())
+            .rev()
+            .any(|len| match_suffix(&hierarchy[.. len], parts, test))
+    } else {
+        match_suffix(hierarchy, parts, test)
+    }
+}
+
+/// Parses a conditional class from the input and returns it as a property named
+/// with the [`CLASS_IF_PREFIX`].
+pub(super) fn parse_class_if(ctx: &mut ParseContext) -> NekoResult<UnresolvedProperty> {
+    ctx.expect(TokenType::ClassIfKeyword)?;
+    let mut property = parse_unresolved_property(ctx)?;
+    ctx.use_class(&property.name);
+    property.name = format!("{CLASS_IF_PREFIX}{}", property.name);
+
+    Ok(property)
+}
+
 /// Parses a class from the input and returns the class name as a string.
 pub(super) fn parse_class(ctx: &mut ParseContext) -> NekoResult<String> {
     ctx.expect(TokenType::ClassKeyword)?;
     let class_name = ctx.expect_as_string(TokenType::Identifier)?;
     ctx.expect(TokenType::Semicolon)?;
+    ctx.use_class(&class_name);
 
     Ok(class_name)
 }
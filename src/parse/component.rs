@@ -0,0 +1,38 @@
+//! Handles parsing for component statements, which attach registered Bevy
+//! components to the nodes of a layout.
+
+use crate::parse::context::{NekoResult, ParseContext};
+use crate::parse::property::{UnresolvedProperty, UnresolvedPropertyValue, parse_unresolved_value};
+use crate::parse::token::TokenType;
+use crate::parse::value::PropertyValue;
+
+/// The prefix of the property names under which components are stored.
+///
+/// A `component health: 100;` statement is stored as the property
+/// `component health`, so the component is rebuilt like any other property
+/// when the variables its value depends on change. The space ensures it cannot
+/// collide with a regular property.
+pub(crate) const COMPONENT_PREFIX: &str = "component ";
+
+/// Parses a component statement, such as `component health: 100;` or
+/// `component enemy;`, and returns it as a property named with the
+/// [`COMPONENT_PREFIX`].
+///
+/// Components written without a value are given the value `true`.
+pub(super) fn parse_component(ctx: &mut ParseContext) -> NekoResult<UnresolvedProperty> {
+    ctx.expect(TokenType::ComponentKeyword)?;
+    let position = ctx.next_position().unwrap_or_default();
+    let name = ctx.expect_as_string(TokenType::Identifier)?;
+
+    let value = match ctx.maybe_consume(TokenType::Colon) {
+        Some(_) => parse_unresolved_value(ctx)?,
+        None => UnresolvedPropertyValue::Constant(PropertyValue::Bool(true)),
+    };
+    ctx.expect(TokenType::Semicolon)?;
+
+    Ok(UnresolvedProperty {
+        name: format!("{COMPONENT_PREFIX}{name}"),
+        value,
+        position,
+    })
+}
@@ -3,18 +3,21 @@
 use std::iter::Peekable;
 use std::vec::IntoIter;
 
-use bevy::platform::collections::HashMap;
+use bevy::platform::collections::{HashMap, HashSet};
 use bevy::prelude::*;
 
-use crate::parse::NekoMaidParseError;
-use crate::parse::element::{NekoElementBuilder, build_tree};
+use crate::parse::class::RUNTIME_CLASSES;
+use crate::parse::element::{DEFAULT_MAX_DEPTH, NekoElementBuilder, build_tree};
 use crate::parse::layout::Layout;
 use crate::parse::module::Module;
-use crate::parse::property::UnresolvedPropertyValue;
-use crate::parse::scope::{Scope, ScopeId, ScopeTree};
-use crate::parse::style::Style;
+use crate::parse::property::{Declarations, UnresolvedProperty, UnresolvedPropertyValue};
+use crate::parse::scope::{Scope, ScopeId, ScopeName, ScopeTree};
+use crate::parse::style::{Style, widget_selector};
 use crate::parse::token::{Token, TokenPosition, TokenType, TokenValue};
+use crate::parse::value::PropertyValue;
+use crate::parse::viewport::ViewportCondition;
 use crate::parse::widget::Widget;
+use crate::parse::{NekoMaidParseError, NekoMaidParseWarning};
 
 /// Context for parsing NekoMaid UI files.
 pub(crate) struct ParseContext {
@@ -39,8 +42,47 @@ pub(crate) struct ParseContext {
     /// A list of elements imported from other modules.
     imported_elements: Vec<NekoElementBuilder>,
 
+    /// A list of layouts imported from other modules.
+    imported_layouts: Vec<Layout>,
+
     /// the name of the widget currently being parsed.
     current_widget: Option<String>,
+
+    /// The viewport condition of the `@when` block currently being parsed.
+    style_condition: Option<ViewportCondition>,
+
+    /// The warnings raised while parsing.
+    warnings: Vec<NekoMaidParseWarning>,
+
+    /// The positions of the global variables declared in this module.
+    variable_positions: HashMap<String, TokenPosition>,
+
+    /// The global variables declared in this module.
+    declarations: Declarations,
+
+    /// The global variables declared as constants, including those of imported
+    /// modules.
+    constants: HashSet<String>,
+
+    /// The maximum number of nested elements in an element tree.
+    max_depth: usize,
+
+    /// The number of layout or style blocks being parsed, nested within each
+    /// other.
+    nesting: usize,
+
+    /// The classes applied by the layouts and widgets parsed so far.
+    classes: HashSet<String>,
+
+    /// The classes referenced by style selectors, with their positions.
+    selector_classes: Vec<(String, TokenPosition)>,
+
+    /// The classes added to elements at runtime, which style selectors may
+    /// reference without any layout applying them.
+    runtime_classes: HashSet<String>,
+
+    /// The global variables replacing those declared by the module.
+    overrides: Vec<(String, UnresolvedPropertyValue)>,
 }
 
 impl ParseContext {
@@ -61,7 +103,19 @@ impl ParseContext {
             modules: HashMap::new(),
             tokens: tokens.into_iter().peekable(),
             imported_elements: Vec::new(),
+            imported_layouts: Vec::new(),
             current_widget: None,
+            style_condition: None,
+            warnings: Vec::new(),
+            variable_positions: HashMap::new(),
+            declarations: Declarations::default(),
+            constants: HashSet::new(),
+            max_depth: DEFAULT_MAX_DEPTH,
+            nesting: 0,
+            classes: HashSet::new(),
+            selector_classes: Vec::new(),
+            runtime_classes: RUNTIME_CLASSES.map(str::to_string).into(),
+            overrides: Vec::new(),
         }
     }
 
@@ -141,14 +195,169 @@ impl ParseContext {
         scope.add_variables([(name, value)]);
     }
 
+    /// Declares the global variable a bound member path, such as
+    /// `player.name`, refers to, unless it already exists.
+    ///
+    /// The variable holds an empty string until a binding sets it, see
+    /// [`crate::bind`].
+    pub(super) fn declare_bound_variable(&mut self, name: &String) {
+        let Some(scope) = self.scope_tree.get_mut(ScopeId(0)) else {
+            return;
+        };
+        if scope
+            .get(&ScopeName::Variable(name.clone(), ScopeId(0)))
+            .is_none()
+        {
+            let value = UnresolvedPropertyValue::Constant(PropertyValue::String(String::new()));
+            scope.add_variables([(name, &value)]);
+        }
+    }
+
+    /// Declares a global variable of this module, recording its position.
+    ///
+    /// Returns an error if a constant with the same name was already declared
+    /// in this module.
+    pub(super) fn declare_variable(
+        &mut self,
+        variable: UnresolvedProperty,
+        constant: bool,
+    ) -> NekoResult<()> {
+        self.declarations.declare(&variable, constant)?;
+        match constant {
+            true => self.constants.insert(variable.name.clone()),
+            false => self.constants.remove(&variable.name),
+        };
+        self.set_variable(&variable.name, &variable.value);
+        self.variable_positions
+            .insert(variable.name, variable.position);
+        Ok(())
+    }
+
     /// Creates and returns a scope that is child of the provided scope.
     pub(crate) fn create_scope(&mut self, parent: ScopeId) -> &mut Scope {
         self.scope_tree.create(Some(parent))
     }
 
+    /// Sets the maximum number of nested elements in an element tree.
+    pub(crate) fn set_max_depth(&mut self, depth: usize) {
+        self.max_depth = depth;
+    }
+
+    /// Enters a nested layout or style block.
+    ///
+    /// Returns an error if the block is nested deeper than the maximum depth,
+    /// before the recursion of the parser can overflow the stack.
+    pub(super) fn enter_block(&mut self) -> NekoResult<()> {
+        if self.nesting >= self.max_depth {
+            return Err(NekoMaidParseError::MaxDepthExceeded {
+                depth: self.max_depth,
+            });
+        }
+
+        self.nesting += 1;
+        Ok(())
+    }
+
+    /// Leaves a block entered with [`enter_block`](Self::enter_block).
+    pub(super) fn exit_block(&mut self) {
+        self.nesting -= 1;
+    }
+
+    /// Replaces the value of a global variable once the module is parsed.
+    pub(crate) fn override_variable(&mut self, name: String, value: UnresolvedPropertyValue) {
+        self.overrides.push((name, value));
+    }
+
+    /// Returns the constant value a global variable was overridden with, if
+    /// any.
+    pub(super) fn overridden_variable(&self, name: &str) -> Option<PropertyValue> {
+        let (_, value) = self.overrides.iter().rev().find(|(n, _)| n == name)?;
+        match value {
+            UnresolvedPropertyValue::Constant(value) => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    /// Returns the constant value of a global variable, as overridden or as
+    /// declared so far.
+    ///
+    /// Returns `None` if the variable is unknown or its value is not a
+    /// constant.
+    pub(super) fn constant_variable(&self, name: &str) -> Option<PropertyValue> {
+        if !self.constants.contains(name) && self.overrides.iter().any(|(n, _)| n == name) {
+            return self.overridden_variable(name);
+        }
+
+        let scope = self.scope_tree.get(ScopeId(0))?;
+        let item = scope.get(&ScopeName::Variable(name.to_string(), ScopeId(0)))?;
+        match &item.unresolved {
+            UnresolvedPropertyValue::Constant(value) => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    /// Records a class applied by a layout.
+    pub(crate) fn use_class(&mut self, class: &str) {
+        self.classes.insert(class.to_string());
+    }
+
+    /// Registers a class added to elements at runtime.
+    pub(crate) fn add_runtime_class(&mut self, class: String) {
+        self.runtime_classes.insert(class);
+    }
+
+    /// Records a class referenced by a style selector, to warn about it if no
+    /// layout applies it.
+    pub(crate) fn use_selector_class(&mut self, class: &str, position: TokenPosition) {
+        self.selector_classes.push((class.to_string(), position));
+    }
+
+    /// Warns about the classes referenced by style selectors that are never
+    /// applied, as the selectors could only match once they are added at
+    /// runtime.
+    fn warn_unused_selector_classes(&mut self) {
+        let mut reported = HashSet::new();
+        for (class, position) in std::mem::take(&mut self.selector_classes) {
+            if self.classes.contains(&class)
+                || self.runtime_classes.contains(&class)
+                || !reported.insert(class.clone())
+            {
+                continue;
+            }
+
+            self.warn(NekoMaidParseWarning::UnusedSelectorClass { class, position });
+        }
+    }
+
     /// Converts this parse context into a [`Module`].
-    pub(crate) fn into_module(self) -> NekoResult<Module> {
+    pub(crate) fn into_module(mut self) -> NekoResult<Module> {
+        self.warn_unused_selector_classes();
+
+        for (name, value) in std::mem::take(&mut self.overrides) {
+            if self.constants.contains(&name) {
+                return Err(NekoMaidParseError::ConstantOverridden { name });
+            }
+
+            let declared = self
+                .scope_tree
+                .get(ScopeId(0))
+                .and_then(|scope| scope.get(&ScopeName::Variable(name.clone(), ScopeId(0))))
+                .is_some();
+            if !declared {
+                self.warn(NekoMaidParseWarning::UndeclaredVariableOverride { name });
+                continue;
+            }
+
+            self.set_variable(&name, &value);
+        }
+
+        // default styles come first, so every style of the module overrides
+        // them.
+        let mut styles = self.default_styles();
+        styles.extend(self.styles.iter().cloned());
+
         let mut elements = self.imported_elements;
+        let mut layouts = self.imported_layouts;
 
         let global_scope_id = ScopeId(0);
         let mut scope_tree = self.scope_tree;
@@ -157,11 +366,13 @@ impl ParseContext {
             let element = build_tree(
                 global_scope_id,
                 &mut scope_tree,
-                &self.styles,
+                &styles,
                 &self.widgets,
-                layout,
+                layout.clone(),
+                self.max_depth,
             )?;
             elements.push(element);
+            layouts.push(layout);
         }
 
         scope_tree.update_dependency_graph();
@@ -171,9 +382,46 @@ impl ParseContext {
             styles: self.styles,
             widgets: self.widgets,
             elements,
+            layouts,
+            warnings: self.warnings,
+            variable_positions: self.variable_positions,
+            classes: self.classes,
+            constants: self.constants,
         })
     }
 
+    /// Creates a style for the default style of every widget that defines one,
+    /// in the order of the widget names.
+    fn default_styles(&mut self) -> Vec<Style> {
+        let mut widgets = self
+            .widgets
+            .values()
+            .map(|widget| {
+                (
+                    widget.name().to_string(),
+                    widget_selector(widget),
+                    widget.default_style(),
+                )
+            })
+            .filter(|(_, _, properties)| !properties.is_empty())
+            .collect::<Vec<_>>();
+        widgets.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+
+        widgets
+            .into_iter()
+            .map(|(_, selector, properties)| {
+                let scope = self.create_scope(ScopeId(0));
+                scope.add_properties(properties.iter());
+                Style::new(selector, scope.id(), TokenPosition::UNKNOWN)
+            })
+            .collect()
+    }
+
+    /// Records a warning about the code being parsed.
+    pub(crate) fn warn(&mut self, warning: NekoMaidParseWarning) {
+        self.warnings.push(warning);
+    }
+
     /// Gets the next token position in the token stream, or `None` if there are
     /// no more tokens.
     pub(crate) fn next_position(&mut self) -> Option<TokenPosition> {
@@ -196,11 +444,13 @@ impl ParseContext {
     /// precedence.
     pub(crate) fn add_style(&mut self, style: Style) {
         for existing_style in &mut self.styles {
-            if existing_style.selector() == style.selector() {
+            if existing_style.selector() == style.selector()
+                && existing_style.condition() == style.condition()
+            {
                 let Some(scope) = self.scope_tree.get(style.scope_id).cloned() else {
                     return;
                 };
-                let Some(existing_scope) = self.scope_tree.get_mut(style.scope_id) else {
+                let Some(existing_scope) = self.scope_tree.get_mut(existing_style.scope_id) else {
                     return;
                 };
                 existing_scope.merge(&scope);
@@ -239,11 +489,22 @@ impl ParseContext {
             }
         }
 
+        // the scopes of imported styles belong to the scope tree of their
+        // module, so they are copied into this one.
         for style in module.styles {
-            self.add_style(style);
+            let Some(properties) = module.scope.get(style.scope_id) else {
+                continue;
+            };
+            let scope = self.create_scope(ScopeId(0));
+            scope.merge(properties);
+            let scope_id = scope.id();
+            self.add_style(Style { scope_id, ..style });
         }
 
         self.imported_elements.extend(module.elements);
+        self.imported_layouts.extend(module.layouts);
+        self.classes.extend(module.classes);
+        self.constants.extend(module.constants);
 
         for (_, widget) in module.widgets {
             self.add_widget(widget);
@@ -269,6 +530,18 @@ impl ParseContext {
     pub(super) fn set_current_widget(&mut self, name: Option<String>) {
         self.current_widget = name;
     }
+
+    /// Gets the viewport condition of the `@when` block currently being
+    /// parsed, which applies to the styles declared in it.
+    pub(super) fn style_condition(&self) -> Option<ViewportCondition> {
+        self.style_condition
+    }
+
+    /// Sets the viewport condition of the `@when` block currently being
+    /// parsed.
+    pub(super) fn set_style_condition(&mut self, condition: Option<ViewportCondition>) {
+        self.style_condition = condition;
+    }
 }
 
 /// A specialized result type for NekoMaid parsing operations.
@@ -1,5 +1,6 @@
 //! A module for parsing and representing NekoMaid UI finalized elements.
 
+use bevy::math::Vec2;
 use bevy::platform::collections::{HashMap, HashSet};
 use bevy::prelude::{Deref, DerefMut};
 
@@ -7,11 +8,12 @@ use crate::parse::NekoMaidParseError;
 use crate::parse::class::{ClassPath, ClassSet};
 use crate::parse::context::NekoResult;
 use crate::parse::layout::Layout;
+use crate::parse::property::UnresolvedPropertyValue;
 use crate::parse::scope::{ScopeId, ScopeTree};
 use crate::parse::style::Style;
 use crate::parse::token::TokenPosition;
 use crate::parse::value::PropertyValue;
-use crate::parse::widget::{NativeWidget, Widget};
+use crate::parse::widget::{CustomWidget, NativeWidget, Widget};
 
 /// A temporary builder for NekoMaid UI elements for easier construction.
 #[derive(Debug, Clone, PartialEq)]
@@ -24,6 +26,9 @@ pub(crate) struct NekoElementBuilder {
 
     /// The children of this element.
     pub(crate) children: Vec<NekoElementBuilder>,
+
+    /// The position of the layout of this element in the source code.
+    pub(crate) position: TokenPosition,
 }
 
 /// A style entry in an element.
@@ -57,6 +62,14 @@ pub struct NekoElement {
     active_properties: HashMap<String, Option<usize>>,
     dirty_active_properties: bool,
 
+    /// Properties set directly on this element at runtime. These take
+    /// precedence over both local and style properties.
+    overrides: HashMap<String, PropertyValue>,
+
+    /// The logical size of the viewport, used to activate the styles declared
+    /// in `@when` blocks. `None` until the element is spawned.
+    viewport: Option<Vec2>,
+
     /// Scope id
     scope: ScopeId,
 }
@@ -74,6 +87,8 @@ impl NekoElement {
             deactivated_styles: Vec::new(),
             active_properties: HashMap::new(),
             dirty_active_properties: false,
+            overrides: HashMap::new(),
+            viewport: None,
             scope: scope_id,
         }
     }
@@ -110,10 +125,36 @@ impl NekoElement {
         }
     }
 
+    /// Replaces the classes of this element with the given set, recording
+    /// which classes were added and which were removed.
+    pub fn set_classes(&mut self, classes: HashSet<String>) {
+        let current = &self.classpath.last().classes;
+        let removed = current
+            .iter()
+            .filter(|class| !classes.contains(*class))
+            .cloned()
+            .collect::<Vec<_>>();
+        let added = classes
+            .iter()
+            .filter(|class| !current.contains(*class))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if added.is_empty() && removed.is_empty() {
+            return;
+        }
+
+        self.classpath.last_mut().classes = classes;
+        self.classpath_changed = true;
+        self.added_classes.extend(added);
+        self.removed_classes.extend(removed);
+    }
+
     /// Updates the list of active styles.
     pub fn update_active_styles(&mut self) {
         for (i, style) in self.styles.iter_mut().enumerate() {
-            let active = self.classpath.matches(style.value.selector());
+            let active = self.classpath.matches(style.value.selector())
+                && style.value.applies_to_viewport(self.viewport);
 
             if style.active != active {
                 style.active = active;
@@ -140,7 +181,8 @@ impl NekoElement {
     /// has a selector that cannot match this element, it will not be added.
     pub fn try_add_style(&mut self, style: &Style) {
         if self.classpath.partial_matches(style.selector()) {
-            let active = self.classpath.matches(style.selector());
+            let active = self.classpath.matches(style.selector())
+                && style.applies_to_viewport(self.viewport);
 
             let entry = StyleEntry {
                 value: style.clone(),
@@ -156,10 +198,45 @@ impl NekoElement {
         }
     }
 
+    /// Sets the logical size of the viewport, re-evaluating the styles of
+    /// this element declared in `@when` blocks.
+    ///
+    /// Returns whether the element has such styles, in which case their
+    /// activation is updated along with the class path.
+    pub fn set_viewport(&mut self, viewport: Vec2) -> bool {
+        if self.viewport == Some(viewport) {
+            return false;
+        }
+        self.viewport = Some(viewport);
+
+        let conditional = self.styles.iter().any(|s| s.value.condition().is_some());
+        if conditional {
+            self.classpath_changed = true;
+        }
+        conditional
+    }
+
     /// Returns the name of all active properties in this element,
     /// including indirect properties coming from styles.
     pub fn active_properties(&self) -> impl Iterator<Item = &String> {
-        self.active_properties.keys()
+        let overrides = self
+            .overrides
+            .keys()
+            .filter(|name| !self.active_properties.contains_key(*name));
+        self.active_properties.keys().chain(overrides)
+    }
+
+    /// Sets a property directly on this element, overriding the value defined
+    /// by the layout or by any style.
+    pub fn set_property(&mut self, name: &str, value: PropertyValue) {
+        self.overrides.insert(name.to_owned(), value);
+    }
+
+    /// Removes a property previously set with
+    /// [`set_property`](Self::set_property), restoring the value defined by the
+    /// layout or by the active styles. Returns the removed value, if any.
+    pub fn clear_property(&mut self, name: &str) -> Option<PropertyValue> {
+        self.overrides.remove(name)
     }
 
     /// Returns the id of the scope used by this element.
@@ -168,7 +245,7 @@ impl NekoElement {
     }
 
     /// Returns a mutable view on the element's properties given scope context.
-    pub(crate) fn view_mut<'a>(&'a mut self, scopes: &'a mut ScopeTree) -> NekoElementView<'a> {
+    pub(crate) fn view_mut<'a>(&'a mut self, scopes: &'a ScopeTree) -> NekoElementView<'a> {
         NekoElementView { el: self, scopes }
     }
 }
@@ -178,7 +255,7 @@ impl NekoElement {
 pub struct NekoElementView<'a> {
     #[deref]
     el: &'a mut NekoElement,
-    scopes: &'a mut ScopeTree,
+    scopes: &'a ScopeTree,
 }
 
 impl<'a> NekoElementView<'a> {
@@ -229,6 +306,10 @@ impl<'a> NekoElementView<'a> {
             self.update_active_properties();
         }
 
+        if let Some(value) = self.el.overrides.get(name) {
+            return Some(value);
+        }
+
         let origin = self.active_properties.get(name)?;
         match *origin {
             Some(i) => {
@@ -265,15 +346,85 @@ impl<'a> NekoElementView<'a> {
     }
 }
 
-/// Builds an element tree.
+/// The maximum number of custom widgets that can be nested within each other
+/// while building an element tree.
+///
+/// Widgets that reference themselves, directly or through other widgets, would
+/// otherwise be expanded forever. This is lower than the
+/// [`DEFAULT_MAX_DEPTH`] so such widgets are reported as recursive rather than
+/// too deep.
+const MAX_WIDGET_DEPTH: usize = 32;
+
+/// The default maximum depth of an element tree, see
+/// [`NekoMaidParser::set_max_depth`](crate::parse::NekoMaidParser::set_max_depth).
+///
+/// Parsing and building a tree recurse once per level, so the limit is kept
+/// low enough for the small stacks of the asset loading threads.
+pub const DEFAULT_MAX_DEPTH: usize = 64;
+
+/// The nesting depth of the element being built.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct BuildDepth {
+    /// The number of elements above this element, including itself.
+    element: usize,
+
+    /// The number of custom widgets expanded above this element.
+    widget: usize,
+
+    /// The maximum depth of an element.
+    max_element: usize,
+}
+
+impl BuildDepth {
+    /// Returns the depth of a child element.
+    ///
+    /// Returns an error if the child would be deeper than the maximum depth.
+    fn child(self) -> NekoResult<Self> {
+        if self.element >= self.max_element {
+            return Err(NekoMaidParseError::MaxDepthExceeded {
+                depth: self.max_element,
+            });
+        }
+
+        Ok(Self {
+            element: self.element + 1,
+            ..self
+        })
+    }
+
+    /// Returns the depth of the layout of an expanded custom widget.
+    fn expand(self) -> Self {
+        Self {
+            widget: self.widget + 1,
+            ..self
+        }
+    }
+}
+
+/// Builds an element tree, with at most `max_depth` nested elements.
 pub(super) fn build_tree(
     global_scope: ScopeId,
     scopes: &mut ScopeTree,
     styles: &[Style],
     widgets: &HashMap<String, Widget>,
     layout: Layout,
+    max_depth: usize,
 ) -> NekoResult<NekoElementBuilder> {
-    build_element(global_scope, scopes, styles, widgets, layout, None)
+    let depth = BuildDepth {
+        element: 0,
+        widget: 0,
+        max_element: max_depth,
+    };
+
+    build_element(
+        global_scope,
+        scopes,
+        styles,
+        widgets,
+        layout,
+        None,
+        depth.child()?,
+    )
 }
 
 /// Builds a [`NekoElementBuilder`] from the given styles and layout.
@@ -284,6 +435,7 @@ pub(super) fn build_element(
     widgets: &HashMap<String, Widget>,
     layout: Layout,
     classpath: Option<ClassPath>,
+    depth: BuildDepth,
 ) -> NekoResult<NekoElementBuilder> {
     let Some(widget) = widgets.get(&layout.widget) else {
         return Err(NekoMaidParseError::UnknownWidget {
@@ -307,6 +459,7 @@ pub(super) fn build_element(
             };
 
             let scope = scopes.create(Some(parent_scope));
+            scope.add_variables(layout.variables.iter());
             scope.add_properties(layout.properties.iter());
             let scope_id = scope.id();
 
@@ -329,6 +482,7 @@ pub(super) fn build_element(
                         widgets,
                         child.clone(),
                         Some(element.classpath().clone()),
+                        depth.child()?,
                     )?);
                 }
             }
@@ -337,12 +491,22 @@ pub(super) fn build_element(
                 element,
                 children,
                 native_widget: native_widget.clone(),
+                position: layout.position,
             })
         }
         Widget::Custom(custom_widget) => {
+            if depth.widget >= MAX_WIDGET_DEPTH {
+                return Err(NekoMaidParseError::WidgetRecursion {
+                    widget: custom_widget.name.clone(),
+                });
+            }
+
+            check_required_properties(custom_widget, &layout)?;
+
             let widget_scope = scopes.create(Some(parent_scope));
             widget_scope.add_variables(custom_widget.default_properties.iter());
             widget_scope.add_variables(layout.properties.iter());
+            widget_scope.add_variables(layout.variables.iter());
 
             let mut widget_layout = custom_widget.layout.clone();
             substitute_widget_slots(&mut widget_layout, layout.children_slots);
@@ -354,22 +518,61 @@ pub(super) fn build_element(
                 widgets,
                 widget_layout,
                 classpath,
+                depth.expand(),
             )
         }
     }
 }
 
+/// Checks that the given layout provides every required property of the
+/// custom widget it uses.
+///
+/// The types of constant values are checked as well. Values referencing
+/// variables are only known once the tree is evaluated, so they are accepted.
+fn check_required_properties(widget: &CustomWidget, layout: &Layout) -> NekoResult<()> {
+    for (name, &expected) in &widget.required_properties {
+        let Some(value) = layout.properties.get(name) else {
+            return Err(NekoMaidParseError::MissingRequiredProperty {
+                widget: widget.name.clone(),
+                property: name.clone(),
+            });
+        };
+
+        if let UnresolvedPropertyValue::Constant(value) = value
+            && value.value_type() != expected
+        {
+            return Err(NekoMaidParseError::InvalidPropertyType {
+                widget: widget.name.clone(),
+                property: name.clone(),
+                expected,
+                found: value.value_type(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
 /// Insert the given nodes into the slots of this layout hierarchy.
+///
+/// The inserted nodes become children of the element containing the slot, so
+/// they can read the variables bound by that element and by the widget
+/// instance, as well as the variables visible where they were written.
 pub(super) fn substitute_widget_slots(
     layout: &mut Layout,
     mut slots: HashMap<String, Vec<Layout>>,
 ) -> HashMap<String, Vec<Layout>> {
-    // the slot list is sorted in ascending order by index position.
+    // the slot list is sorted in ascending order by index position, keeping
+    // the declaration order of slots sharing the same index. this way, the
+    // children always follow the order of the widget layout, no matter in
+    // which order the caller provided them.
+    //
     // it's important to substitute the slots in the end first to
     // not mess up the indices when inserting elements to the children vector.
     //
     // by popping the slot from this layout we guarantee that it's not mistakenly
     // used twice.
+    layout.slots.sort_by_key(|slot| slot.index);
     while let Some(slot) = layout.slots.pop() {
         let layout_children = layout.get_slot_mut(slot.location);
 
@@ -0,0 +1,329 @@
+//! Formats parsed modules back into NekoMaid UI source code.
+//!
+//! Imports are flattened, so the output contains the variables, widgets,
+//! styles, and layouts of imported modules as well. Selectors of custom widgets
+//! are written in their unrolled form, using only native widgets.
+
+use std::fmt::{self, Write};
+
+use bevy::platform::collections::{HashMap, HashSet};
+
+use crate::parse::class::CLASS_IF_PREFIX;
+use crate::parse::component::COMPONENT_PREFIX;
+use crate::parse::layout::Layout;
+use crate::parse::module::{Module, is_bound_variable};
+use crate::parse::scope::ScopeId;
+use crate::parse::style::{Selector, SelectorPart, Style};
+use crate::parse::widget::{CustomWidget, Widget};
+
+/// The string used for one level of indentation.
+const INDENT: &str = "    ";
+
+impl fmt::Display for Module {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut blocks = vec![];
+
+        if let Some(global_scope) = self.scope.get(ScopeId(0)) {
+            let mut variables = String::new();
+            let declared = global_scope
+                .variables()
+                .filter(|(name, _)| !is_bound_variable(name));
+            for (name, value) in sorted(declared) {
+                let keyword = declaration_keyword(self.constants.contains(name));
+                writeln!(variables, "{keyword} {name} = {value};")?;
+            }
+            blocks.push(variables);
+        }
+
+        for widget in self.widgets_in_order() {
+            let mut block = String::new();
+            write_widget(&mut block, widget)?;
+            blocks.push(block);
+        }
+
+        for style in &self.styles {
+            let mut block = String::new();
+            self.write_style(&mut block, style)?;
+
+            if let Some(condition) = style.condition() {
+                let mut when = String::new();
+                writeln!(when, "@when {condition} {{")?;
+                for line in block.lines() {
+                    indent(&mut when, 1)?;
+                    writeln!(when, "{line}")?;
+                }
+                writeln!(when, "}}")?;
+                block = when;
+            }
+
+            blocks.push(block);
+        }
+
+        for layout in &self.layouts {
+            let mut block = String::new();
+            write_layout(&mut block, layout, "layout", 0)?;
+            blocks.push(block);
+        }
+
+        let blocks = blocks
+            .into_iter()
+            .filter(|block| !block.is_empty())
+            .collect::<Vec<_>>();
+        write!(f, "{}", blocks.join("\n"))
+    }
+}
+
+impl Module {
+    /// Returns the custom widgets of this module, sorted by name, with every
+    /// widget placed after the widgets its layout uses.
+    fn widgets_in_order(&self) -> Vec<&CustomWidget> {
+        fn visit<'a>(
+            name: &str,
+            widgets: &'a HashMap<String, Widget>,
+            visited: &mut HashSet<String>,
+            order: &mut Vec<&'a CustomWidget>,
+        ) {
+            let Some(Widget::Custom(widget)) = widgets.get(name) else {
+                return;
+            };
+            if !visited.insert(name.to_string()) {
+                return;
+            }
+
+            let mut used = vec![];
+            used_widgets(&widget.layout, &mut used);
+            used.sort();
+            for dependency in used {
+                visit(dependency, widgets, visited, order);
+            }
+
+            order.push(widget);
+        }
+
+        let mut names = self.widgets.keys().collect::<Vec<_>>();
+        names.sort();
+
+        let mut visited = HashSet::new();
+        let mut order = vec![];
+        for name in names {
+            visit(name, &self.widgets, &mut visited, &mut order);
+        }
+
+        order
+    }
+
+    /// Writes a style, nesting a `with` block for every part of its selector
+    /// after the first.
+    fn write_style(&self, out: &mut String, style: &Style) -> fmt::Result {
+        let Some(scope) = self.scope.get(style.scope_id) else {
+            return Ok(());
+        };
+        let Selector { hierarchy } = style.selector();
+
+        for (depth, part) in hierarchy.iter().enumerate() {
+            let keyword = if depth == 0 { "style" } else { "with" };
+            indent(out, depth)?;
+            write!(out, "{keyword} ")?;
+            write_selector_part(out, part)?;
+            writeln!(out, " {{")?;
+        }
+
+        for (name, value) in sorted(scope.properties()) {
+            indent(out, hierarchy.len())?;
+            writeln!(out, "{name}: {value};")?;
+        }
+
+        for depth in (0 .. hierarchy.len()).rev() {
+            indent(out, depth)?;
+            writeln!(out, "}}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes a custom widget definition.
+fn write_widget(out: &mut String, widget: &CustomWidget) -> fmt::Result {
+    writeln!(out, "def {} {{", widget.name)?;
+
+    for (name, value) in sorted(widget.default_properties.iter()) {
+        indent(out, 1)?;
+        writeln!(out, "var {name} = {value};")?;
+    }
+
+    for (name, property_type) in sorted(widget.required_properties.iter()) {
+        indent(out, 1)?;
+        writeln!(out, "property {name}: {property_type};")?;
+    }
+
+    if !widget.default_properties.is_empty() || !widget.required_properties.is_empty() {
+        writeln!(out)?;
+    }
+
+    if !widget.default_style.is_empty() {
+        indent(out, 1)?;
+        writeln!(out, "style {{")?;
+        for (name, value) in sorted(widget.default_style.iter()) {
+            indent(out, 2)?;
+            writeln!(out, "{name}: {value};")?;
+        }
+        indent(out, 1)?;
+        writeln!(out, "}}")?;
+        writeln!(out)?;
+    }
+
+    write_layout(out, &widget.layout, "layout", 1)?;
+    writeln!(out, "}}")
+}
+
+/// Writes a layout, introduced by the given keyword, and all of its children.
+fn write_layout(out: &mut String, layout: &Layout, keyword: &str, depth: usize) -> fmt::Result {
+    indent(out, depth)?;
+    writeln!(out, "{keyword} {} {{", layout.widget)?;
+
+    let mut classes = layout.classes.iter().collect::<Vec<_>>();
+    classes.sort();
+
+    for (name, value) in sorted(layout.variables.iter()) {
+        let keyword = declaration_keyword(layout.constants.contains(name));
+        indent(out, depth + 1)?;
+        writeln!(out, "{keyword} {name} = {value};")?;
+    }
+
+    for class in &classes {
+        indent(out, depth + 1)?;
+        writeln!(out, "class {class};")?;
+    }
+
+    for (name, value) in sorted(layout.properties.iter()) {
+        indent(out, depth + 1)?;
+        if let Some(class) = name.strip_prefix(CLASS_IF_PREFIX) {
+            writeln!(out, "class-if {class}: {value};")?;
+        } else if let Some(component) = name.strip_prefix(COMPONENT_PREFIX) {
+            writeln!(out, "component {component}: {value};")?;
+        } else {
+            writeln!(out, "{name}: {value};")?;
+        }
+    }
+
+    let mut locations = layout
+        .children_slots
+        .keys()
+        .chain(layout.slots.iter().map(|slot| &slot.location))
+        .collect::<Vec<_>>();
+    locations.sort();
+    locations.dedup();
+
+    let has_declarations =
+        !layout.variables.is_empty() || !classes.is_empty() || !layout.properties.is_empty();
+    if has_declarations && !locations.is_empty() {
+        writeln!(out)?;
+    }
+
+    for (i, location) in locations.into_iter().enumerate() {
+        if i > 0 {
+            writeln!(out)?;
+        }
+
+        if location == "default" {
+            write_slot_content(out, layout, location, depth + 1)?;
+        } else {
+            indent(out, depth + 1)?;
+            writeln!(out, "in {location} {{")?;
+            write_slot_content(out, layout, location, depth + 2)?;
+            indent(out, depth + 1)?;
+            writeln!(out, "}}")?;
+        }
+    }
+
+    indent(out, depth)?;
+    writeln!(out, "}}")
+}
+
+/// Writes the children placed into an input slot of a layout, along with the
+/// output slots positioned between them.
+fn write_slot_content(
+    out: &mut String,
+    layout: &Layout,
+    location: &str,
+    depth: usize,
+) -> fmt::Result {
+    let children = layout.get_slot(location);
+    let slots = layout
+        .slots
+        .iter()
+        .filter(|slot| slot.location == location)
+        .collect::<Vec<_>>();
+
+    for index in 0 ..= children.len() {
+        for slot in slots.iter().filter(|slot| slot.index == index) {
+            indent(out, depth)?;
+            match (slot.name.as_str(), slot.required) {
+                ("default", false) => writeln!(out, "output;")?,
+                (name, false) => writeln!(out, "output {name};")?,
+                (name, true) => writeln!(out, "output required {name};")?,
+            }
+        }
+
+        if let Some(child) = children.get(index) {
+            write_layout(out, child, "with", depth)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes a selector part, such as `** div +active !hidden`.
+fn write_selector_part(out: &mut String, part: &SelectorPart) -> fmt::Result {
+    if part.descendant {
+        write!(out, "** ")?;
+    }
+    write!(out, "{}", part.widget)?;
+
+    let mut whitelist = part.whitelist.iter().collect::<Vec<_>>();
+    whitelist.sort();
+    for class in whitelist {
+        write!(out, " +{class}")?;
+    }
+
+    let mut blacklist = part.blacklist.iter().collect::<Vec<_>>();
+    blacklist.sort();
+    for class in blacklist {
+        write!(out, " !{class}")?;
+    }
+
+    Ok(())
+}
+
+/// Returns the keyword declaring a variable, `const` for constants and `var`
+/// otherwise.
+fn declaration_keyword(constant: bool) -> &'static str {
+    match constant {
+        true => "const",
+        false => "var",
+    }
+}
+
+/// Collects the names of the widgets used by a layout and its children.
+fn used_widgets<'a>(layout: &'a Layout, used: &mut Vec<&'a str>) {
+    used.push(&layout.widget);
+    for child in layout.children_slots.values().flatten() {
+        used_widgets(child, used);
+    }
+}
+
+/// Sorts properties or variables by name, so they are always written in the
+/// same order.
+fn sorted<'a, T>(items: impl Iterator<Item = (&'a String, T)>) -> Vec<(&'a String, T)> {
+    let mut items = items.collect::<Vec<_>>();
+    items.sort_by_key(|(name, _)| *name);
+    items
+}
+
+/// Writes the indentation for the given depth.
+fn indent(out: &mut String, depth: usize) -> fmt::Result {
+    for _ in 0 .. depth {
+        out.write_str(INDENT)?;
+    }
+    Ok(())
+}
@@ -1,41 +1,160 @@
 //! Handles parsing for import statements and predicts the imports required by a
 //! set of tokens.
 
+use crate::parse::NekoMaidParseError;
 use crate::parse::context::{NekoResult, ParseContext};
-use crate::parse::token::{Token, TokenType, TokenValue};
+use crate::parse::property::{UnresolvedPropertyValue, parse_single_value};
+use crate::parse::token::{Token, TokenPosition, TokenType};
+use crate::parse::value::PropertyValue;
+
+/// An import statement, such as `import "ui/common";`.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Import {
+    /// The path of the imported module.
+    pub path: String,
+
+    /// The position of the path in the source code.
+    pub position: TokenPosition,
+
+    /// The condition the import is guarded by, if any.
+    pub condition: Option<ImportCondition>,
+}
+
+/// The condition guarding an import, such as `if $platform == "mobile"`.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct ImportCondition {
+    /// The name of the variable the condition tests.
+    pub variable: String,
+
+    /// The position of the variable in the source code.
+    pub position: TokenPosition,
+
+    /// The value the variable is compared to.
+    pub value: UnresolvedPropertyValue,
+
+    /// Whether the variable must be equal to the value (`==`), or different
+    /// from it (`!=`).
+    pub equal: bool,
+}
+
+impl ImportCondition {
+    /// Evaluates this condition, looking up variables with `lookup`.
+    ///
+    /// Returns `None` if a variable could not be resolved.
+    pub fn evaluate(&self, lookup: &impl Fn(&String) -> Option<PropertyValue>) -> Option<bool> {
+        let variable = lookup(&self.variable)?;
+        let value = self.value.resolve(lookup)?;
+        Some((variable == value) == self.equal)
+    }
+}
 
 /// Predicts the imports required by the given tokens.
 ///
 /// This function is not guaranteed to be accurate if the tokens are malformed.
-pub(super) fn predict_imports(tokens: &[Token]) -> Vec<String> {
+pub(super) fn predict_imports(tokens: &[Token]) -> Vec<Import> {
     let mut imports = Vec::new();
 
-    for i in 0 .. tokens.len() - 1 {
-        if tokens[i].token_type != TokenType::ImportKeyword {
-            continue;
-        }
-
-        if tokens[i + 1].token_type != TokenType::StringLiteral {
+    for (i, token) in tokens.iter().enumerate() {
+        if token.token_type != TokenType::ImportKeyword {
             continue;
         }
 
-        let TokenValue::String(name) = &tokens[i + 1].value else {
+        let Some(length) = tokens[i ..]
+            .iter()
+            .position(|t| t.token_type == TokenType::Semicolon)
+        else {
             continue;
         };
 
-        imports.push(name.clone());
+        let statement = tokens[i ..= i + length].to_vec();
+        if let Ok(import) = parse_import_statement(&mut ParseContext::new(statement)) {
+            imports.push(import);
+        }
     }
 
     imports
 }
 
-/// Parses an import statement from the token stream an attempts to import it.
-pub(super) fn parse_import(ctx: &mut ParseContext) -> NekoResult<()> {
+/// Parses an import statement, along with its condition.
+fn parse_import_statement(ctx: &mut ParseContext) -> NekoResult<Import> {
     ctx.expect(TokenType::ImportKeyword)?;
-    let path_pos = ctx.next_position().unwrap_or_default();
+    let position = ctx.next_position().unwrap_or_default();
     let path = ctx.expect_as_string(TokenType::StringLiteral)?;
+
+    let condition = match ctx.maybe_consume(TokenType::IfKeyword) {
+        Some(_) => Some(parse_import_condition(ctx)?),
+        None => None,
+    };
+
     ctx.expect(TokenType::Semicolon)?;
 
-    ctx.import_module(&path, path_pos)?;
+    Ok(Import {
+        path,
+        position,
+        condition,
+    })
+}
+
+/// Parses the condition of an import, following its `if` keyword.
+fn parse_import_condition(ctx: &mut ParseContext) -> NekoResult<ImportCondition> {
+    let position = ctx.next_position().unwrap_or_default();
+    let variable = ctx.expect_as_string(TokenType::Variable)?;
+
+    let equal = match ctx.peek().map(|token| token.token_type) {
+        Some(TokenType::DoubleEquals) => true,
+        Some(TokenType::NotEquals) => false,
+        _ => {
+            let next = ctx.consume()?;
+            return Err(NekoMaidParseError::UnexpectedToken {
+                expected: vec![
+                    TokenType::DoubleEquals.type_name().to_string(),
+                    TokenType::NotEquals.type_name().to_string(),
+                ],
+                found: next.token_type.type_name().to_string(),
+                position: next.position,
+            });
+        }
+    };
+    ctx.consume()?;
+
+    let value = parse_single_value(ctx)?;
+
+    Ok(ImportCondition {
+        variable,
+        position,
+        value,
+        equal,
+    })
+}
+
+/// Parses an import statement from the token stream an attempts to import it.
+///
+/// Imports whose condition does not hold are skipped. The variables of the
+/// condition are looked up in the overridden and previously declared global
+/// variables, and must have a constant value.
+pub(super) fn parse_import(ctx: &mut ParseContext) -> NekoResult<()> {
+    let import = parse_import_statement(ctx)?;
+
+    if let Some(condition) = &import.condition {
+        let lookup = |name: &String| ctx.constant_variable(name);
+        let Some(holds) = condition.evaluate(&lookup) else {
+            let variable = [&condition.variable]
+                .into_iter()
+                .chain(condition.value.variables())
+                .find(|name| lookup(name).is_none())
+                .unwrap_or(&condition.variable);
+
+            return Err(NekoMaidParseError::VariableNotFound {
+                variable: variable.clone(),
+                position: condition.position,
+            });
+        };
+
+        if !holds {
+            return Ok(());
+        }
+    }
+
+    ctx.import_module(&import.path, import.position)?;
     Ok(())
 }
@@ -3,11 +3,20 @@
 use bevy::platform::collections::{HashMap, HashSet};
 use lazy_static::lazy_static;
 
-use crate::parse::NekoMaidParseError;
-use crate::parse::class::parse_class;
+use crate::native::property_widgets;
+use crate::parse::class::{parse_class, parse_class_if};
+use crate::parse::component::parse_component;
 use crate::parse::context::{NekoResult, ParseContext};
-use crate::parse::property::{UnresolvedPropertyValue, parse_unresolved_property};
-use crate::parse::token::{TokenType, TokenValue};
+use crate::parse::property::{
+    Declarations,
+    UnresolvedPropertyValue,
+    parse_declaration,
+    parse_unresolved_property,
+    record_property_position,
+};
+use crate::parse::token::{TokenPosition, TokenType, TokenValue};
+use crate::parse::widget::Widget;
+use crate::parse::{NekoMaidParseError, NekoMaidParseWarning};
 
 /// A slot in a layout.
 #[derive(Clone, Debug, PartialEq)]
@@ -18,6 +27,9 @@ pub struct Slot {
     pub location: String,
     /// The index in `location` this slot is positioned.
     pub index: usize,
+    /// Whether the widget must receive content in this slot wherever it is
+    /// used, declared with `output required <name>;`.
+    pub required: bool,
 }
 
 lazy_static! {
@@ -30,9 +42,19 @@ pub(crate) struct Layout {
     /// The widget type.
     pub(crate) widget: String,
 
+    /// The position of the layout in the source code.
+    pub(crate) position: TokenPosition,
+
     /// The properties of the layout.
     pub(crate) properties: HashMap<String, UnresolvedPropertyValue>,
 
+    /// The variables bound by this layout. They are visible to all of its
+    /// descendants, including the content placed into its output slots.
+    pub(crate) variables: HashMap<String, UnresolvedPropertyValue>,
+
+    /// The variables bound by this layout that are declared as constants.
+    pub(crate) constants: HashSet<String>,
+
     /// The children by input slot. Each key should be a
     /// valid slot in the widget's layout.
     pub(crate) children_slots: HashMap<String, Vec<Layout>>,
@@ -46,10 +68,13 @@ pub(crate) struct Layout {
 
 impl Layout {
     /// Create a new layout.
-    pub fn new(widget: String) -> Self {
+    pub fn new(widget: String, position: TokenPosition) -> Self {
         Self {
             widget,
+            position,
             properties: HashMap::new(),
+            variables: HashMap::new(),
+            constants: HashSet::new(),
             children_slots: HashMap::new(),
             classes: HashSet::new(),
             slots: vec![],
@@ -70,6 +95,15 @@ impl Layout {
 
 /// Parses a layout from the input and returns a [`Layout`].
 pub(super) fn parse_layout(ctx: &mut ParseContext) -> NekoResult<Layout> {
+    ctx.enter_block()?;
+    let layout = parse_layout_block(ctx);
+    ctx.exit_block();
+    layout
+}
+
+/// Parses the widget and the body of a layout.
+fn parse_layout_block(ctx: &mut ParseContext) -> NekoResult<Layout> {
+    let position = ctx.next_position().unwrap_or_default();
     ctx.maybe_consume(TokenType::LayoutKeyword);
     ctx.maybe_consume(TokenType::WithKeyword);
 
@@ -83,7 +117,10 @@ pub(super) fn parse_layout(ctx: &mut ParseContext) -> NekoResult<Layout> {
         });
     };
 
-    let mut layout = Layout::new(widget.clone());
+    let mut layout = Layout::new(widget.clone(), position);
+    let mut slot_positions = HashMap::new();
+    let mut property_positions = HashMap::new();
+    let mut declarations = Declarations::default();
 
     ctx.expect(TokenType::OpenBrace)?;
 
@@ -91,23 +128,63 @@ pub(super) fn parse_layout(ctx: &mut ParseContext) -> NekoResult<Layout> {
         match next.token_type {
             TokenType::Identifier => {
                 let property = parse_unresolved_property(ctx)?;
+                check_property_widget(ctx, &widget, &property.name, next.position);
+                record_property_position(
+                    ctx,
+                    &mut property_positions,
+                    &property.name,
+                    next.position,
+                );
                 layout.properties.insert(property.name, property.value);
             }
+            TokenType::VarKeyword | TokenType::ConstKeyword => {
+                let (variable, constant) = parse_declaration(ctx)?;
+                declarations.declare(&variable, constant)?;
+                match constant {
+                    true => layout.constants.insert(variable.name.clone()),
+                    false => layout.constants.remove(&variable.name),
+                };
+                layout.variables.insert(variable.name, variable.value);
+            }
             TokenType::ClassKeyword => {
                 let class = parse_class(ctx)?;
                 layout.classes.insert(class);
             }
+            TokenType::ClassIfKeyword => {
+                let property = parse_class_if(ctx)?;
+                record_property_position(
+                    ctx,
+                    &mut property_positions,
+                    &property.name,
+                    next.position,
+                );
+                layout.properties.insert(property.name, property.value);
+            }
+            TokenType::ComponentKeyword => {
+                let property = parse_component(ctx)?;
+                record_property_position(
+                    ctx,
+                    &mut property_positions,
+                    &property.name,
+                    next.position,
+                );
+                layout.properties.insert(property.name, property.value);
+            }
             TokenType::WithKeyword => {
+                slot_positions
+                    .entry("default".to_string())
+                    .or_insert(next.position);
                 let child_layout = parse_layout(ctx)?;
                 let children = layout.get_slot_mut("default".to_string());
                 children.push(child_layout);
             }
             TokenType::OutputKeyword => {
-                let name = parse_slot(ctx)?;
+                let (name, required) = parse_slot(ctx)?;
                 layout.slots.push(Slot {
                     name,
                     location: "default".to_string(),
                     index: layout.get_slot("default").len(),
+                    required,
                 });
             }
             TokenType::InKeyword => {
@@ -126,6 +203,7 @@ pub(super) fn parse_layout(ctx: &mut ParseContext) -> NekoResult<Layout> {
                         position: in_position,
                     });
                 }
+                slot_positions.insert(slot_name.clone(), in_position);
                 layout.children_slots.insert(slot_name, children);
                 layout.slots.extend(slots);
             }
@@ -134,7 +212,11 @@ pub(super) fn parse_layout(ctx: &mut ParseContext) -> NekoResult<Layout> {
                 return Err(NekoMaidParseError::UnexpectedToken {
                     expected: vec![
                         TokenType::Identifier.type_name().to_string(),
+                        TokenType::VarKeyword.type_name().to_string(),
+                        TokenType::ConstKeyword.type_name().to_string(),
                         TokenType::ClassKeyword.type_name().to_string(),
+                        TokenType::ClassIfKeyword.type_name().to_string(),
+                        TokenType::ComponentKeyword.type_name().to_string(),
                         TokenType::WithKeyword.type_name().to_string(),
                         TokenType::OutputKeyword.type_name().to_string(),
                         TokenType::InKeyword.type_name().to_string(),
@@ -148,11 +230,91 @@ pub(super) fn parse_layout(ctx: &mut ParseContext) -> NekoResult<Layout> {
     }
 
     ctx.expect(TokenType::CloseBrace)?;
+
+    validate_input_slots(ctx, &layout, &slot_positions, widget_position)?;
+
     Ok(layout)
 }
 
-/// Parses a slot statement.
-pub(super) fn parse_slot(ctx: &mut ParseContext) -> NekoResult<String> {
+/// Warns if a property is set on a native widget it has no effect on.
+fn check_property_widget(
+    ctx: &mut ParseContext,
+    widget: &str,
+    property: &str,
+    position: TokenPosition,
+) {
+    let Some(Widget::Native(_)) = ctx.get_widget(widget) else {
+        return;
+    };
+    let Some(applies_to) = property_widgets(property) else {
+        return;
+    };
+    if applies_to.contains(&widget) {
+        return;
+    }
+
+    ctx.warn(NekoMaidParseWarning::InapplicableProperty {
+        property: property.to_string(),
+        widget: widget.to_string(),
+        applies_to: applies_to.iter().map(|w| w.to_string()).collect(),
+        position,
+    });
+}
+
+/// Validates that every input slot provided to a custom widget matches one of
+/// the output slots declared by that widget, and warns if a required output
+/// slot of the widget receives no content.
+///
+/// These checks run where the widget is used, while the slots themselves are
+/// validated where the widget is defined (see `validate_layout_slots`).
+fn validate_input_slots(
+    ctx: &mut ParseContext,
+    layout: &Layout,
+    positions: &HashMap<String, TokenPosition>,
+    widget_position: TokenPosition,
+) -> NekoResult<()> {
+    let Some(Widget::Custom(widget)) = ctx.get_widget(&layout.widget) else {
+        return Ok(());
+    };
+    let outputs = widget.outputs();
+
+    for slot in layout.children_slots.keys() {
+        if !widget.has_output(slot) {
+            return Err(NekoMaidParseError::UnknownSlot {
+                slot: slot.clone(),
+                widget: layout.widget.clone(),
+                outputs: outputs.iter().map(|output| output.to_string()).collect(),
+                position: positions.get(slot).copied().unwrap_or_default(),
+            });
+        }
+    }
+
+    // content forwarded to an output slot of the enclosing widget counts as
+    // content, as it is only known where that widget is used.
+    let empty = widget
+        .required_outputs()
+        .into_iter()
+        .filter(|output| {
+            layout.get_slot(output).is_empty()
+                && !layout.slots.iter().any(|slot| slot.location == *output)
+        })
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+
+    for slot in empty {
+        ctx.warn(NekoMaidParseWarning::EmptyOutputSlot {
+            widget: layout.widget.clone(),
+            slot,
+            position: widget_position,
+        });
+    }
+
+    Ok(())
+}
+
+/// Parses a slot statement, returning the name of the slot and whether it is
+/// required.
+pub(super) fn parse_slot(ctx: &mut ParseContext) -> NekoResult<(String, bool)> {
     let token = ctx.expect(TokenType::OutputKeyword)?;
 
     if ctx.get_current_widget().is_none() {
@@ -161,17 +323,28 @@ pub(super) fn parse_slot(ctx: &mut ParseContext) -> NekoResult<String> {
         });
     }
 
-    let name = ctx
-        .maybe_consume(TokenType::Identifier)
-        .and_then(|t| match t.value {
-            TokenValue::String(s) => Some(s),
-            _ => None,
-        })
-        .unwrap_or("default".to_string());
+    let identifier = |ctx: &mut ParseContext| {
+        ctx.maybe_consume(TokenType::Identifier)
+            .and_then(|t| match t.value {
+                TokenValue::String(s) => Some(s),
+                _ => None,
+            })
+    };
+
+    // `required` is only a modifier when followed by the slot name, so slots
+    // can still be named `required`.
+    let mut name = identifier(ctx);
+    let required = name.as_deref() == Some("required")
+        && ctx
+            .peek()
+            .is_some_and(|t| t.token_type == TokenType::Identifier);
+    if required {
+        name = identifier(ctx);
+    }
 
     ctx.expect(TokenType::Semicolon)?;
 
-    Ok(name)
+    Ok((name.unwrap_or("default".to_string()), required))
 }
 
 /// A parsed in statement.
@@ -202,11 +375,12 @@ pub(super) fn parse_in(ctx: &mut ParseContext) -> NekoResult<InStatement> {
                 children.push(child_layout);
             }
             TokenType::OutputKeyword => {
-                let name = parse_slot(ctx)?;
+                let (name, required) = parse_slot(ctx)?;
                 slots.push(Slot {
                     name,
                     location: slot_name.clone(),
                     index: children.len(),
+                    required,
                 });
             }
             TokenType::CloseBrace => break,
@@ -2,15 +2,18 @@
 //! It provides functions to read and interpret `.neko_ui` files.
 
 use crate::parse::context::{NekoResult, ParseContext};
-use crate::parse::import::predict_imports;
+use crate::parse::import::{Import, predict_imports};
 use crate::parse::module::Module;
-use crate::parse::token::TokenPosition;
+use crate::parse::property::{PropertyType, parse_unresolved_value};
+use crate::parse::token::{TokenPosition, TokenType};
 use crate::parse::tokenizer::{TokenizeError, Tokenizer};
 use crate::parse::widget::{NativeWidget, Widget};
 
 pub mod class;
+pub mod component;
 pub mod context;
 pub mod element;
+pub mod format;
 pub mod import;
 pub mod layout;
 pub mod module;
@@ -20,6 +23,7 @@ pub mod style;
 pub mod token;
 pub mod tokenizer;
 pub mod value;
+pub mod viewport;
 pub mod widget;
 
 #[cfg(test)]
@@ -31,7 +35,7 @@ pub struct NekoMaidParser {
     context: ParseContext,
 
     /// The predicted imports required by the tokens.
-    imports: Vec<String>,
+    imports: Vec<Import>,
 }
 
 impl NekoMaidParser {
@@ -51,12 +55,82 @@ impl NekoMaidParser {
         self.context.add_widget(Widget::Native(widget));
     }
 
+    /// Overrides the value of a global variable of the parsed module.
+    ///
+    /// The value is written like in a NekoMaid UI file, such as `#ff0000`,
+    /// `10px` or `"Hello"`, and replaces the value declared by the module once
+    /// it is parsed. Returns an error if the value cannot be parsed, or if it
+    /// references another variable, as overrides must be constant.
+    ///
+    /// Overriding a constant makes [`finish`](Self::finish) fail with
+    /// [`NekoMaidParseError::ConstantOverridden`], and overriding a variable
+    /// the module does not declare is ignored with a
+    /// [`NekoMaidParseWarning::UndeclaredVariableOverride`].
+    pub fn override_variable(&mut self, name: &str, value: &str) -> NekoResult<()> {
+        let tokens = Tokenizer::tokenize(&format!("{value};"))?;
+        let mut ctx = ParseContext::new(tokens);
+        let value = parse_unresolved_value(&mut ctx)?;
+        ctx.expect(TokenType::Semicolon)?;
+
+        if let Some(next) = ctx.peek() {
+            return Err(NekoMaidParseError::UnexpectedToken {
+                expected: vec![TokenType::EndOfStream.type_name().to_string()],
+                found: next.token_type.type_name().to_string(),
+                position: next.position,
+            });
+        }
+
+        if !value.variables().is_empty() {
+            return Err(NekoMaidParseError::NonConstantOverride {
+                name: name.to_string(),
+            });
+        }
+
+        self.context.override_variable(name.to_string(), value);
+        Ok(())
+    }
+
+    /// Sets the maximum number of nested elements in the element trees built by
+    /// this parser, [`DEFAULT_MAX_DEPTH`](element::DEFAULT_MAX_DEPTH) by
+    /// default.
+    ///
+    /// Deeper trees, such as those produced by expanding deeply nested widgets,
+    /// fail with [`NekoMaidParseError::MaxDepthExceeded`]. So do layouts and
+    /// styles nested deeper than this in the source.
+    pub fn set_max_depth(&mut self, depth: usize) {
+        self.context.set_max_depth(depth);
+    }
+
+    /// Registers a class added to elements at runtime, such as with
+    /// [`NekoUINode::add_class`](crate::components::NekoUINode::add_class).
+    ///
+    /// Style selectors referencing the class are not reported with
+    /// [`NekoMaidParseWarning::UnusedSelectorClass`], even though no layout
+    /// applies it.
+    pub fn add_runtime_class(&mut self, class: impl Into<String>) {
+        self.context.add_runtime_class(class.into());
+    }
+
     /// Predicts the imports required by the given tokens.
     ///
+    /// Conditional imports are skipped if their condition does not hold for
+    /// the variables overridden so far with
+    /// [`override_variable`](Self::override_variable). Imports whose condition
+    /// depends on other variables are always included.
+    ///
     /// This function is not guaranteed to be accurate if the tokens are
     /// malformed.
-    pub fn predict_imports(&self) -> &Vec<String> {
-        &self.imports
+    pub fn predict_imports(&self) -> Vec<String> {
+        let lookup = |name: &String| self.context.overridden_variable(name);
+
+        self.imports
+            .iter()
+            .filter(|import| {
+                let condition = import.condition.as_ref();
+                condition.is_none_or(|condition| condition.evaluate(&lookup) != Some(false))
+            })
+            .map(|import| import.path.clone())
+            .collect()
     }
 
     /// Adds a module to this parser's context under the given name.
@@ -152,6 +226,108 @@ pub enum NekoMaidParseError {
         position: TokenPosition,
     },
 
+    /// An error indicating that a widget extends a widget that is not a custom
+    /// widget.
+    #[error(
+        "Widget '{widget}' at {position} cannot extend '{base}', only custom widgets can be extended"
+    )]
+    InvalidWidgetBase {
+        /// The name of the widget being defined.
+        widget: String,
+
+        /// The name of the widget being extended.
+        base: String,
+
+        /// The position of the base widget reference in the source code.
+        position: TokenPosition,
+    },
+
+    /// An error indicating that a widget contains itself, either directly or
+    /// through other widgets.
+    #[error("Widget '{widget}' is recursive, it cannot contain itself")]
+    WidgetRecursion {
+        /// The name of the widget that was nested too deeply.
+        widget: String,
+    },
+
+    /// An error indicating that an element tree is nested deeper than the
+    /// maximum depth of the parser.
+    #[error("Element tree exceeds the maximum depth of {depth}")]
+    MaxDepthExceeded {
+        /// The maximum depth that was exceeded.
+        depth: usize,
+    },
+
+    /// An error indicating that a constant was declared again in the same
+    /// scope.
+    #[error("Constant '{name}' at {position} was already declared at {previous}")]
+    ConstantRedeclared {
+        /// The name of the constant.
+        name: String,
+
+        /// The position of the constant declaration in the source code.
+        previous: TokenPosition,
+
+        /// The position of the repeated declaration in the source code.
+        position: TokenPosition,
+    },
+
+    /// An error indicating that a global constant was overridden, such as with
+    /// [`NekoMaidParser::override_variable`].
+    #[error("Constant '{name}' cannot be overridden")]
+    ConstantOverridden {
+        /// The name of the constant.
+        name: String,
+    },
+
+    /// An error indicating that a global variable was overridden, such as
+    /// with [`NekoMaidParser::override_variable`], with a value referencing
+    /// other variables.
+    #[error("Variable '{name}' can only be overridden with a constant value")]
+    NonConstantOverride {
+        /// The name of the overridden variable.
+        name: String,
+    },
+
+    /// An error indicating that a `property` declaration names an unknown
+    /// type.
+    #[error("Unknown property type '{name}' at {position}")]
+    UnknownPropertyType {
+        /// The name of the type.
+        name: String,
+
+        /// The position of the type in the source code.
+        position: TokenPosition,
+    },
+
+    /// An error indicating that a widget was used without one of its required
+    /// properties.
+    #[error("Widget '{widget}' requires the property '{property}'")]
+    MissingRequiredProperty {
+        /// The name of the widget.
+        widget: String,
+
+        /// The name of the missing property.
+        property: String,
+    },
+
+    /// An error indicating that a required property of a widget was given a
+    /// value of the wrong type.
+    #[error("Property '{property}' of widget '{widget}' must be a {expected}, found {found}")]
+    InvalidPropertyType {
+        /// The name of the widget.
+        widget: String,
+
+        /// The name of the property.
+        property: String,
+
+        /// The declared type of the property.
+        expected: PropertyType,
+
+        /// The type of the provided value.
+        found: PropertyType,
+    },
+
     /// An error indicating that multiple layouts were defined in a single
     /// widget definition.
     #[error("A widget cannot have multiple layouts defined: {position}")]
@@ -171,6 +347,27 @@ pub enum NekoMaidParseError {
         position: TokenPosition,
     },
 
+    /// An error indicating that content was provided to a slot that the widget
+    /// does not declare.
+    ///
+    /// This is checked where the widget is used.
+    #[error(
+        "Widget '{widget}' has no output slot named '{slot}', at {position}, its output slots are: {outputs:?}"
+    )]
+    UnknownSlot {
+        /// The name of the provided slot.
+        slot: String,
+
+        /// The name of the widget receiving the slot.
+        widget: String,
+
+        /// The output slots declared by the widget.
+        outputs: Vec<String>,
+
+        /// The position of the slot in the source code.
+        position: TokenPosition,
+    },
+
     /// An error indicating that multiple output slots were defined in a single
     /// widget definition.
     #[error("Layout of widget {widget} at {position} contains duplicated '{name}' output slots.")]
@@ -186,6 +383,9 @@ pub enum NekoMaidParseError {
     },
 
     /// An error indicating that a layout has no output slot.
+    ///
+    /// This is checked where the widget is defined, so a widget can always
+    /// receive content.
     #[error("Layout has no output slot: {widget} at {position}")]
     LayoutHasNoOutput {
         /// The name of the widget whose layout has no output.
@@ -195,6 +395,22 @@ pub enum NekoMaidParseError {
         position: TokenPosition,
     },
 
+    /// An error indicating that a value is followed or preceded by an
+    /// operator, as in `width: $base * 2;`.
+    ///
+    /// Property values are single values, variables, or lists of them;
+    /// expressions are not supported yet.
+    #[error(
+        "Expressions are not supported yet, found operator '{operator}' at {position}. Use a single value or variable instead."
+    )]
+    ExpressionNotSupported {
+        /// The operator found.
+        operator: String,
+
+        /// The position of the operator in the source code.
+        position: TokenPosition,
+    },
+
     /// An error indicating that a top-level has an output slot.
     #[error("Top-level layout with invalid output at {position}.")]
     TopLevelLayoutWithInvalidOutput {
@@ -202,3 +418,82 @@ pub enum NekoMaidParseError {
         position: TokenPosition,
     },
 }
+
+/// Warnings that can occur during parsing of NekoMaid UI files.
+///
+/// Unlike errors, warnings do not prevent a module from being loaded. They are
+/// available from [`Module::warnings`] and logged when the module is loaded as
+/// an asset.
+#[derive(Debug, thiserror::Error, Clone, PartialEq)]
+pub enum NekoMaidParseWarning {
+    /// A property was set on a widget it has no effect on.
+    #[error(
+        "Property '{property}' has no effect on widget '{widget}' at {position}, it only applies to: {applies_to:?}"
+    )]
+    InapplicableProperty {
+        /// The name of the property.
+        property: String,
+
+        /// The name of the widget the property was set on.
+        widget: String,
+
+        /// The widgets the property applies to.
+        applies_to: Vec<String>,
+
+        /// The position of the property in the source code.
+        position: TokenPosition,
+    },
+
+    /// A property was assigned more than once in the same block. The last
+    /// assignment is used.
+    #[error("Property '{property}' at {position} was already assigned at {previous}")]
+    DuplicateProperty {
+        /// The name of the property.
+        property: String,
+
+        /// The position of the previous assignment in the source code.
+        previous: TokenPosition,
+
+        /// The position of the repeated assignment in the source code.
+        position: TokenPosition,
+    },
+
+    /// A style selector references a class that no layout of the module
+    /// applies, so the style only matches if the class is added at runtime.
+    #[error("Class '{class}' at {position} is not applied by any layout")]
+    UnusedSelectorClass {
+        /// The name of the class.
+        class: String,
+
+        /// The position of the class in the selector.
+        position: TokenPosition,
+    },
+
+    /// A custom widget was used without providing any content to one of its
+    /// required output slots, declared with `output required <name>;`.
+    ///
+    /// This is checked where the widget is used. Output slots not marked as
+    /// required may be left empty.
+    #[error(
+        "Widget '{widget}' at {position} receives no content in its required output slot '{slot}', add children with `with` or `in {slot}`"
+    )]
+    EmptyOutputSlot {
+        /// The name of the widget.
+        widget: String,
+
+        /// The name of the output slot.
+        slot: String,
+
+        /// The position of the widget in the source code.
+        position: TokenPosition,
+    },
+
+    /// A variable was overridden, such as with
+    /// [`NekoMaidParser::override_variable`], but the module declares no global
+    /// variable with that name. The override is ignored.
+    #[error("Overridden variable '{name}' is not declared by the module")]
+    UndeclaredVariableOverride {
+        /// The name of the variable.
+        name: String,
+    },
+}
@@ -1,17 +1,18 @@
 //! Module parsing functionality.
 
-use bevy::platform::collections::HashMap;
+use bevy::platform::collections::{HashMap, HashSet};
 
-use crate::parse::NekoMaidParseError;
 use crate::parse::context::{NekoResult, ParseContext};
 use crate::parse::element::NekoElementBuilder;
 use crate::parse::import::parse_import;
-use crate::parse::layout::parse_layout;
-use crate::parse::property::parse_variable;
-use crate::parse::scope::ScopeTree;
+use crate::parse::layout::{Layout, parse_layout};
+use crate::parse::property::{PropertyType, UnresolvedPropertyValue, parse_declaration};
+use crate::parse::scope::{ScopeId, ScopeName, ScopeTree};
 use crate::parse::style::{Selector, Style, parse_style};
-use crate::parse::token::TokenType;
+use crate::parse::token::{TokenPosition, TokenType};
+use crate::parse::viewport::parse_when;
 use crate::parse::widget::{Widget, parse_widget};
+use crate::parse::{NekoMaidParseError, NekoMaidParseWarning};
 
 /// A NekoMaid UI module.
 #[derive(Debug, Clone, PartialEq)]
@@ -28,7 +29,161 @@ pub struct Module {
     pub(crate) widgets: HashMap<String, Widget>,
 
     /// A list of elements defined in this module, ready to be instantiated.
+    ///
+    /// Each top-level `layout` block adds one element. The elements of imported
+    /// modules come first, in the order of the imports, followed by the
+    /// layouts of this module in the order they are written.
     pub(crate) elements: Vec<NekoElementBuilder>,
+
+    /// The layouts the elements of this module were built from, in the same
+    /// order as [`elements`](Self::elements).
+    pub(crate) layouts: Vec<Layout>,
+
+    /// The warnings raised while parsing this module.
+    pub(crate) warnings: Vec<NekoMaidParseWarning>,
+
+    /// The positions of the global variables declared in this module.
+    pub(crate) variable_positions: HashMap<String, TokenPosition>,
+
+    /// The classes applied by the layouts and widgets of this module, including
+    /// those of imported modules.
+    pub(crate) classes: HashSet<String>,
+
+    /// The global variables declared as constants, including those of imported
+    /// modules.
+    pub(crate) constants: HashSet<String>,
+}
+
+impl Module {
+    /// Returns the warnings raised while parsing this module.
+    ///
+    /// Warnings of imported modules are not included.
+    pub fn warnings(&self) -> &[NekoMaidParseWarning] {
+        &self.warnings
+    }
+
+    /// Returns the position of the declaration of the given global variable.
+    ///
+    /// If the variable is declared more than once, the last declaration is
+    /// returned. Variables of imported modules are not included.
+    pub fn variable_position(&self, name: &str) -> Option<TokenPosition> {
+        self.variable_positions.get(name).copied()
+    }
+
+    /// Returns the global variables of this module, including those of
+    /// imported modules, sorted by name.
+    ///
+    /// Bound variables, such as `player.name`, are not declared by the module
+    /// and are not included.
+    ///
+    /// The type of each variable is inferred from its value, following the
+    /// variables it references.
+    pub fn variables(&self) -> Vec<ModuleVariable<'_>> {
+        let Some(global_scope) = self.scope.get(ScopeId(0)) else {
+            return vec![];
+        };
+
+        let mut variables = global_scope
+            .variables()
+            .filter(|(name, _)| !is_bound_variable(name))
+            .map(|(name, value)| ModuleVariable {
+                name,
+                value_type: self.value_type(value),
+                constant: self.constants.contains(name),
+            })
+            .collect::<Vec<_>>();
+        variables.sort_by_key(|variable| variable.name);
+        variables
+    }
+
+    /// Infers the type of a global variable value, or returns `None` if it
+    /// references an unknown variable or a reference cycle.
+    fn value_type(&self, value: &UnresolvedPropertyValue) -> Option<PropertyType> {
+        let global_scope = self.scope.get(ScopeId(0))?;
+        let mut value = value;
+        let mut visited = HashSet::new();
+
+        loop {
+            match value {
+                UnresolvedPropertyValue::Constant(constant) => return Some(constant.value_type()),
+                UnresolvedPropertyValue::List(_) => return Some(PropertyType::List),
+                UnresolvedPropertyValue::Variable(name) => {
+                    if !visited.insert(name) {
+                        return None;
+                    }
+                    let name = ScopeName::Variable(name.clone(), ScopeId(0));
+                    value = &global_scope.get(&name)?.unresolved;
+                }
+            }
+        }
+    }
+
+    /// Iterates over all elements of this module in depth-first order, in the
+    /// order they would be spawned.
+    ///
+    /// Custom widgets are already expanded into their native widgets.
+    pub fn iter_elements(&self) -> impl Iterator<Item = ModuleElement<'_>> {
+        let mut stack = self
+            .elements
+            .iter()
+            .rev()
+            .map(|element| (element, None, 0))
+            .collect::<Vec<_>>();
+        let mut index = 0;
+
+        std::iter::from_fn(move || {
+            let (element, parent, depth) = stack.pop()?;
+            for child in element.children.iter().rev() {
+                stack.push((child, Some(index), depth + 1));
+            }
+            index += 1;
+
+            Some(ModuleElement {
+                widget: &element.element.classpath().last().widget,
+                position: element.position,
+                classes: element.element.classes(),
+                parent,
+                depth,
+            })
+        })
+    }
+}
+
+/// An element of a [`Module`], as returned by [`Module::iter_elements`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModuleElement<'a> {
+    /// The native widget of the element.
+    pub widget: &'a str,
+
+    /// The position of the layout of the element in the source code.
+    ///
+    /// For the root element of a custom widget, this is the position of the
+    /// layout in the widget definition.
+    pub position: TokenPosition,
+
+    /// The classes of the element.
+    pub classes: &'a HashSet<String>,
+
+    /// The position of the parent element in the iteration, or `None` for
+    /// top-level elements.
+    pub parent: Option<usize>,
+
+    /// The number of ancestors of the element.
+    pub depth: usize,
+}
+
+/// A global variable of a [`Module`], as returned by [`Module::variables`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModuleVariable<'a> {
+    /// The name of the variable.
+    pub name: &'a str,
+
+    /// The type of the value of the variable, or `None` if it could not be
+    /// inferred.
+    pub value_type: Option<PropertyType>,
+
+    /// Whether the variable is declared with `const`.
+    pub constant: bool,
 }
 
 /// Parses a module from the given parse context.
@@ -36,9 +191,9 @@ pub(super) fn parse_module(mut ctx: ParseContext) -> NekoResult<Module> {
     while let Some(next) = ctx.peek() {
         match next.token_type {
             TokenType::ImportKeyword => parse_import(&mut ctx)?,
-            TokenType::VarKeyword => {
-                let variable = parse_variable(&mut ctx)?;
-                ctx.set_variable(&variable.name, &variable.value);
+            TokenType::VarKeyword | TokenType::ConstKeyword => {
+                let (variable, constant) = parse_declaration(&mut ctx)?;
+                ctx.declare_variable(variable, constant)?;
             }
             TokenType::DefKeyword => {
                 let widget = parse_widget(&mut ctx)?;
@@ -47,6 +202,9 @@ pub(super) fn parse_module(mut ctx: ParseContext) -> NekoResult<Module> {
             TokenType::StyleKeyword => {
                 parse_style(&mut ctx, Selector::default())?;
             }
+            TokenType::WhenKeyword => {
+                parse_when(&mut ctx)?;
+            }
             TokenType::LayoutKeyword => {
                 let layout = parse_layout(&mut ctx)?;
                 ctx.add_layout(layout);
@@ -56,8 +214,10 @@ pub(super) fn parse_module(mut ctx: ParseContext) -> NekoResult<Module> {
                     expected: vec![
                         TokenType::ImportKeyword.type_name().to_string(),
                         TokenType::VarKeyword.type_name().to_string(),
+                        TokenType::ConstKeyword.type_name().to_string(),
                         TokenType::DefKeyword.type_name().to_string(),
                         TokenType::StyleKeyword.type_name().to_string(),
+                        TokenType::WhenKeyword.type_name().to_string(),
                         TokenType::LayoutKeyword.type_name().to_string(),
                     ],
                     found: next.token_type.type_name().to_string(),
@@ -69,3 +229,11 @@ pub(super) fn parse_module(mut ctx: ParseContext) -> NekoResult<Module> {
 
     ctx.into_module()
 }
+
+/// Returns true if the given global variable is a member path created by a
+/// binding, such as `player.name`, rather than declared by the module.
+///
+/// Declared variable names cannot contain dots.
+pub(crate) fn is_bound_variable(name: &str) -> bool {
+    name.contains('.')
+}
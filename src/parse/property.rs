@@ -2,12 +2,26 @@
 
 use std::fmt;
 
+use bevy::platform::collections::HashMap;
 use bevy::prelude::*;
 
-use crate::parse::NekoMaidParseError;
 use crate::parse::context::{NekoResult, ParseContext};
-use crate::parse::token::TokenType;
+use crate::parse::token::{TokenPosition, TokenType};
 use crate::parse::value::PropertyValue;
+use crate::parse::{NekoMaidParseError, NekoMaidParseWarning};
+
+/// The shorthand properties, which accept a whitespace-separated list of
+/// values, such as `overflow: hidden scroll;`.
+///
+/// Every other property accepts a single value.
+pub(crate) const LIST_PROPERTIES: &[&str] = &[
+    "overflow",
+    "place-items",
+    "place-content",
+    "border",
+    "border-radius",
+    "grid-template-areas",
+];
 
 /// A property within a style or element.
 #[derive(Debug, Clone, PartialEq)]
@@ -17,6 +31,9 @@ pub(super) struct UnresolvedProperty {
 
     /// The value of the property.
     pub value: UnresolvedPropertyValue,
+
+    /// The position of the property name in the source code.
+    pub position: TokenPosition,
 }
 
 /// An unresolved property value that may be a constant or a variable reference.
@@ -27,6 +44,40 @@ pub(crate) enum UnresolvedPropertyValue {
 
     /// A variable reference.
     Variable(String),
+
+    /// A list of values separated by whitespace, such as `4px 8px`.
+    List(Vec<UnresolvedPropertyValue>),
+}
+
+impl UnresolvedPropertyValue {
+    /// Returns the names of all variables referenced by this value.
+    pub(crate) fn variables(&self) -> Vec<&String> {
+        match self {
+            UnresolvedPropertyValue::Constant(_) => vec![],
+            UnresolvedPropertyValue::Variable(name) => vec![name],
+            UnresolvedPropertyValue::List(values) => {
+                values.iter().flat_map(|value| value.variables()).collect()
+            }
+        }
+    }
+
+    /// Resolves this value, looking up referenced variables with `lookup`.
+    ///
+    /// Returns `None` if a referenced variable could not be resolved.
+    pub(crate) fn resolve(
+        &self,
+        lookup: &impl Fn(&String) -> Option<PropertyValue>,
+    ) -> Option<PropertyValue> {
+        match self {
+            UnresolvedPropertyValue::Constant(value) => Some(value.clone()),
+            UnresolvedPropertyValue::Variable(name) => lookup(name),
+            UnresolvedPropertyValue::List(values) => values
+                .iter()
+                .map(|value| value.resolve(lookup))
+                .collect::<Option<_>>()
+                .map(PropertyValue::List),
+        }
+    }
 }
 
 impl fmt::Display for UnresolvedPropertyValue {
@@ -34,6 +85,10 @@ impl fmt::Display for UnresolvedPropertyValue {
         match self {
             UnresolvedPropertyValue::Constant(value) => write!(f, "{}", value),
             UnresolvedPropertyValue::Variable(name) => write!(f, "${}", name),
+            UnresolvedPropertyValue::List(values) => {
+                let values = values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+                write!(f, "{}", values.join(" "))
+            }
         }
     }
 }
@@ -58,6 +113,26 @@ pub enum PropertyType {
 
     /// A pixel type.
     Pixels,
+
+    /// A list of values.
+    List,
+}
+
+impl PropertyType {
+    /// Returns the property type with the given name, as written in a
+    /// `property` declaration.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "string" => Some(PropertyType::String),
+            "number" => Some(PropertyType::Number),
+            "boolean" => Some(PropertyType::Boolean),
+            "color" => Some(PropertyType::Color),
+            "percentage" => Some(PropertyType::Percentage),
+            "pixels" => Some(PropertyType::Pixels),
+            "list" => Some(PropertyType::List),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for PropertyType {
@@ -69,6 +144,7 @@ impl fmt::Display for PropertyType {
             PropertyType::Color => "color",
             PropertyType::Percentage => "percentage",
             PropertyType::Pixels => "pixels",
+            PropertyType::List => "list",
         };
         write!(f, "{}", type_name)
     }
@@ -77,30 +153,147 @@ impl fmt::Display for PropertyType {
 /// Parses an unresolved property from the input and returns a
 /// [`UnresolvedProperty`].
 pub(super) fn parse_unresolved_property(ctx: &mut ParseContext) -> NekoResult<UnresolvedProperty> {
+    let position = ctx.next_position().unwrap_or_default();
     let name = ctx.expect_as_string(TokenType::Identifier)?;
     ctx.expect(TokenType::Colon)?;
-    let value = parse_unresolved_value(ctx)?;
+    let value = match LIST_PROPERTIES.contains(&name.as_str()) {
+        true => parse_unresolved_value(ctx)?,
+        false => parse_single_property_value(ctx)?,
+    };
     ctx.expect(TokenType::Semicolon)?;
 
-    Ok(UnresolvedProperty { name, value })
+    Ok(UnresolvedProperty {
+        name,
+        value,
+        position,
+    })
+}
+
+/// Records the position of a property assignment within a block, warning if
+/// the property was already assigned earlier in the same block.
+pub(super) fn record_property_position(
+    ctx: &mut ParseContext,
+    positions: &mut HashMap<String, TokenPosition>,
+    name: &str,
+    position: TokenPosition,
+) {
+    if let Some(previous) = positions.insert(name.to_string(), position) {
+        ctx.warn(NekoMaidParseWarning::DuplicateProperty {
+            property: name.to_string(),
+            previous,
+            position,
+        });
+    }
+}
+
+/// The variables declared in a single scope, used to enforce the rules of
+/// variable declarations.
+///
+/// A `var` may be declared again in the same scope, which reassigns it, while
+/// a `const` may not. Child scopes may shadow both.
+#[derive(Debug, Default)]
+pub(super) struct Declarations {
+    /// The position of the last declaration of each variable, and whether it
+    /// was a constant.
+    declared: HashMap<String, (TokenPosition, bool)>,
+}
+
+impl Declarations {
+    /// Records the declaration of a variable, returning an error if a constant
+    /// with the same name was already declared.
+    pub(super) fn declare(
+        &mut self,
+        variable: &UnresolvedProperty,
+        constant: bool,
+    ) -> NekoResult<()> {
+        if let Some(&(previous, true)) = self.declared.get(&variable.name) {
+            return Err(NekoMaidParseError::ConstantRedeclared {
+                name: variable.name.clone(),
+                previous,
+                position: variable.position,
+            });
+        }
+
+        self.declared
+            .insert(variable.name.clone(), (variable.position, constant));
+        Ok(())
+    }
+}
+
+/// Parses a `var` or `const` declaration from the input and returns it, along
+/// with whether it is a constant.
+pub(super) fn parse_declaration(ctx: &mut ParseContext) -> NekoResult<(UnresolvedProperty, bool)> {
+    match ctx.maybe_consume(TokenType::ConstKeyword) {
+        Some(_) => Ok((parse_variable_body(ctx)?, true)),
+        None => Ok((parse_variable(ctx)?, false)),
+    }
 }
 
 /// Parses a variable declaration from the input and returns a [`Property`].
 pub(super) fn parse_variable(ctx: &mut ParseContext) -> NekoResult<UnresolvedProperty> {
     ctx.expect(TokenType::VarKeyword)?;
+    parse_variable_body(ctx)
+}
+
+/// Parses the name and value of a variable declaration, following its keyword.
+fn parse_variable_body(ctx: &mut ParseContext) -> NekoResult<UnresolvedProperty> {
+    let position = ctx.next_position().unwrap_or_default();
     let name = ctx.expect_as_string(TokenType::Identifier)?;
     ctx.expect(TokenType::Equals)?;
     let value = parse_unresolved_value(ctx)?;
     ctx.expect(TokenType::Semicolon)?;
 
-    Ok(UnresolvedProperty { name, value })
+    Ok(UnresolvedProperty {
+        name,
+        value,
+        position,
+    })
 }
 
 /// Parses an unresolved property value from the input and returns a
 /// [`UnresolvedPropertyValue`].
+///
+/// Multiple values separated by whitespace, up to the next semicolon, are
+/// parsed as a list.
 pub(super) fn parse_unresolved_value(
     ctx: &mut ParseContext,
 ) -> NekoResult<UnresolvedPropertyValue> {
+    let mut values = vec![parse_single_value(ctx)?];
+    while ctx
+        .peek()
+        .is_some_and(|token| token.token_type != TokenType::Semicolon)
+    {
+        values.push(parse_single_value(ctx)?);
+    }
+
+    match values.len() {
+        1 => Ok(values.remove(0)),
+        _ => Ok(UnresolvedPropertyValue::List(values)),
+    }
+}
+
+/// Parses the value of a property that is not one of the
+/// [`LIST_PROPERTIES`], which must be a single value.
+///
+/// A trailing operator is reported as an unsupported expression, while any
+/// other trailing value is left for the caller to reject.
+fn parse_single_property_value(ctx: &mut ParseContext) -> NekoResult<UnresolvedPropertyValue> {
+    let value = parse_single_value(ctx)?;
+
+    if let Some(next) = ctx.peek()
+        && next.token_type.is_operator()
+    {
+        return Err(NekoMaidParseError::ExpressionNotSupported {
+            operator: next.token_type.type_name().to_string(),
+            position: next.position,
+        });
+    }
+
+    Ok(value)
+}
+
+/// Parses a single unresolved property value from the input.
+pub(super) fn parse_single_value(ctx: &mut ParseContext) -> NekoResult<UnresolvedPropertyValue> {
     let next_pos = ctx.next_position().unwrap_or_default();
     let next = ctx.consume()?;
 
@@ -124,9 +317,23 @@ pub(super) fn parse_unresolved_value(
             next.into_pixels_property(next_pos)?,
         )),
         TokenType::Variable => {
-            let var_name = next.into_variable_name(next_pos)?;
+            let mut var_name = next.into_variable_name(next_pos)?;
+            let mut bound = false;
+            while let Some(member) = ctx.maybe_consume(TokenType::Member) {
+                let member_pos = member.position;
+                var_name.push('.');
+                var_name.push_str(&member.into_variable_name(member_pos)?);
+                bound = true;
+            }
+            if bound {
+                ctx.declare_bound_variable(&var_name);
+            }
             Ok(UnresolvedPropertyValue::Variable(var_name))
         }
+        operator if operator.is_operator() => Err(NekoMaidParseError::ExpressionNotSupported {
+            operator: operator.type_name().to_string(),
+            position: next.position,
+        }),
         _ => Err(NekoMaidParseError::UnexpectedToken {
             expected: vec![
                 TokenType::StringLiteral.type_name().to_string(),
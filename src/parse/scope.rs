@@ -103,12 +103,27 @@ impl Scope {
             .and_then(|item| item.value.as_ref())
     }
 
+    /// Returns the evaluated value of the variable with the given name, if it
+    /// has been evaluated.
+    pub fn get_variable(&self, name: &str) -> Option<&PropertyValue> {
+        self.variables
+            .get(name)
+            .and_then(|item| item.value.as_ref())
+    }
+
     pub fn variables(&self) -> impl Iterator<Item = (&String, &UnresolvedPropertyValue)> {
         self.variables
             .iter()
             .map(|(name, item)| (name, &item.unresolved))
     }
 
+    /// Returns the unresolved values of the properties of this scope.
+    pub fn properties(&self) -> impl Iterator<Item = (&String, &UnresolvedPropertyValue)> {
+        self.properties
+            .iter()
+            .map(|(name, item)| (name, &item.unresolved))
+    }
+
     pub fn property_names(&self) -> impl Iterator<Item = &String> {
         self.properties.iter().map(|(name, _)| name)
     }
@@ -412,17 +427,12 @@ impl ScopeTree {
             return;
         };
 
-        let value = match &item.unresolved {
-            UnresolvedPropertyValue::Constant(value) => value.clone(),
-            UnresolvedPropertyValue::Variable(variable) => {
-                let value = self
-                    .find_variable(variable, name.scope_id())
-                    .and_then(|(item, _)| item.value.clone());
-                match value {
-                    Some(value) => value,
-                    None => panic!("variable {name} not defined."),
-                }
-            }
+        let lookup = |variable: &String| {
+            self.find_variable(variable, name.scope_id())
+                .and_then(|(item, _)| item.value.clone())
+        };
+        let Some(value) = item.unresolved.resolve(&lookup) else {
+            panic!("variable {name} not defined.");
         };
 
         let Some(item) = self.get_item_mut(name) else {
@@ -467,17 +477,14 @@ impl ScopeTree {
             for (name, entry) in scope.items() {
                 graph.add_node(name.clone());
 
-                match &entry.unresolved {
-                    UnresolvedPropertyValue::Variable(variable) => {
-                        let Some(&origin_scope) = variables.get(variable) else {
-                            panic!("Undefined variable {}", variable);
-                        };
-                        graph.add_dependency(
-                            name,
-                            ScopeName::Variable(variable.clone(), origin_scope),
-                        );
-                    }
-                    _ => {}
+                for variable in entry.unresolved.variables() {
+                    let Some(&origin_scope) = variables.get(variable) else {
+                        panic!("Undefined variable {}", variable);
+                    };
+                    graph.add_dependency(
+                        name.clone(),
+                        ScopeName::Variable(variable.clone(), origin_scope),
+                    );
                 }
             }
         }
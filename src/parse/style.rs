@@ -1,13 +1,15 @@
 //! A parser for NekoMaid UI style definitions.
 
-use bevy::platform::collections::HashSet;
+use bevy::math::Vec2;
+use bevy::platform::collections::{HashMap, HashSet};
 
 use crate::parse::NekoMaidParseError;
 use crate::parse::context::{NekoResult, ParseContext};
 use crate::parse::layout::Layout;
-use crate::parse::property::parse_unresolved_property;
+use crate::parse::property::{parse_unresolved_property, record_property_position};
 use crate::parse::scope::ScopeId;
-use crate::parse::token::TokenType;
+use crate::parse::token::{TokenPosition, TokenType};
+use crate::parse::viewport::ViewportCondition;
 use crate::parse::widget::Widget;
 
 /// A NekoMaid UI style definition.
@@ -18,18 +20,60 @@ pub struct Style {
 
     /// The id of the scope containing the properties of this style.
     pub(crate) scope_id: ScopeId,
+
+    /// The position of the style in the source code.
+    pub(crate) position: TokenPosition,
+
+    /// The viewport condition the style only applies under, if it is declared
+    /// in a `@when` block.
+    pub(crate) condition: Option<ViewportCondition>,
 }
 
 impl Style {
     /// Creates a new Style with the given selector and properties.
-    pub(crate) fn new(selector: Selector, scope_id: ScopeId) -> Self {
-        Self { selector, scope_id }
+    pub(crate) fn new(selector: Selector, scope_id: ScopeId, position: TokenPosition) -> Self {
+        Self {
+            selector,
+            scope_id,
+            position,
+            condition: None,
+        }
+    }
+
+    /// Restricts this style to the viewports matching the given condition.
+    pub(crate) fn with_condition(mut self, condition: Option<ViewportCondition>) -> Self {
+        self.condition = condition;
+        self
+    }
+
+    /// Returns the viewport condition this style only applies under, if any.
+    pub fn condition(&self) -> Option<ViewportCondition> {
+        self.condition
+    }
+
+    /// Returns whether this style applies to a viewport of the given size.
+    ///
+    /// Styles without a condition apply to any viewport, while conditional
+    /// styles never apply while the viewport size is unknown.
+    pub fn applies_to_viewport(&self, viewport: Option<Vec2>) -> bool {
+        match (self.condition, viewport) {
+            (None, _) => true,
+            (Some(condition), Some(viewport)) => condition.holds(viewport),
+            (Some(_), None) => false,
+        }
     }
 
     /// Returns a reference to the selector of this style.
     pub fn selector(&self) -> &Selector {
         &self.selector
     }
+
+    /// Returns the position of this style in the source code.
+    ///
+    /// For nested styles, this is the position of their `with` keyword.
+    pub fn position(&self) -> TokenPosition {
+        self.position
+    }
 }
 
 /// A selector for targeting widgets in styles.
@@ -50,51 +94,44 @@ pub struct SelectorPart {
 
     /// The classes the selector part excludes.
     pub blacklist: HashSet<String>,
+
+    /// Whether any number of widgets may sit between this part and the
+    /// previous part of the selector. (Written as `**` in a selector)
+    pub descendant: bool,
 }
 
 /// Parses a style from the given parse context.
-pub(super) fn parse_style(ctx: &mut ParseContext, mut selector: Selector) -> NekoResult<()> {
+pub(super) fn parse_style(ctx: &mut ParseContext, selector: Selector) -> NekoResult<()> {
+    ctx.enter_block()?;
+    let result = parse_style_block(ctx, selector);
+    ctx.exit_block();
+    result
+}
+
+/// Parses the selector and the body of a style, along with its nested styles.
+fn parse_style_block(ctx: &mut ParseContext, mut selector: Selector) -> NekoResult<()> {
+    let position = ctx.next_position().unwrap_or_default();
     ctx.maybe_consume(TokenType::StyleKeyword);
     ctx.maybe_consume(TokenType::WithKeyword);
 
-    let widget_position = ctx.next_position().unwrap_or_default();
-    let widget = ctx.expect_as_string(TokenType::Identifier)?;
+    let descendant = ctx.maybe_consume(TokenType::DoubleStar).is_some();
+    parse_selector_segment(ctx, &mut selector, descendant)?;
 
-    let (whitelist, blacklist) = parse_style_selector(ctx)?;
-
-    let Some(w) = ctx.get_widget(&widget) else {
-        return Err(NekoMaidParseError::UnknownWidget {
-            widget,
-            position: widget_position,
-        });
-    };
-
-    if let Widget::Custom(custom_widget) = w {
-        let selector_index = selector.hierarchy.len();
-        unroll_widget(&custom_widget.layout, "default", &mut selector);
-
-        selector.hierarchy[selector_index]
-            .whitelist
-            .extend(whitelist);
-        selector.hierarchy[selector_index]
-            .blacklist
-            .extend(blacklist);
-    } else {
-        selector.hierarchy.push(SelectorPart {
-            widget,
-            whitelist,
-            blacklist,
-        });
+    while ctx.maybe_consume(TokenType::DoubleStar).is_some() {
+        parse_selector_segment(ctx, &mut selector, true)?;
     }
 
     ctx.expect(TokenType::OpenBrace)?;
 
     let mut properties = vec![];
+    let mut property_positions = HashMap::new();
 
     while let Some(next) = ctx.peek() {
         match next.token_type {
             TokenType::Identifier => {
+                let position = next.position;
                 let property = parse_unresolved_property(ctx)?;
+                record_property_position(ctx, &mut property_positions, &property.name, position);
                 properties.push((property.name, property.value));
             }
             TokenType::WithKeyword => {
@@ -121,7 +158,50 @@ pub(super) fn parse_style(ctx: &mut ParseContext, mut selector: Selector) -> Nek
         let scope = ctx.create_scope(ScopeId(0));
         scope.add_properties(properties.iter().map(|(k, v)| (k, v)));
         let scope_id = scope.id();
-        ctx.add_style(Style::new(selector, scope_id));
+        let condition = ctx.style_condition();
+        ctx.add_style(Style::new(selector, scope_id, position).with_condition(condition));
+    }
+
+    Ok(())
+}
+
+/// Parses a single widget of a selector, along with its class filters, and
+/// appends it to the given [`Selector`].
+///
+/// If `descendant` is true, the widget may be nested any number of levels
+/// below the previous part of the selector, rather than being a direct child.
+fn parse_selector_segment(
+    ctx: &mut ParseContext,
+    selector: &mut Selector,
+    descendant: bool,
+) -> NekoResult<()> {
+    let widget_position = ctx.next_position().unwrap_or_default();
+    let widget = ctx.expect_as_string(TokenType::Identifier)?;
+
+    let (whitelist, blacklist) = parse_style_selector(ctx)?;
+
+    let Some(w) = ctx.get_widget(&widget) else {
+        return Err(NekoMaidParseError::UnknownWidget {
+            widget,
+            position: widget_position,
+        });
+    };
+
+    if let Widget::Custom(custom_widget) = w {
+        let selector_index = selector.hierarchy.len();
+        unroll_widget(&custom_widget.layout, "default", selector);
+
+        let part = &mut selector.hierarchy[selector_index];
+        part.whitelist.extend(whitelist);
+        part.blacklist.extend(blacklist);
+        part.descendant = descendant;
+    } else {
+        selector.hierarchy.push(SelectorPart {
+            widget,
+            whitelist,
+            blacklist,
+            descendant,
+        });
     }
 
     Ok(())
@@ -139,21 +219,26 @@ pub(super) fn parse_style_selector(
             TokenType::Plus => {
                 ctx.expect(TokenType::Plus)?;
 
+                let position = ctx.next_position().unwrap_or_default();
                 let class_name = ctx.expect_as_string(TokenType::Identifier)?;
+                ctx.use_selector_class(&class_name, position);
                 whitelist.insert(class_name);
             }
             TokenType::Exclamation => {
                 ctx.expect(TokenType::Exclamation)?;
 
+                let position = ctx.next_position().unwrap_or_default();
                 let class_name = ctx.expect_as_string(TokenType::Identifier)?;
+                ctx.use_selector_class(&class_name, position);
                 blacklist.insert(class_name);
             }
-            TokenType::OpenBrace => break,
+            TokenType::OpenBrace | TokenType::DoubleStar => break,
             _ => {
                 return Err(NekoMaidParseError::UnexpectedToken {
                     expected: vec![
                         TokenType::Plus.type_name().to_string(),
                         TokenType::Exclamation.type_name().to_string(),
+                        TokenType::DoubleStar.type_name().to_string(),
                         TokenType::OpenBrace.type_name().to_string(),
                     ],
                     found: next.token_type.type_name().to_string(),
@@ -166,12 +251,31 @@ pub(super) fn parse_style_selector(
     Ok((whitelist, blacklist))
 }
 
+/// Returns the selector matching the elements a style targeting the given
+/// widget applies to, as if written `style <widget> { ... }`.
+pub(super) fn widget_selector(widget: &Widget) -> Selector {
+    let mut selector = Selector::default();
+    match widget {
+        Widget::Custom(custom_widget) => {
+            unroll_widget(&custom_widget.layout, "default", &mut selector)
+        }
+        Widget::Native(native_widget) => selector.hierarchy.push(SelectorPart {
+            widget: native_widget.name.clone(),
+            whitelist: HashSet::new(),
+            blacklist: HashSet::new(),
+            descendant: false,
+        }),
+    }
+    selector
+}
+
 /// Unrolls a custom widget's layout into selector parts.
 fn unroll_widget(layout: &Layout, slot: &str, selector: &mut Selector) {
     selector.hierarchy.push(SelectorPart {
         widget: layout.widget.clone(),
         whitelist: layout.classes.clone(),
         blacklist: HashSet::new(),
+        descendant: false,
     });
 
     for child in layout.get_slot(slot) {
@@ -1,15 +1,24 @@
 //! Tests
 
 use bevy::asset::AssetServer;
+use bevy::color::{Color, ColorToPacked};
 use bevy::ecs::entity::Entity;
 use bevy::ecs::system::{Commands, Res};
-use bevy::platform::collections::HashSet;
+use bevy::math::Vec2;
+use bevy::platform::collections::{HashMap, HashSet};
 use pretty_assertions::assert_eq;
 
-use crate::parse::NekoMaidParser;
-use crate::parse::element::NekoElement;
+use crate::parse::class::{ClassPath, ClassSet};
+use crate::parse::element::{DEFAULT_MAX_DEPTH, NekoElement};
+use crate::parse::module::Module;
+use crate::parse::property::{PropertyType, UnresolvedPropertyValue};
+use crate::parse::scope::{ScopeId, ScopeName};
 use crate::parse::style::{Selector, SelectorPart};
-use crate::parse::widget::NativeWidget;
+use crate::parse::token::{TokenPosition, TokenType, TokenValue};
+use crate::parse::tokenizer::Tokenizer;
+use crate::parse::value::PropertyValue;
+use crate::parse::widget::{NativeWidget, Widget};
+use crate::parse::{NekoMaidParseError, NekoMaidParseWarning, NekoMaidParser};
 
 fn spawn_func(_: &Res<AssetServer>, _: &mut Commands, _: &NekoElement, _: Entity) -> Entity {
     Entity::PLACEHOLDER
@@ -19,6 +28,7 @@ fn native<S: Into<String>>(name: S) -> NativeWidget {
     NativeWidget {
         name: name.into(),
         spawn_func,
+        default_style: HashMap::new(),
     }
 }
 
@@ -60,21 +70,25 @@ style div {
                     widget: "div".into(),
                     whitelist: HashSet::new(),
                     blacklist: HashSet::new(),
+                    descendant: false,
                 },
                 SelectorPart {
                     widget: "div".into(),
                     whitelist: HashSet::from(["scrollview".into(), "active".into()]),
                     blacklist: HashSet::new(),
+                    descendant: false,
                 },
                 SelectorPart {
                     widget: "div".into(),
                     whitelist: HashSet::from(["content-pane".into()]),
                     blacklist: HashSet::new(),
+                    descendant: false,
                 },
                 SelectorPart {
                     widget: "p".into(),
                     whitelist: HashSet::from(["h1".into()]),
                     blacklist: HashSet::new(),
+                    descendant: false,
                 },
             ]
         },
@@ -122,18 +136,1192 @@ style card {
                     widget: "div".into(),
                     whitelist: HashSet::from(["card".into()]),
                     blacklist: HashSet::new(),
+                    descendant: false,
                 },
                 SelectorPart {
                     widget: "div".into(),
                     whitelist: HashSet::from(["card-body".into()]),
                     blacklist: HashSet::new(),
+                    descendant: false,
                 },
                 SelectorPart {
                     widget: "p".into(),
                     whitelist: HashSet::from(["h3".into()]),
                     blacklist: HashSet::new(),
+                    descendant: false,
                 },
             ]
         },
     );
 }
+
+#[test]
+fn widget_extends() {
+    const SOURCE: &str = r#"
+def button {
+    var color = #ff0000;
+    var padding = 4px;
+
+    layout div {
+        class button;
+        background-color: $color;
+        output;
+    }
+}
+
+def primary-button extends button {
+    var color = #0000ff;
+}
+    "#;
+
+    let mut parse = NekoMaidParser::tokenize(SOURCE).unwrap();
+    parse.register_native_widget(native("div"));
+    let module = parse.finish().unwrap();
+
+    let Some(Widget::Custom(base)) = module.widgets.get("button") else {
+        panic!("button is not a custom widget");
+    };
+    let Some(Widget::Custom(derived)) = module.widgets.get("primary-button") else {
+        panic!("primary-button is not a custom widget");
+    };
+
+    assert_eq!(derived.layout, base.layout);
+    assert_eq!(
+        derived.default_properties.get("padding"),
+        Some(&UnresolvedPropertyValue::Constant(PropertyValue::Pixels(
+            4.0
+        )))
+    );
+    assert_eq!(
+        derived.default_properties.get("color"),
+        Some(&UnresolvedPropertyValue::Constant(PropertyValue::Color(
+            bevy::color::Color::srgb(0.0, 0.0, 1.0)
+        )))
+    );
+}
+
+#[test]
+fn widget_extends_native() {
+    const SOURCE: &str = r#"
+def panel extends div {
+    var color = #0000ff;
+}
+    "#;
+
+    let mut parse = NekoMaidParser::tokenize(SOURCE).unwrap();
+    parse.register_native_widget(native("div"));
+    let result = parse.finish();
+
+    assert!(matches!(
+        result,
+        Err(NekoMaidParseError::InvalidWidgetBase { widget, base, .. })
+            if widget == "panel" && base == "div"
+    ));
+}
+
+#[test]
+fn widget_recursion() {
+    // the second definition replaces the first one, so it ends up containing
+    // itself once the tree is built.
+    const SOURCE: &str = r#"
+def panel {
+    layout div {
+        output;
+    }
+}
+
+def panel {
+    layout div {
+        with panel {}
+        output;
+    }
+}
+
+layout panel {}
+    "#;
+
+    let mut parse = NekoMaidParser::tokenize(SOURCE).unwrap();
+    parse.register_native_widget(native("div"));
+    let result = parse.finish();
+
+    assert!(matches!(
+        result,
+        Err(NekoMaidParseError::WidgetRecursion { .. })
+    ));
+}
+
+#[test]
+fn max_tree_depth() {
+    const SOURCE: &str = r#"
+def panel {
+    layout div {
+        with div {
+            output;
+        }
+    }
+}
+
+layout div {
+    with panel {
+        with div {}
+    }
+}
+    "#;
+
+    let parse = |max_depth| {
+        let mut parse = NekoMaidParser::tokenize(SOURCE).unwrap();
+        parse.register_native_widget(native("div"));
+        parse.set_max_depth(max_depth);
+        parse.finish()
+    };
+
+    assert!(parse(4).is_ok());
+    assert_eq!(
+        parse(3).unwrap_err(),
+        NekoMaidParseError::MaxDepthExceeded { depth: 3 }
+    );
+}
+
+#[test]
+fn max_depth_while_parsing() {
+    let depth = 10_000;
+    let source = format!(
+        "layout div {{{}{}}}",
+        "with div {".repeat(depth),
+        "}".repeat(depth)
+    );
+
+    let mut parse = NekoMaidParser::tokenize(&source).unwrap();
+    parse.register_native_widget(native("div"));
+    assert_eq!(
+        parse.finish().unwrap_err(),
+        NekoMaidParseError::MaxDepthExceeded {
+            depth: DEFAULT_MAX_DEPTH
+        }
+    );
+
+    let source = format!(
+        "style div {}{{}}{} layout div {{}}",
+        "{ with div ".repeat(depth),
+        "}".repeat(depth)
+    );
+    let mut parse = NekoMaidParser::tokenize(&source).unwrap();
+    parse.register_native_widget(native("div"));
+    parse.set_max_depth(10);
+    assert_eq!(
+        parse.finish().unwrap_err(),
+        NekoMaidParseError::MaxDepthExceeded { depth: 10 }
+    );
+}
+
+#[test]
+fn widget_nested_in_own_slot() {
+    const SOURCE: &str = r#"
+def card {
+    layout div {
+        output;
+    }
+}
+
+layout card {
+    with card {
+        with div {}
+    }
+}
+    "#;
+
+    let mut parse = NekoMaidParser::tokenize(SOURCE).unwrap();
+    parse.register_native_widget(native("div"));
+    assert!(parse.finish().is_ok());
+}
+
+#[test]
+fn unknown_input_slot() {
+    const SOURCE: &str = r#"
+def card {
+    layout div {
+        output body;
+    }
+}
+
+layout card {
+    in body {
+        with div {}
+    }
+}
+
+layout card {
+    in sidebar {
+        with div {}
+    }
+}
+    "#;
+
+    let mut parse = NekoMaidParser::tokenize(SOURCE).unwrap();
+    parse.register_native_widget(native("div"));
+    let result = parse.finish();
+
+    assert!(matches!(
+        result,
+        Err(NekoMaidParseError::UnknownSlot { slot, widget, outputs, position })
+            if slot == "sidebar" && widget == "card" && outputs == ["body"] && position.line == 15
+    ));
+}
+
+#[test]
+fn expression_not_supported() {
+    const SOURCE: &str = r#"
+var base = 10px;
+
+layout div {
+    width: $base * 2;
+}
+    "#;
+
+    let mut parse = NekoMaidParser::tokenize(SOURCE).unwrap();
+    parse.register_native_widget(native("div"));
+    let result = parse.finish();
+
+    assert!(matches!(
+        result,
+        Err(NekoMaidParseError::ExpressionNotSupported { operator, position })
+            if operator == "*" && position.line == 5 && position.column == 18
+    ));
+}
+
+#[test]
+fn list_only_for_shorthands() {
+    const SOURCE: &str = r#"
+layout div {
+    overflow: hidden scroll;
+    width: 10px 20px;
+}
+    "#;
+
+    let mut parse = NekoMaidParser::tokenize(SOURCE).unwrap();
+    parse.register_native_widget(native("div"));
+    let result = parse.finish();
+
+    assert!(matches!(
+        result,
+        Err(NekoMaidParseError::UnexpectedToken { expected, found, position })
+            if expected == [";"] && found == "pixels" && position.line == 4 && position.column == 17
+    ));
+}
+
+#[test]
+fn slot_children_follow_layout_order() {
+    const SOURCE: &str = r#"
+def card {
+    layout div {
+        output head;
+        with div {
+            class separator;
+        }
+        output body;
+        output footer;
+    }
+}
+
+layout card {
+    in footer {
+        with div { class footer; }
+    }
+
+    in body {
+        with div { class body; }
+    }
+
+    in head {
+        with div { class head; }
+    }
+}
+    "#;
+
+    let mut parse = NekoMaidParser::tokenize(SOURCE).unwrap();
+    parse.register_native_widget(native("div"));
+    let module = parse.finish().unwrap();
+
+    let classes = module.elements[0]
+        .children
+        .iter()
+        .map(|c| c.element.classes().iter().next().unwrap().as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(classes, vec!["head", "separator", "body", "footer"]);
+}
+
+#[test]
+fn slot_children_follow_layout_order_in_default() {
+    const SOURCE: &str = r#"
+def list {
+    layout div {
+        in default {
+            output head;
+            with div { class separator; }
+        }
+        output body;
+    }
+}
+
+layout list {
+    in body {
+        with div { class body; }
+    }
+
+    in head {
+        with div { class head; }
+    }
+}
+    "#;
+
+    let mut parse = NekoMaidParser::tokenize(SOURCE).unwrap();
+    parse.register_native_widget(native("div"));
+    let module = parse.finish().unwrap();
+
+    let classes = module.elements[0]
+        .children
+        .iter()
+        .map(|c| c.element.classes().iter().next().unwrap().as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(classes, vec!["head", "separator", "body"]);
+}
+
+#[test]
+fn list_value_with_comments() {
+    const SOURCE: &str = r#"
+def card {
+    var padding =
+        4px // vertical
+        // both sides
+        8px; // horizontal
+
+    layout div {
+        output;
+    }
+}
+    "#;
+
+    let mut parse = NekoMaidParser::tokenize(SOURCE).unwrap();
+    parse.register_native_widget(native("div"));
+    let module = parse.finish().unwrap();
+
+    let Some(Widget::Custom(card)) = module.widgets.get("card") else {
+        panic!("card is not a custom widget");
+    };
+
+    assert_eq!(
+        card.default_properties.get("padding"),
+        Some(&UnresolvedPropertyValue::List(vec![
+            UnresolvedPropertyValue::Constant(PropertyValue::Pixels(4.0)),
+            UnresolvedPropertyValue::Constant(PropertyValue::Pixels(8.0)),
+        ]))
+    );
+}
+
+#[test]
+fn inapplicable_property_warning() {
+    const SOURCE: &str = r#"
+layout div {
+    src: "cat.png";
+
+    with p {
+        text: "Hello";
+    }
+}
+    "#;
+
+    let mut parse = NekoMaidParser::tokenize(SOURCE).unwrap();
+    parse.register_native_widget(native("div"));
+    parse.register_native_widget(native("p"));
+    let module = parse.finish().unwrap();
+
+    assert_eq!(
+        module.warnings(),
+        &[NekoMaidParseWarning::InapplicableProperty {
+            property: "src".into(),
+            widget: "div".into(),
+            applies_to: vec!["img".into()],
+            position: TokenPosition::new(3, 5, 3),
+        }]
+    );
+}
+
+#[test]
+fn inherited_property_on_any_widget() {
+    const SOURCE: &str = r#"
+layout div {
+    color: #ff0000;
+    font-size: 32;
+
+    with p {
+        text: "Hello";
+    }
+}
+    "#;
+
+    let mut parse = NekoMaidParser::tokenize(SOURCE).unwrap();
+    parse.register_native_widget(native("div"));
+    parse.register_native_widget(native("p"));
+    let module = parse.finish().unwrap();
+
+    assert_eq!(module.warnings(), &[]);
+}
+
+#[test]
+fn descendant_wildcard_selector() {
+    const SOURCE: &str = r#"
+style div +card ** p {
+    test: "Deep";
+}
+    "#;
+
+    let mut parse = NekoMaidParser::tokenize(SOURCE).unwrap();
+    parse.register_native_widget(native("div"));
+    parse.register_native_widget(native("p"));
+    let module = parse.finish().unwrap();
+
+    let selector = &module.styles[0].selector;
+    assert!(selector.hierarchy[1].descendant);
+
+    let class_set = |widget: &str, classes: &[&str]| ClassSet {
+        widget: widget.into(),
+        classes: classes.iter().map(|c| c.to_string()).collect(),
+    };
+
+    let mut deep = ClassPath::new(class_set("div", &["card"]));
+    deep.append(class_set("div", &[]));
+    deep.append(class_set("div", &["body"]));
+    deep.append(class_set("p", &[]));
+    assert!(deep.matches(selector));
+
+    let mut direct = ClassPath::new(class_set("div", &["card"]));
+    direct.append(class_set("p", &[]));
+    assert!(direct.matches(selector));
+
+    let mut no_ancestor = ClassPath::new(class_set("div", &[]));
+    no_ancestor.append(class_set("div", &["body"]));
+    no_ancestor.append(class_set("p", &[]));
+    assert!(!no_ancestor.matches(selector));
+    assert!(no_ancestor.partial_matches(selector));
+}
+
+#[test]
+fn iter_module_elements() {
+    const SOURCE: &str = r#"
+def card {
+    layout div {
+        class card;
+        output default;
+    }
+}
+
+layout div {
+    with card {
+        with p {
+            class title;
+        }
+    }
+
+    with p {}
+}
+
+layout p {}
+    "#;
+
+    let mut parse = NekoMaidParser::tokenize(SOURCE).unwrap();
+    parse.register_native_widget(native("div"));
+    parse.register_native_widget(native("p"));
+    let module = parse.finish().unwrap();
+
+    let elements = module
+        .iter_elements()
+        .map(|e| {
+            let mut classes = e.classes.iter().map(String::as_str).collect::<Vec<_>>();
+            classes.sort();
+            (e.widget, classes, e.parent, e.depth)
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        elements,
+        vec![
+            ("div", vec![], None, 0),
+            ("div", vec!["card"], Some(0), 1),
+            ("p", vec!["title"], Some(1), 2),
+            ("p", vec![], Some(0), 1),
+            ("p", vec![], None, 0),
+        ]
+    );
+}
+
+#[test]
+fn duplicate_property_warning() {
+    const SOURCE: &str = r#"
+style div {
+    width: 10px;
+    width: 20px;
+}
+
+layout div {
+    height: 10px;
+
+    with div {
+        height: 20px;
+    }
+}
+    "#;
+
+    let mut parse = NekoMaidParser::tokenize(SOURCE).unwrap();
+    parse.register_native_widget(native("div"));
+    let module = parse.finish().unwrap();
+
+    assert_eq!(
+        module.warnings(),
+        &[NekoMaidParseWarning::DuplicateProperty {
+            property: "width".into(),
+            previous: TokenPosition::new(3, 5, 5),
+            position: TokenPosition::new(4, 5, 5),
+        }]
+    );
+}
+
+#[test]
+fn unused_selector_class_warning() {
+    const SOURCE: &str = r#"
+style div +activ {
+    width: 10px;
+}
+
+style div +active !disabled +hovered {
+    width: 20px;
+}
+
+layout div {
+    class active;
+    class-if disabled: false;
+}
+    "#;
+
+    let mut parse = NekoMaidParser::tokenize(SOURCE).unwrap();
+    parse.register_native_widget(native("div"));
+    let module = parse.finish().unwrap();
+
+    assert_eq!(
+        module.warnings(),
+        &[NekoMaidParseWarning::UnusedSelectorClass {
+            class: "activ".into(),
+            position: TokenPosition::new(2, 12, 5),
+        }]
+    );
+}
+
+#[test]
+fn runtime_class_not_reported() {
+    const SOURCE: &str = r#"
+style div +selected {
+    width: 10px;
+}
+
+layout div {}
+    "#;
+
+    let mut parse = NekoMaidParser::tokenize(SOURCE).unwrap();
+    parse.register_native_widget(native("div"));
+    parse.add_runtime_class("selected");
+    let module = parse.finish().unwrap();
+
+    assert_eq!(module.warnings(), &[]);
+}
+
+#[test]
+fn layout_without_output() {
+    const SOURCE: &str = r#"
+def card {
+    layout div {
+        with div {}
+    }
+}
+    "#;
+
+    let mut parse = NekoMaidParser::tokenize(SOURCE).unwrap();
+    parse.register_native_widget(native("div"));
+
+    assert!(matches!(
+        parse.finish(),
+        Err(NekoMaidParseError::LayoutHasNoOutput { widget, .. }) if widget == "card"
+    ));
+}
+
+#[test]
+fn required_output_slot() {
+    const SOURCE: &str = r#"
+def card {
+    layout div {
+        output head;
+        output required body;
+        output required;
+    }
+}
+    "#;
+
+    let mut parse = NekoMaidParser::tokenize(SOURCE).unwrap();
+    parse.register_native_widget(native("div"));
+    let module = parse.finish().unwrap();
+
+    let Some(Widget::Custom(card)) = module.widgets.get("card") else {
+        panic!("card is not a custom widget");
+    };
+    // a slot may still be named `required`
+    assert_eq!(card.outputs(), vec!["body", "head", "required"]);
+    assert_eq!(card.required_outputs(), vec!["body"]);
+}
+
+#[test]
+fn empty_output_slot() {
+    const SOURCE: &str = r#"
+def card {
+    layout div {
+        output head;
+        output required body;
+    }
+}
+
+def frame {
+    layout card {
+        in body {
+            output;
+        }
+    }
+}
+
+layout card {}
+
+layout card {
+    in body {
+        with div {}
+    }
+}
+    "#;
+
+    let mut parse = NekoMaidParser::tokenize(SOURCE).unwrap();
+    parse.register_native_widget(native("div"));
+    let module = parse.finish().unwrap();
+
+    // the optional head slot and the body slot forwarded by frame are fine
+    assert_eq!(
+        module.warnings(),
+        &[NekoMaidParseWarning::EmptyOutputSlot {
+            widget: "card".into(),
+            slot: "body".into(),
+            position: TokenPosition::new(17, 8, 4),
+        }]
+    );
+}
+
+#[test]
+fn required_widget_property() {
+    const WIDGET: &str = r#"
+def card {
+    property title: string;
+
+    layout div {
+        with p {
+            text: $title;
+        }
+        output;
+    }
+}
+    "#;
+
+    let parse = |layout: &str| {
+        let mut parse = NekoMaidParser::tokenize(&format!("{WIDGET}\n{layout}")).unwrap();
+        parse.register_native_widget(native("div"));
+        parse.register_native_widget(native("p"));
+        parse.finish()
+    };
+
+    assert!(parse(r#"layout card { title: "Hello"; }"#).is_ok());
+    assert_eq!(
+        parse("layout card {}").unwrap_err(),
+        NekoMaidParseError::MissingRequiredProperty {
+            widget: "card".into(),
+            property: "title".into(),
+        }
+    );
+    assert_eq!(
+        parse("layout card { title: 10px; }").unwrap_err(),
+        NekoMaidParseError::InvalidPropertyType {
+            widget: "card".into(),
+            property: "title".into(),
+            expected: PropertyType::String,
+            found: PropertyType::Pixels,
+        }
+    );
+}
+
+#[test]
+fn source_positions() {
+    const SOURCE: &str = r#"
+var accent = #ff0000;
+
+style div {
+    with p {
+        color: $accent;
+    }
+}
+
+layout div {
+    with p {}
+}
+    "#;
+
+    let mut parse = NekoMaidParser::tokenize(SOURCE).unwrap();
+    parse.register_native_widget(native("div"));
+    parse.register_native_widget(native("p"));
+    let module = parse.finish().unwrap();
+
+    assert_eq!(module.variable_position("accent").unwrap().line, 2);
+    assert_eq!(module.styles[0].position().line, 5);
+
+    let lines = module
+        .iter_elements()
+        .map(|e| e.position.line)
+        .collect::<Vec<_>>();
+    assert_eq!(lines, vec![10, 11]);
+}
+
+#[test]
+fn constant_declarations() {
+    let parse = |source: &str| {
+        let mut parse = NekoMaidParser::tokenize(source).unwrap();
+        parse.register_native_widget(native("div"));
+        parse.finish()
+    };
+
+    // variables may be reassigned, and constants shadowed in child scopes
+    assert!(parse("var a = 1; var a = 2; const b = 1; layout div { var b = 2; }").is_ok());
+    assert!(parse("layout div { var a = 1; var a = 2; }").is_ok());
+
+    assert_eq!(
+        parse("const a = 1;\nvar a = 2;").unwrap_err(),
+        NekoMaidParseError::ConstantRedeclared {
+            name: "a".into(),
+            previous: TokenPosition::new(1, 7, 1),
+            position: TokenPosition::new(2, 5, 1),
+        }
+    );
+    assert!(matches!(
+        parse("layout div { const a = 1; const a = 2; }"),
+        Err(NekoMaidParseError::ConstantRedeclared { .. })
+    ));
+}
+
+#[test]
+fn hex_color_literals() {
+    let color = |code: &str| {
+        let tokens = Tokenizer::tokenize(code).unwrap();
+        assert_eq!(tokens[0].token_type, TokenType::ColorLiteral);
+        assert_eq!(tokens[0].position.length, code.len() - 1);
+        match &tokens[0].value {
+            TokenValue::Color(color) => color.to_srgba().to_u8_array(),
+            value => panic!("expected a color, found {value:?}"),
+        }
+    };
+
+    assert_eq!(color("#f00"), [255, 0, 0, 255]);
+    assert_eq!(color("#f008"), [255, 0, 0, 0x88]);
+    assert_eq!(color("#FF0000"), [255, 0, 0, 255]);
+    assert_eq!(color("#ff000080"), [255, 0, 0, 0x80]);
+    assert_eq!(color("#1a2b3c4d"), [0x1a, 0x2b, 0x3c, 0x4d]);
+
+    assert!(Tokenizer::tokenize("#f0000").is_err());
+    assert!(Tokenizer::tokenize("#ff00000").is_err());
+}
+
+#[test]
+fn variable_overrides() {
+    let mut parse = NekoMaidParser::tokenize("var color = #000000;\nvar size = 0px;").unwrap();
+    assert!(parse.override_variable("color", "").is_err());
+    assert!(parse.override_variable("color", "#ff0000; 1").is_err());
+
+    parse.override_variable("color", "#ff0000").unwrap();
+    parse.override_variable("size", "10px 20px").unwrap();
+    let module = parse.finish().unwrap();
+
+    let global = module.scope.get(ScopeId(0)).unwrap();
+    let variables = global.variables().collect::<HashMap<_, _>>();
+    assert_eq!(
+        variables[&"color".to_string()],
+        &UnresolvedPropertyValue::Constant(PropertyValue::Color(Color::srgb(1.0, 0.0, 0.0)))
+    );
+    assert_eq!(
+        variables[&"size".to_string()],
+        &UnresolvedPropertyValue::List(vec![
+            UnresolvedPropertyValue::Constant(PropertyValue::Pixels(10.0)),
+            UnresolvedPropertyValue::Constant(PropertyValue::Pixels(20.0)),
+        ])
+    );
+}
+
+#[test]
+fn constant_override() {
+    let mut parse = NekoMaidParser::tokenize("const color = #000000;").unwrap();
+    parse.override_variable("color", "#ff0000").unwrap();
+
+    assert_eq!(
+        parse.finish().unwrap_err(),
+        NekoMaidParseError::ConstantOverridden {
+            name: "color".into()
+        }
+    );
+}
+
+#[test]
+fn non_constant_override() {
+    let mut parse = NekoMaidParser::tokenize("var color = #000000;").unwrap();
+
+    for value in ["$nope", "$color", "1px $color"] {
+        assert_eq!(
+            parse.override_variable("color", value),
+            Err(NekoMaidParseError::NonConstantOverride {
+                name: "color".into()
+            })
+        );
+    }
+    assert!(parse.finish().is_ok());
+}
+
+#[test]
+fn undeclared_variable_override() {
+    let mut parse = NekoMaidParser::tokenize("var color = #000000;").unwrap();
+    parse.override_variable("size", "10px").unwrap();
+    let module = parse.finish().unwrap();
+
+    assert_eq!(
+        module.warnings(),
+        &[NekoMaidParseWarning::UndeclaredVariableOverride {
+            name: "size".into()
+        }]
+    );
+
+    let global = module.scope.get(ScopeId(0)).unwrap();
+    assert!(global.variables().all(|(name, _)| name != "size"));
+}
+
+#[test]
+fn merge_imported_styles() {
+    let parse_module = |source: &str| {
+        let mut parse = NekoMaidParser::tokenize(source).unwrap();
+        parse.register_native_widget(native("div"));
+        parse
+    };
+
+    let a = parse_module("style div { width: 10px; height: 5px; }")
+        .finish()
+        .unwrap();
+    let b = parse_module("var unused = 1;\nstyle div { width: 20px; }")
+        .finish()
+        .unwrap();
+
+    let mut parse = parse_module(
+        r#"
+import "a";
+import "b";
+
+layout div {}
+        "#,
+    );
+    parse.add_module("a".into(), a);
+    parse.add_module("b".into(), b);
+    let module = parse.finish().unwrap();
+
+    assert_eq!(module.styles.len(), 1);
+    assert_eq!(module.elements[0].element.styles.len(), 1);
+
+    let style = module.styles[0].scope_id;
+    let property = |name: &str| {
+        let name = ScopeName::Property(name.into(), style);
+        module.scope.get_entry(&name).unwrap().unresolved.clone()
+    };
+
+    // the properties of later imports take precedence
+    assert_eq!(
+        property("width"),
+        UnresolvedPropertyValue::Constant(PropertyValue::Pixels(20.0))
+    );
+    assert_eq!(
+        property("height"),
+        UnresolvedPropertyValue::Constant(PropertyValue::Pixels(5.0))
+    );
+}
+
+#[test]
+fn viewport_condition() {
+    const SOURCE: &str = r#"
+style div {
+    width: 300px;
+}
+
+@when width <= 600px {
+    style div {
+        width: 100%;
+    }
+}
+    "#;
+
+    let mut parse = NekoMaidParser::tokenize(SOURCE).unwrap();
+    parse.register_native_widget(native("div"));
+    let module = parse.finish().unwrap();
+
+    assert_eq!(module.styles.len(), 2);
+    assert_eq!(module.styles[0].condition(), None);
+
+    let condition = module.styles[1].condition().unwrap();
+    assert_eq!(condition.to_string(), "width <= 600px");
+    assert!(condition.holds(Vec2::new(600.0, 800.0)));
+    assert!(!condition.holds(Vec2::new(601.0, 800.0)));
+
+    assert_eq!(
+        module.to_string(),
+        "style div {\n    width: 300px;\n}\n\n@when width <= 600px {\n    style div {\n        width: 100%;\n    }\n}\n"
+    );
+}
+
+#[test]
+fn format_round_trip() {
+    const SLOTS: &str = include_str!("../../assets/slots.neko_ui");
+    const FEATURES: &str = r#"
+const gap = 4px;
+var spacing = 1px 2px;
+var selected = true;
+
+def card {
+    var title = "Card";
+
+    layout div {
+        const inner = 2px;
+        padding: $inner;
+
+        output required default;
+    }
+}
+
+style div +panel ** p {
+    color: #ff0000;
+}
+
+@when width <= 600px {
+    style div {
+        width: 100%;
+    }
+}
+
+layout card {
+    class panel;
+    class-if selected: $selected;
+    component enemy;
+    component health: 100;
+    margin: $gap;
+    padding: $spacing;
+
+    with p {
+        text: "Hello";
+    }
+}
+    "#;
+
+    fn parse(source: &str) -> Module {
+        let mut parse = NekoMaidParser::tokenize(source).unwrap();
+        parse.register_native_widget(native("div"));
+        parse.register_native_widget(native("p"));
+        parse.finish().unwrap()
+    }
+
+    fn structure(module: &Module) -> Vec<(String, Vec<String>, Option<usize>, usize)> {
+        module
+            .iter_elements()
+            .map(|element| {
+                let mut classes = element.classes.iter().cloned().collect::<Vec<_>>();
+                classes.sort();
+                (
+                    element.widget.to_string(),
+                    classes,
+                    element.parent,
+                    element.depth,
+                )
+            })
+            .collect()
+    }
+
+    for source in [SLOTS, FEATURES] {
+        let module = parse(source);
+        let printed = module.to_string();
+        let reparsed = parse(&printed);
+
+        assert_eq!(printed, reparsed.to_string());
+        assert_eq!(structure(&module), structure(&reparsed));
+        assert_eq!(module.variables(), reparsed.variables());
+    }
+
+    let printed = parse(FEATURES).to_string();
+    assert!(printed.contains("const gap = 4px;"));
+    assert!(printed.contains("const inner = 2px;"));
+}
+
+#[test]
+fn native_default_style() {
+    const SOURCE: &str = r#"
+style p +title {
+    color: #ff0000;
+}
+
+layout p {}
+    "#;
+
+    let mut parse = NekoMaidParser::tokenize(SOURCE).unwrap();
+    parse.register_native_widget(NativeWidget {
+        default_style: HashMap::from([("color".to_string(), PropertyValue::Color(Color::WHITE))]),
+        ..native("p")
+    });
+    let module = parse.finish().unwrap();
+
+    let element = &module.elements[0].element;
+    let styles = element
+        .styles
+        .iter()
+        .map(|s| s.value.selector())
+        .collect::<Vec<_>>();
+    assert_eq!(styles.len(), 2);
+    assert_eq!(styles[0].hierarchy[0].widget, "p");
+    assert!(styles[0].hierarchy[0].whitelist.is_empty());
+    assert_eq!(
+        styles[1].hierarchy[0].whitelist,
+        HashSet::from(["title".to_string()])
+    );
+    assert!(element.styles[0].active);
+    assert!(!element.styles[1].active);
+}
+
+#[test]
+fn conditional_imports() {
+    const SOURCE: &str = r#"
+var platform = "desktop";
+
+import "mobile" if $platform == "mobile";
+import "desktop" if $platform != "mobile";
+    "#;
+
+    let load = |source: &str| {
+        let mut parse = NekoMaidParser::tokenize(source).unwrap();
+        parse.register_native_widget(native("div"));
+        parse.register_native_widget(native("p"));
+        parse.finish().unwrap()
+    };
+
+    let parse = |platform: Option<&str>| {
+        let mut parse = NekoMaidParser::tokenize(SOURCE).unwrap();
+        parse.register_native_widget(native("div"));
+        parse.register_native_widget(native("p"));
+        if let Some(platform) = platform {
+            parse.override_variable("platform", platform).unwrap();
+        }
+        let imports = parse.predict_imports();
+        parse.add_module("mobile".into(), load("layout p {}"));
+        parse.add_module("desktop".into(), load("layout div {}"));
+        (imports, parse.finish())
+    };
+
+    let widgets = |module: &Module| {
+        module
+            .iter_elements()
+            .map(|element| element.widget.to_string())
+            .collect::<Vec<_>>()
+    };
+
+    // the declared value is only known once the module is parsed
+    let (imports, module) = parse(None);
+    assert_eq!(imports, vec!["mobile", "desktop"]);
+    assert_eq!(widgets(&module.unwrap()), vec!["div"]);
+
+    let (imports, module) = parse(Some(r#""mobile""#));
+    assert_eq!(imports, vec!["mobile"]);
+    assert_eq!(widgets(&module.unwrap()), vec!["p"]);
+
+    let mut parse = NekoMaidParser::tokenize(r#"import "mobile" if $os == "ios";"#).unwrap();
+    parse.add_module("mobile".into(), load("layout p {}"));
+    assert_eq!(
+        parse.finish().unwrap_err(),
+        NekoMaidParseError::VariableNotFound {
+            variable: "os".into(),
+            position: TokenPosition::new(1, 21, 2),
+        }
+    );
+}
+
+#[test]
+fn module_variables() {
+    const SOURCE: &str = r#"
+import "theme";
+
+var padding = 4px 8px;
+var size = 50%;
+var title = "Hello";
+var highlight = $accent;
+var visible = true;
+    "#;
+
+    let theme = NekoMaidParser::tokenize("const accent = #ff0000;")
+        .unwrap()
+        .finish()
+        .unwrap();
+
+    let mut parse = NekoMaidParser::tokenize(SOURCE).unwrap();
+    parse.add_module("theme".into(), theme);
+    let module = parse.finish().unwrap();
+
+    let variables = module
+        .variables()
+        .into_iter()
+        .map(|v| (v.name, v.value_type, v.constant))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        variables,
+        vec![
+            ("accent", Some(PropertyType::Color), true),
+            ("highlight", Some(PropertyType::Color), false),
+            ("padding", Some(PropertyType::List), false),
+            ("size", Some(PropertyType::Percentage), false),
+            ("title", Some(PropertyType::String), false),
+            ("visible", Some(PropertyType::Boolean), false),
+        ]
+    );
+}
+
+#[test]
+fn bound_member_variables() {
+    const SOURCE: &str = r#"
+var opacity = 1;
+
+layout p {
+    text: $player.name;
+    width: $pair.0;
+    opacity:.5;
+}
+    "#;
+
+    let mut parse = NekoMaidParser::tokenize(SOURCE).unwrap();
+    parse.register_native_widget(native("p"));
+    let module = parse.finish().unwrap();
+
+    let variables = module
+        .variables()
+        .into_iter()
+        .map(|v| v.name)
+        .collect::<Vec<_>>();
+    assert_eq!(variables, vec!["opacity"]);
+
+    let formatted = module.to_string();
+    assert!(formatted.contains("text: $player.name;"));
+    assert!(formatted.contains("width: $pair.0;"));
+    assert!(!formatted.contains("var player.name"));
+}
@@ -200,6 +200,9 @@ pub(crate) enum TokenType {
     /// The exclamation symbol.
     Exclamation,
 
+    /// The double star symbol.
+    DoubleStar,
+
     /// The semicolon symbol.
     Semicolon,
 
@@ -215,6 +218,33 @@ pub(crate) enum TokenType {
     /// The equals symbol.
     Equals,
 
+    /// The double equals symbol.
+    DoubleEquals,
+
+    /// The not equals symbol.
+    NotEquals,
+
+    /// The less than symbol.
+    LessThan,
+
+    /// The less than or equal symbol.
+    LessEquals,
+
+    /// The greater than symbol.
+    GreaterThan,
+
+    /// The greater than or equal symbol.
+    GreaterEquals,
+
+    /// The star symbol.
+    Star,
+
+    /// The slash symbol.
+    Slash,
+
+    /// The minus symbol.
+    Minus,
+
     // === Keywords ===
     /// The `import` keyword.
     ImportKeyword,
@@ -225,6 +255,12 @@ pub(crate) enum TokenType {
     /// The `var` keyword.
     VarKeyword,
 
+    /// The `const` keyword.
+    ConstKeyword,
+
+    /// The `property` keyword.
+    PropertyKeyword,
+
     /// The `layout` keyword.
     LayoutKeyword,
 
@@ -237,12 +273,27 @@ pub(crate) enum TokenType {
     /// The `class` keyword.
     ClassKeyword,
 
+    /// The `class-if` keyword.
+    ClassIfKeyword,
+
+    /// The `component` keyword.
+    ComponentKeyword,
+
     /// The `output` keyword.
     OutputKeyword,
 
     /// The `in` keyword.
     InKeyword,
 
+    /// The `extends` keyword.
+    ExtendsKeyword,
+
+    /// The `if` keyword.
+    IfKeyword,
+
+    /// The `@when` keyword.
+    WhenKeyword,
+
     // === Literals ===
     /// A boolean literal.
     BooleanLiteral,
@@ -269,6 +320,9 @@ pub(crate) enum TokenType {
     /// An identifier token.
     Identifier,
 
+    /// A member access on a variable, such as `.name`.
+    Member,
+
     // === Ignore ===
     /// A comment token.
     Comment,
@@ -283,20 +337,37 @@ impl TokenType {
         match self {
             TokenType::Plus => "+",
             TokenType::Exclamation => "!",
+            TokenType::DoubleStar => "**",
             TokenType::Semicolon => ";",
             TokenType::Colon => ":",
             TokenType::OpenBrace => "{",
             TokenType::CloseBrace => "}",
             TokenType::Equals => "=",
+            TokenType::DoubleEquals => "==",
+            TokenType::NotEquals => "!=",
+            TokenType::LessThan => "<",
+            TokenType::LessEquals => "<=",
+            TokenType::GreaterThan => ">",
+            TokenType::GreaterEquals => ">=",
+            TokenType::Star => "*",
+            TokenType::Slash => "/",
+            TokenType::Minus => "-",
             TokenType::ImportKeyword => "import",
             TokenType::StyleKeyword => "style",
             TokenType::VarKeyword => "var",
+            TokenType::ConstKeyword => "const",
+            TokenType::PropertyKeyword => "property",
             TokenType::LayoutKeyword => "layout",
             TokenType::WithKeyword => "with",
             TokenType::DefKeyword => "def",
             TokenType::ClassKeyword => "class",
+            TokenType::ClassIfKeyword => "class-if",
+            TokenType::ComponentKeyword => "component",
             TokenType::OutputKeyword => "output",
             TokenType::InKeyword => "in",
+            TokenType::ExtendsKeyword => "extends",
+            TokenType::IfKeyword => "if",
+            TokenType::WhenKeyword => "@when",
             TokenType::BooleanLiteral => "boolean",
             TokenType::ColorLiteral => "color",
             TokenType::NumberLiteral => "number",
@@ -305,6 +376,7 @@ impl TokenType {
             TokenType::StringLiteral => "string",
             TokenType::Variable => "variable",
             TokenType::Identifier => "identifier",
+            TokenType::Member => "member",
             TokenType::Comment => "comment",
             TokenType::EndOfStream => "EOS",
         }
@@ -314,7 +386,10 @@ impl TokenType {
     pub(crate) fn has_string(&self) -> bool {
         matches!(
             self,
-            TokenType::Identifier | TokenType::StringLiteral | TokenType::Variable
+            TokenType::Identifier
+                | TokenType::StringLiteral
+                | TokenType::Variable
+                | TokenType::Member
         )
     }
 
@@ -336,6 +411,26 @@ impl TokenType {
         matches!(self, TokenType::ColorLiteral)
     }
 
+    /// Returns true if the token type is an operator that could be part of an
+    /// expression, such as `$base * 2`.
+    pub(crate) fn is_operator(&self) -> bool {
+        matches!(
+            self,
+            TokenType::Plus
+                | TokenType::Minus
+                | TokenType::Star
+                | TokenType::DoubleStar
+                | TokenType::Slash
+                | TokenType::Exclamation
+                | TokenType::DoubleEquals
+                | TokenType::NotEquals
+                | TokenType::LessThan
+                | TokenType::LessEquals
+                | TokenType::GreaterThan
+                | TokenType::GreaterEquals
+        )
+    }
+
     /// Returns true if the token type should be ignored by the tokenizer.
     pub(crate) fn is_ignore(&self) -> bool {
         matches!(self, TokenType::Comment | TokenType::EndOfStream)
@@ -11,7 +11,14 @@ lazy_static! {
     static ref TOKENS: Vec<(TokenType, Regex)> = vec![
         // symbols
         (TokenType::Plus,            Regex::new(r"^\s*(\+)").unwrap()),
+        (TokenType::NotEquals,       Regex::new(r"^\s*(!=)").unwrap()),
+        (TokenType::DoubleEquals,    Regex::new(r"^\s*(==)").unwrap()),
         (TokenType::Exclamation,     Regex::new(r"^\s*(!)").unwrap()),
+        (TokenType::LessEquals,      Regex::new(r"^\s*(<=)").unwrap()),
+        (TokenType::LessThan,        Regex::new(r"^\s*(<)").unwrap()),
+        (TokenType::GreaterEquals,   Regex::new(r"^\s*(>=)").unwrap()),
+        (TokenType::GreaterThan,     Regex::new(r"^\s*(>)").unwrap()),
+        (TokenType::DoubleStar,      Regex::new(r"^\s*(\*\*)").unwrap()),
         (TokenType::Semicolon,       Regex::new(r"^\s*(;)").unwrap()),
         (TokenType::Colon,           Regex::new(r"^\s*(:)").unwrap()),
         (TokenType::OpenBrace,       Regex::new(r"^\s*(\{)").unwrap()),
@@ -22,12 +29,19 @@ lazy_static! {
         (TokenType::ImportKeyword,   Regex::new(r"^\s*(import)\b").unwrap()),
         (TokenType::StyleKeyword,    Regex::new(r"^\s*(style)\b").unwrap()),
         (TokenType::VarKeyword,      Regex::new(r"^\s*(var)\b").unwrap()),
+        (TokenType::ConstKeyword,    Regex::new(r"^\s*(const)\b").unwrap()),
+        (TokenType::PropertyKeyword, Regex::new(r"^\s*(property)\b").unwrap()),
         (TokenType::LayoutKeyword,   Regex::new(r"^\s*(layout)\b").unwrap()),
         (TokenType::WithKeyword,     Regex::new(r"^\s*(with)\b").unwrap()),
         (TokenType::DefKeyword,      Regex::new(r"^\s*(def)\b").unwrap()),
+        (TokenType::ClassIfKeyword,  Regex::new(r"^\s*(class-if)\b").unwrap()),
         (TokenType::ClassKeyword,    Regex::new(r"^\s*(class)\b").unwrap()),
+        (TokenType::ComponentKeyword, Regex::new(r"^\s*(component)\b").unwrap()),
         (TokenType::OutputKeyword,   Regex::new(r"^\s*(output)\b").unwrap()),
         (TokenType::InKeyword,   Regex::new(r"^\s*(in)\b").unwrap()),
+        (TokenType::ExtendsKeyword,  Regex::new(r"^\s*(extends)\b").unwrap()),
+        (TokenType::IfKeyword,       Regex::new(r"^\s*(if)\b").unwrap()),
+        (TokenType::WhenKeyword,     Regex::new(r"^\s*(@when)\b").unwrap()),
 
         // literals
         (TokenType::BooleanLiteral,  Regex::new(r"^\s*([Tt]rue|[Ff]alse)\b").unwrap()),
@@ -35,9 +49,9 @@ lazy_static! {
         (TokenType::PercentLiteral,  Regex::new(r"^\s*(-?\d+\.?\d*|-?\d*\.\d+)%").unwrap()),
         (TokenType::PixelsLiteral,   Regex::new(r"^\s*(-?\d+\.?\d*|-?\d*\.\d+)px\b").unwrap()),
         (TokenType::NumberLiteral,   Regex::new(r"^\s*(-?\d+\.?\d*|-?\d*\.\d+)").unwrap()),
-        (TokenType::StringLiteral,   Regex::new(r#"^\s*"(.*)""#).unwrap()),
-        (TokenType::StringLiteral,   Regex::new(r#"^\s*'(.*)'"#).unwrap()),
-        (TokenType::StringLiteral,   Regex::new(r#"^\s*`(.*)`"#).unwrap()),
+        (TokenType::StringLiteral,   Regex::new(r#"^\s*"([^"\n]*)""#).unwrap()),
+        (TokenType::StringLiteral,   Regex::new(r#"^\s*'([^'\n]*)'"#).unwrap()),
+        (TokenType::StringLiteral,   Regex::new(r#"^\s*`([^`\n]*)`"#).unwrap()),
 
         // non-literals
         (TokenType::Variable,        Regex::new(r"^\s*\$([a-zA-Z_][a-zA-Z0-9_-]*)").unwrap()),
@@ -45,8 +59,19 @@ lazy_static! {
 
         // ignore
         (TokenType::Comment,         Regex::new(r"^\s*//(.*)(?:\n|$)").unwrap()),
+
+        // operators, after comments and negative numbers so they do not match
+        // the start of either
+        (TokenType::Star,            Regex::new(r"^\s*(\*)").unwrap()),
+        (TokenType::Slash,           Regex::new(r"^\s*(/)").unwrap()),
+        (TokenType::Minus,           Regex::new(r"^\s*(-)").unwrap()),
         (TokenType::EndOfStream,     Regex::new(r"^(\s*)$").unwrap()),
     ];
+
+    /// A member access directly following a variable, such as `.name` in
+    /// `$player.name` or `.0` in `$pair.0`. Only tried after a variable or
+    /// another member so `.5` keeps tokenizing as a number everywhere else.
+    static ref MEMBER: Regex = Regex::new(r"^\.([a-zA-Z0-9_][a-zA-Z0-9_-]*)").unwrap();
 }
 
 /// A position within the source code.
@@ -84,6 +109,18 @@ impl Tokenizer {
         let mut tokens = Vec::new();
 
         'outer: while position.index < code.len() {
+            if matches!(
+                tokens.last(),
+                Some(Token {
+                    token_type: TokenType::Variable | TokenType::Member,
+                    ..
+                })
+            ) && let Some(t) = try_token(code, &mut position, &MEMBER, TokenType::Member)
+            {
+                tokens.push(t);
+                continue;
+            }
+
             for (token_type, regex) in TOKENS.iter() {
                 if let Some(t) = try_token(code, &mut position, regex, *token_type) {
                     if !t.token_type.is_ignore() {
@@ -322,4 +359,15 @@ but not here";
         assert_eq!(tokens[2].token_type, TokenType::StringLiteral);
         assert_eq!(tokens[2].value, "backtick".into());
     }
+
+    #[test]
+    fn string_ends_at_first_quote() {
+        let tokens = Tokenizer::tokenize(r#""a b" "c d""#).unwrap();
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[0].value, "a b".into());
+        assert_eq!(tokens[1].value, "c d".into());
+
+        // like before, strings cannot span several lines
+        assert!(Tokenizer::tokenize("\"a\nb\"").is_err());
+    }
 }
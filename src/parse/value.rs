@@ -27,6 +27,9 @@ pub enum PropertyValue {
 
     /// A pixel number value.
     Pixels(f64),
+
+    /// A list of values, such as the two values of `overflow: hidden scroll;`.
+    List(Vec<PropertyValue>),
 }
 
 impl PropertyValue {
@@ -39,6 +42,69 @@ impl PropertyValue {
             PropertyValue::Color(_) => PropertyType::Color,
             PropertyValue::Percent(_) => PropertyType::Percentage,
             PropertyValue::Pixels(_) => PropertyType::Pixels,
+            PropertyValue::List(_) => PropertyType::List,
+        }
+    }
+
+    /// Returns this value as a UI length.
+    ///
+    /// Numbers are treated as pixels and the string `"auto"` is converted to
+    /// [`Val::Auto`]. Returns `None` for any other value.
+    pub fn as_val(&self) -> Option<Val> {
+        match self {
+            PropertyValue::String(s) if s == "auto" => Some(Val::Auto),
+            PropertyValue::Pixels(n) => Some(Val::Px(*n as f32)),
+            PropertyValue::Percent(n) => Some(Val::Percent(*n as f32)),
+            PropertyValue::Number(n) => Some(Val::Px(*n as f32)),
+            _ => None,
+        }
+    }
+
+    /// Returns this value as a color, if it is one.
+    ///
+    /// Strings holding a hex code, such as `"#ff0000"`, or a named color, such
+    /// as `"red"`, are converted as well.
+    pub fn as_color(&self) -> Option<Color> {
+        match self {
+            PropertyValue::Color(c) => Some(*c),
+            PropertyValue::String(s) if s.starts_with('#') => Srgba::hex(s).ok().map(Color::from),
+            PropertyValue::String(s) => named_color(s),
+            _ => None,
+        }
+    }
+
+    /// Returns this value as a number, if it is one.
+    pub fn as_f32(&self) -> Option<f32> {
+        match self {
+            PropertyValue::Number(n) => Some(*n as f32),
+            _ => None,
+        }
+    }
+
+    /// Returns this value as a boolean, if it is one.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            PropertyValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// Returns the values of this list, or this value alone if it is not a
+    /// list.
+    ///
+    /// This is used by shorthand properties, which accept one or more values.
+    pub fn as_list(&self) -> &[PropertyValue] {
+        match self {
+            PropertyValue::List(values) => values,
+            _ => std::slice::from_ref(self),
+        }
+    }
+
+    /// Returns this value as a string slice, if it is a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            PropertyValue::String(s) => Some(s),
+            _ => None,
         }
     }
 }
@@ -94,44 +160,87 @@ impl fmt::Display for PropertyValue {
             PropertyValue::Percent(p) => write!(f, "{}%", p),
             PropertyValue::Pixels(px) => write!(f, "{}px", px),
             PropertyValue::Color(c) => write!(f, "{}", c.to_srgba().to_hex()),
+            PropertyValue::List(values) => {
+                let values = values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+                write!(f, "{}", values.join(" "))
+            }
         }
     }
 }
 
 impl From<&PropertyValue> for Val {
     fn from(property: &PropertyValue) -> Self {
-        match property {
-            PropertyValue::String(s) if s == "auto" => Val::Auto,
-            PropertyValue::Pixels(n) => Val::Px(*n as f32),
-            PropertyValue::Percent(n) => Val::Percent(*n as f32),
-            PropertyValue::Number(n) => Val::Px(*n as f32),
-            _ => {
-                warn_once!("Failed to convert PropertyValue {} to Val", property);
-                Self::default()
-            }
-        }
-    }
-}
+        property.as_val().unwrap_or_else(|| {
+            warn_once!("Failed to convert PropertyValue {} to Val", property);
+            Self::default()
+        })
+    }
+}
+
+/// Returns the color with the given CSS name, ignoring case.
+///
+/// Only the basic CSS colors and `transparent` are supported.
+fn named_color(name: &str) -> Option<Color> {
+    use bevy::color::palettes::css;
+
+    let color = match name.to_ascii_lowercase().as_str() {
+        "transparent" => return Some(Color::NONE),
+        "black" => css::BLACK,
+        "silver" => css::SILVER,
+        "gray" | "grey" => css::GRAY,
+        "white" => css::WHITE,
+        "maroon" => css::MAROON,
+        "red" => css::RED,
+        "purple" => css::PURPLE,
+        "fuchsia" | "magenta" => css::FUCHSIA,
+        "green" => css::GREEN,
+        "lime" => css::LIME,
+        "olive" => css::OLIVE,
+        "yellow" => css::YELLOW,
+        "navy" => css::NAVY,
+        "blue" => css::BLUE,
+        "teal" => css::TEAL,
+        "aqua" | "cyan" => css::AQUA,
+        "orange" => css::ORANGE,
+        _ => return None,
+    };
+    Some(color.into())
+}
+
+/// The keyword that sets a color property to the text color of the element,
+/// inherited from its ancestors if the element has no `color` of its own.
+///
+/// On the `color` property itself, the keyword inherits the color of the
+/// ancestors, as if the property was not set.
+pub const CURRENT_COLOR: &str = "currentColor";
 
 impl From<&PropertyValue> for Color {
     fn from(property: &PropertyValue) -> Self {
-        match property {
-            PropertyValue::Color(c) => *c,
-            _ => {
-                warn_once!("Failed to convert PropertyValue {} to Color", property);
-                Self::default()
-            }
+        // the current color depends on the ancestors of the element, so it is
+        // resolved once the node properties are applied.
+        if property.as_str() == Some(CURRENT_COLOR) {
+            return Color::NONE;
         }
+
+        property.as_color().unwrap_or_else(|| {
+            warn_once!("Failed to convert PropertyValue {} to Color", property);
+            Self::default()
+        })
     }
 }
 
+/// Converts the values of the `overflow`, `overflow-x` and `overflow-y`
+/// properties.
+///
+/// `auto` is the same as `scroll` for all of them: the node scrolls, with the
+/// mouse wheel as well, but only moves once its content overflows.
 impl From<&PropertyValue> for OverflowAxis {
     fn from(property: &PropertyValue) -> Self {
         match property {
             PropertyValue::String(s) if s == "visible" => OverflowAxis::Visible,
             PropertyValue::String(s) if s == "clip" => OverflowAxis::Clip,
             PropertyValue::String(s) if s == "hidden" => OverflowAxis::Hidden,
-            PropertyValue::String(s) if s == "scroll" => OverflowAxis::Scroll,
+            PropertyValue::String(s) if s == "scroll" || s == "auto" => OverflowAxis::Scroll,
             _ => {
                 warn!(
                     "Failed to convert PropertyValue {} to OverflowAxis",
@@ -315,25 +424,19 @@ impl From<&PropertyValue> for JustifyContent {
 
 impl From<&PropertyValue> for f32 {
     fn from(property: &PropertyValue) -> Self {
-        match property {
-            PropertyValue::Number(n) => *n as f32,
-            _ => {
-                warn!("Failed to convert PropertyValue {} to f32", property);
-                Self::default()
-            }
-        }
+        property.as_f32().unwrap_or_else(|| {
+            warn!("Failed to convert PropertyValue {} to f32", property);
+            Self::default()
+        })
     }
 }
 
 impl From<&PropertyValue> for bool {
     fn from(property: &PropertyValue) -> Self {
-        match property {
-            PropertyValue::Bool(b) => *b,
-            _ => {
-                warn!("Failed to convert PropertyValue {} to bool", property);
-                Self::default()
-            }
-        }
+        property.as_bool().unwrap_or_else(|| {
+            warn!("Failed to convert PropertyValue {} to bool", property);
+            Self::default()
+        })
     }
 }
 
@@ -412,9 +515,9 @@ impl From<&PropertyValue> for GridAutoFlow {
 
 impl From<&PropertyValue> for String {
     fn from(property: &PropertyValue) -> Self {
-        match property {
-            PropertyValue::String(s) => s.clone(),
-            _ => {
+        match property.as_str() {
+            Some(s) => s.to_string(),
+            None => {
                 warn!("Failed to convert PropertyValue {} to String", property);
                 Self::default()
             }
@@ -422,10 +525,11 @@ impl From<&PropertyValue> for String {
     }
 }
 
+// Like in CSS, unitless line heights are relative to the font size.
 impl From<&PropertyValue> for LineHeight {
     fn from(property: &PropertyValue) -> Self {
         match property {
-            PropertyValue::Number(n) => LineHeight::Px(*n as f32),
+            PropertyValue::Number(n) => LineHeight::RelativeToFont(*n as f32),
             PropertyValue::Pixels(n) => LineHeight::Px(*n as f32),
             PropertyValue::Percent(n) => LineHeight::RelativeToFont(*n as f32 / 100.0),
             _ => {
@@ -481,3 +585,229 @@ impl From<&PropertyValue> for LineBreak {
         }
     }
 }
+
+/// The direction in which the content of a node flows.
+///
+/// Bevy UI has no notion of writing direction, so right-to-left layouts are
+/// emulated by mirroring the row direction and the `start`/`end` alignment of
+/// the node. The `left` and `right` offsets are physical and are not mirrored,
+/// and there is no `inset` shorthand.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutDirection {
+    /// Content flows from left to right.
+    #[default]
+    Ltr,
+
+    /// Content flows from right to left.
+    Rtl,
+}
+
+impl From<&PropertyValue> for LayoutDirection {
+    fn from(property: &PropertyValue) -> Self {
+        match property {
+            PropertyValue::String(s) if s == "ltr" => LayoutDirection::Ltr,
+            PropertyValue::String(s) if s == "rtl" => LayoutDirection::Rtl,
+            _ => {
+                warn!(
+                    "Failed to convert PropertyValue {} to LayoutDirection",
+                    property
+                );
+                Self::default()
+            }
+        }
+    }
+}
+
+/// How whitespace within the text of a text widget is handled.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum WhiteSpace {
+    /// Runs of whitespace are collapsed into a single space and lines wrap.
+    Normal,
+
+    /// Runs of whitespace are collapsed into a single space and lines never
+    /// wrap.
+    NoWrap,
+
+    /// Whitespace is preserved and lines only break at newlines.
+    Pre,
+
+    /// Whitespace is preserved and lines wrap.
+    #[default]
+    PreWrap,
+}
+
+impl WhiteSpace {
+    /// Returns whether runs of whitespace are collapsed.
+    pub fn collapses(&self) -> bool {
+        matches!(self, WhiteSpace::Normal | WhiteSpace::NoWrap)
+    }
+
+    /// Returns whether lines wrap when they are too long.
+    pub fn wraps(&self) -> bool {
+        matches!(self, WhiteSpace::Normal | WhiteSpace::PreWrap)
+    }
+}
+
+impl From<&PropertyValue> for WhiteSpace {
+    fn from(property: &PropertyValue) -> Self {
+        match property {
+            PropertyValue::String(s) if s == "normal" => WhiteSpace::Normal,
+            PropertyValue::String(s) if s == "nowrap" => WhiteSpace::NoWrap,
+            PropertyValue::String(s) if s == "pre" => WhiteSpace::Pre,
+            PropertyValue::String(s) if s == "pre-wrap" => WhiteSpace::PreWrap,
+            _ => {
+                warn!("Failed to convert PropertyValue {} to WhiteSpace", property);
+                Self::default()
+            }
+        }
+    }
+}
+
+/// The style of a font face, set with the `font-style` property.
+///
+/// The face is selected from the font family of the node, see
+/// [`FontFamily`](crate::font::FontFamily).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FontStyle {
+    /// The regular face of the family.
+    #[default]
+    Normal,
+
+    /// The italic face of the family.
+    Italic,
+}
+
+impl From<&PropertyValue> for FontStyle {
+    fn from(property: &PropertyValue) -> Self {
+        match property {
+            PropertyValue::String(s) if s == "normal" => FontStyle::Normal,
+            PropertyValue::String(s) if s == "italic" => FontStyle::Italic,
+            _ => {
+                warn!("Failed to convert PropertyValue {} to FontStyle", property);
+                Self::default()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn as_val() {
+        let auto = PropertyValue::String("auto".into());
+        assert_eq!(auto.as_val(), Some(Val::Auto));
+        assert_eq!(PropertyValue::Pixels(4.0).as_val(), Some(Val::Px(4.0)));
+        assert_eq!(PropertyValue::Number(4.0).as_val(), Some(Val::Px(4.0)));
+        assert_eq!(
+            PropertyValue::Percent(50.0).as_val(),
+            Some(Val::Percent(50.0))
+        );
+        assert_eq!(PropertyValue::Pixels(-8.0).as_val(), Some(Val::Px(-8.0)));
+        assert_eq!(
+            PropertyValue::Percent(-50.0).as_val(),
+            Some(Val::Percent(-50.0))
+        );
+        assert_eq!(PropertyValue::String("big".into()).as_val(), None);
+        assert_eq!(PropertyValue::Bool(true).as_val(), None);
+    }
+
+    #[test]
+    fn line_height() {
+        assert_eq!(
+            LineHeight::from(&PropertyValue::Number(1.5)),
+            LineHeight::RelativeToFont(1.5)
+        );
+        assert_eq!(
+            LineHeight::from(&PropertyValue::Percent(150.0)),
+            LineHeight::RelativeToFont(1.5)
+        );
+        assert_eq!(
+            LineHeight::from(&PropertyValue::Pixels(24.0)),
+            LineHeight::Px(24.0)
+        );
+    }
+
+    #[test]
+    fn content_distribution() {
+        let keyword = |s: &str| PropertyValue::String(s.into());
+
+        for (name, justify, align) in [
+            ("start", JustifyContent::Start, AlignContent::Start),
+            ("center", JustifyContent::Center, AlignContent::Center),
+            ("stretch", JustifyContent::Stretch, AlignContent::Stretch),
+            (
+                "space-between",
+                JustifyContent::SpaceBetween,
+                AlignContent::SpaceBetween,
+            ),
+            (
+                "space-around",
+                JustifyContent::SpaceAround,
+                AlignContent::SpaceAround,
+            ),
+            (
+                "space-evenly",
+                JustifyContent::SpaceEvenly,
+                AlignContent::SpaceEvenly,
+            ),
+        ] {
+            assert_eq!(JustifyContent::from(&keyword(name)), justify, "{name}");
+            assert_eq!(AlignContent::from(&keyword(name)), align, "{name}");
+        }
+
+        // unknown keywords are logged and fall back to the default.
+        assert_eq!(
+            JustifyContent::from(&keyword("space-evenlyy")),
+            JustifyContent::Default
+        );
+        assert_eq!(
+            AlignContent::from(&keyword("between")),
+            AlignContent::Default
+        );
+    }
+
+    #[test]
+    fn as_color() {
+        let red = Color::srgb(1.0, 0.0, 0.0);
+        assert_eq!(PropertyValue::Color(red).as_color(), Some(red));
+        assert_eq!(PropertyValue::String("red".into()).as_color(), Some(red));
+        assert_eq!(PropertyValue::String("Red".into()).as_color(), Some(red));
+        assert_eq!(
+            PropertyValue::String("#ff0000".into()).as_color(),
+            Some(red)
+        );
+        assert_eq!(
+            PropertyValue::String("transparent".into()).as_color(),
+            Some(Color::NONE)
+        );
+        assert_eq!(PropertyValue::String("#ff00000".into()).as_color(), None);
+        assert_eq!(PropertyValue::String("reddish".into()).as_color(), None);
+        assert_eq!(PropertyValue::Number(1.0).as_color(), None);
+    }
+
+    #[test]
+    fn color_from_string() {
+        let red = Color::srgb(1.0, 0.0, 0.0);
+        assert_eq!(Color::from(&PropertyValue::String("#ff0000".into())), red);
+        assert_eq!(Color::from(&PropertyValue::String("red".into())), red);
+    }
+
+    #[test]
+    fn as_f32() {
+        assert_eq!(PropertyValue::Number(1.5).as_f32(), Some(1.5));
+        assert_eq!(PropertyValue::Pixels(1.5).as_f32(), None);
+        assert_eq!(PropertyValue::Bool(true).as_f32(), None);
+    }
+
+    #[test]
+    fn as_bool_and_str() {
+        assert_eq!(PropertyValue::Bool(true).as_bool(), Some(true));
+        assert_eq!(PropertyValue::Number(1.0).as_bool(), None);
+        assert_eq!(PropertyValue::String("a".into()).as_str(), Some("a"));
+        assert_eq!(PropertyValue::Number(1.0).as_str(), None);
+    }
+}
@@ -0,0 +1,184 @@
+//! Handles parsing for `@when` blocks, which restrict styles to viewports of
+//! certain sizes.
+//!
+//! ```text
+//! @when width < 600px {
+//!     style div +sidebar {
+//!         width: 100%;
+//!     }
+//! }
+//! ```
+//!
+//! The viewport is the logical size of the primary window. Conditional styles
+//! are activated and deactivated as the window is resized.
+
+use std::fmt;
+
+use bevy::math::Vec2;
+
+use crate::parse::NekoMaidParseError;
+use crate::parse::context::{NekoResult, ParseContext};
+use crate::parse::style::{Selector, parse_style};
+use crate::parse::token::{TokenType, TokenValue};
+
+/// A dimension of the viewport a condition tests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViewportDimension {
+    /// The width of the viewport.
+    Width,
+
+    /// The height of the viewport.
+    Height,
+}
+
+/// A comparison between a viewport dimension and a size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    /// `<`
+    Less,
+
+    /// `<=`
+    LessEqual,
+
+    /// `>`
+    Greater,
+
+    /// `>=`
+    GreaterEqual,
+}
+
+/// A condition on the size of the viewport, such as `width < 600px`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ViewportCondition {
+    /// The dimension of the viewport tested.
+    pub dimension: ViewportDimension,
+
+    /// How the dimension is compared to the size.
+    pub comparison: Comparison,
+
+    /// The size, in logical pixels, the dimension is compared to.
+    pub size: f32,
+}
+
+impl ViewportCondition {
+    /// Returns whether this condition holds for a viewport of the given
+    /// logical size.
+    pub fn holds(&self, viewport: Vec2) -> bool {
+        let value = match self.dimension {
+            ViewportDimension::Width => viewport.x,
+            ViewportDimension::Height => viewport.y,
+        };
+
+        match self.comparison {
+            Comparison::Less => value < self.size,
+            Comparison::LessEqual => value <= self.size,
+            Comparison::Greater => value > self.size,
+            Comparison::GreaterEqual => value >= self.size,
+        }
+    }
+}
+
+impl fmt::Display for ViewportCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let dimension = match self.dimension {
+            ViewportDimension::Width => "width",
+            ViewportDimension::Height => "height",
+        };
+        let comparison = match self.comparison {
+            Comparison::Less => "<",
+            Comparison::LessEqual => "<=",
+            Comparison::Greater => ">",
+            Comparison::GreaterEqual => ">=",
+        };
+        write!(f, "{dimension} {comparison} {}px", self.size)
+    }
+}
+
+/// Parses a `@when` block, along with the styles declared in it.
+pub(super) fn parse_when(ctx: &mut ParseContext) -> NekoResult<()> {
+    ctx.expect(TokenType::WhenKeyword)?;
+    let condition = parse_viewport_condition(ctx)?;
+    ctx.expect(TokenType::OpenBrace)?;
+
+    ctx.set_style_condition(Some(condition));
+    while let Some(next) = ctx.peek() {
+        match next.token_type {
+            TokenType::StyleKeyword => parse_style(ctx, Selector::default())?,
+            TokenType::CloseBrace => break,
+            _ => {
+                return Err(NekoMaidParseError::UnexpectedToken {
+                    expected: vec![
+                        TokenType::StyleKeyword.type_name().to_string(),
+                        TokenType::CloseBrace.type_name().to_string(),
+                    ],
+                    found: next.token_type.type_name().to_string(),
+                    position: next.position,
+                });
+            }
+        }
+    }
+    ctx.set_style_condition(None);
+
+    ctx.expect(TokenType::CloseBrace)?;
+    Ok(())
+}
+
+/// Parses a viewport condition, such as `width < 600px`.
+fn parse_viewport_condition(ctx: &mut ParseContext) -> NekoResult<ViewportCondition> {
+    let token = ctx.consume()?;
+    let dimension = match (token.token_type, &token.value) {
+        (TokenType::Identifier, TokenValue::String(name)) if name == "width" => {
+            ViewportDimension::Width
+        }
+        (TokenType::Identifier, TokenValue::String(name)) if name == "height" => {
+            ViewportDimension::Height
+        }
+        _ => {
+            return Err(NekoMaidParseError::UnexpectedToken {
+                expected: vec!["width".to_string(), "height".to_string()],
+                found: token.token_type.type_name().to_string(),
+                position: token.position,
+            });
+        }
+    };
+
+    let token = ctx.consume()?;
+    let comparison = match token.token_type {
+        TokenType::LessThan => Comparison::Less,
+        TokenType::LessEquals => Comparison::LessEqual,
+        TokenType::GreaterThan => Comparison::Greater,
+        TokenType::GreaterEquals => Comparison::GreaterEqual,
+        _ => {
+            return Err(NekoMaidParseError::UnexpectedToken {
+                expected: vec![
+                    TokenType::LessThan.type_name().to_string(),
+                    TokenType::LessEquals.type_name().to_string(),
+                    TokenType::GreaterThan.type_name().to_string(),
+                    TokenType::GreaterEquals.type_name().to_string(),
+                ],
+                found: token.token_type.type_name().to_string(),
+                position: token.position,
+            });
+        }
+    };
+
+    let token = ctx.consume()?;
+    let size = match (token.token_type, &token.value) {
+        (TokenType::PixelsLiteral | TokenType::NumberLiteral, TokenValue::Number(size)) => {
+            *size as f32
+        }
+        _ => {
+            return Err(NekoMaidParseError::UnexpectedToken {
+                expected: vec![TokenType::PixelsLiteral.type_name().to_string()],
+                found: token.token_type.type_name().to_string(),
+                position: token.position,
+            });
+        }
+    };
+
+    Ok(ViewportCondition {
+        dimension,
+        comparison,
+        size,
+    })
+}
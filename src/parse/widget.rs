@@ -9,14 +9,21 @@ use crate::parse::NekoMaidParseError;
 use crate::parse::context::{NekoResult, ParseContext};
 use crate::parse::element::NekoElement;
 use crate::parse::layout::{Layout, parse_layout};
-use crate::parse::property::{UnresolvedPropertyValue, parse_variable};
+use crate::parse::property::{
+    PropertyType,
+    UnresolvedPropertyValue,
+    parse_unresolved_property,
+    parse_variable,
+    record_property_position,
+};
 use crate::parse::token::{TokenPosition, TokenType};
+use crate::parse::value::PropertyValue;
 
 /// A NekoMaid UI widget definition.
 #[derive(Debug, Clone, PartialEq)]
 pub(crate) enum Widget {
     /// A custom widget defined in NekoMaid UI.
-    Custom(CustomWidget),
+    Custom(Box<CustomWidget>),
 
     /// A native widget provided by the NekoMaid UI system.
     Native(NativeWidget),
@@ -30,6 +37,21 @@ impl Widget {
             Widget::Native(native) => &native.name,
         }
     }
+
+    /// Returns the properties of the default style of the widget.
+    pub fn default_style(&self) -> HashMap<String, UnresolvedPropertyValue> {
+        match self {
+            Widget::Custom(custom) => custom.default_style.clone(),
+            Widget::Native(native) => native
+                .default_style
+                .iter()
+                .map(|(name, value)| {
+                    let value = UnresolvedPropertyValue::Constant(value.clone());
+                    (name.clone(), value)
+                })
+                .collect(),
+        }
+    }
 }
 
 /// A custom widget definition.
@@ -41,8 +63,72 @@ pub(crate) struct CustomWidget {
     /// The default properties of the widget.
     pub default_properties: HashMap<String, UnresolvedPropertyValue>,
 
+    /// The properties that must be provided wherever the widget is used, and
+    /// their types.
+    pub required_properties: HashMap<String, PropertyType>,
+
     /// The layout of the widget.
     pub layout: Layout,
+
+    /// The properties applied to the widget before any style, written in a
+    /// `style` block of the widget definition.
+    pub default_style: HashMap<String, UnresolvedPropertyValue>,
+}
+
+impl CustomWidget {
+    /// Returns whether this widget declares an output slot with the given
+    /// name.
+    pub fn has_output(&self, name: &str) -> bool {
+        fn f(layout: &Layout, name: &str) -> bool {
+            layout.slots.iter().any(|s| s.name == name)
+                || layout
+                    .children_slots
+                    .values()
+                    .flatten()
+                    .any(|child| f(child, name))
+        }
+
+        f(&self.layout, name)
+    }
+
+    /// Returns the names of the output slots declared by this widget, sorted
+    /// by name.
+    pub fn outputs(&self) -> Vec<&str> {
+        fn f<'a>(layout: &'a Layout, outputs: &mut Vec<&'a str>) {
+            outputs.extend(layout.slots.iter().map(|s| s.name.as_str()));
+            for child in layout.children_slots.values().flatten() {
+                f(child, outputs);
+            }
+        }
+
+        let mut outputs = vec![];
+        f(&self.layout, &mut outputs);
+        outputs.sort();
+        outputs.dedup();
+        outputs
+    }
+
+    /// Returns the names of the output slots declared as required by this
+    /// widget, sorted by name.
+    pub fn required_outputs(&self) -> Vec<&str> {
+        fn f<'a>(layout: &'a Layout, outputs: &mut Vec<&'a str>) {
+            outputs.extend(
+                layout
+                    .slots
+                    .iter()
+                    .filter(|s| s.required)
+                    .map(|s| s.name.as_str()),
+            );
+            for child in layout.children_slots.values().flatten() {
+                f(child, outputs);
+            }
+        }
+
+        let mut outputs = vec![];
+        f(&self.layout, &mut outputs);
+        outputs.sort();
+        outputs
+    }
 }
 
 /// A native widget definition.
@@ -56,6 +142,12 @@ pub struct NativeWidget {
     /// This function takes a mutable reference to `Commands` and the parent
     /// entity, and returns the spawned widget entity.
     pub spawn_func: fn(&Res<AssetServer>, &mut Commands, &NekoElement, Entity) -> Entity,
+
+    /// The properties applied to the widget before any style.
+    ///
+    /// They have the lowest precedence, so any style or layout setting the
+    /// same property overrides them.
+    pub default_style: HashMap<String, PropertyValue>,
 }
 
 impl PartialEq<NativeWidget> for NativeWidget {
@@ -72,9 +164,16 @@ pub(super) fn parse_widget(ctx: &mut ParseContext) -> NekoResult<Widget> {
     let name = ctx.expect_as_string(TokenType::Identifier)?;
     ctx.set_current_widget(Some(name.clone()));
 
+    let base = match ctx.maybe_consume(TokenType::ExtendsKeyword) {
+        Some(_) => Some(parse_widget_base(ctx, &name)?),
+        None => None,
+    };
+
     ctx.expect(TokenType::OpenBrace)?;
 
     let mut properties = HashMap::new();
+    let mut required_properties = HashMap::new();
+    let mut default_style = HashMap::new();
     let mut layout = None;
 
     while let Some(next) = ctx.peek() {
@@ -83,6 +182,13 @@ pub(super) fn parse_widget(ctx: &mut ParseContext) -> NekoResult<Widget> {
                 let property = parse_variable(ctx)?;
                 properties.insert(property.name, property.value);
             }
+            TokenType::PropertyKeyword => {
+                let (name, property_type) = parse_required_property(ctx)?;
+                required_properties.insert(name, property_type);
+            }
+            TokenType::StyleKeyword => {
+                default_style.extend(parse_default_style(ctx)?);
+            }
             TokenType::LayoutKeyword => {
                 if layout.is_some() {
                     return Err(NekoMaidParseError::MultipleLayoutsDefined {
@@ -98,6 +204,8 @@ pub(super) fn parse_widget(ctx: &mut ParseContext) -> NekoResult<Widget> {
                 return Err(NekoMaidParseError::UnexpectedToken {
                     expected: vec![
                         TokenType::VarKeyword.type_name().to_string(),
+                        TokenType::PropertyKeyword.type_name().to_string(),
+                        TokenType::StyleKeyword.type_name().to_string(),
                         TokenType::LayoutKeyword.type_name().to_string(),
                         TokenType::CloseBrace.type_name().to_string(),
                     ],
@@ -110,6 +218,26 @@ pub(super) fn parse_widget(ctx: &mut ParseContext) -> NekoResult<Widget> {
 
     ctx.expect(TokenType::CloseBrace)?;
 
+    if let Some(layout) = &layout {
+        validate_layout_slots(layout, &name, &widget_position)?;
+    }
+
+    if let Some(base) = base {
+        let mut base_properties = base.default_properties;
+        base_properties.extend(properties);
+        properties = base_properties;
+
+        let mut base_required = base.required_properties;
+        base_required.extend(required_properties);
+        required_properties = base_required;
+
+        let mut base_style = base.default_style;
+        base_style.extend(default_style);
+        default_style = base_style;
+
+        layout = layout.or(Some(base.layout));
+    }
+
     let Some(layout) = layout else {
         return Err(NekoMaidParseError::IncompleteWidgetDefinition {
             widget: name,
@@ -117,19 +245,102 @@ pub(super) fn parse_widget(ctx: &mut ParseContext) -> NekoResult<Widget> {
         });
     };
 
-    validate_layout_slots(&layout, &name, &widget_position)?;
-
     ctx.set_current_widget(None);
 
-    Ok(Widget::Custom(CustomWidget {
+    Ok(Widget::Custom(Box::new(CustomWidget {
         name,
         default_properties: properties,
+        required_properties,
         layout,
-    }))
+        default_style,
+    })))
+}
+
+/// Parses the default style of a widget, such as `style { padding: 4px; }`,
+/// and returns its properties.
+fn parse_default_style(
+    ctx: &mut ParseContext,
+) -> NekoResult<HashMap<String, UnresolvedPropertyValue>> {
+    ctx.expect(TokenType::StyleKeyword)?;
+    ctx.expect(TokenType::OpenBrace)?;
+
+    let mut properties = HashMap::new();
+    let mut property_positions = HashMap::new();
+
+    while let Some(next) = ctx.peek() {
+        match next.token_type {
+            TokenType::Identifier => {
+                let position = next.position;
+                let property = parse_unresolved_property(ctx)?;
+                record_property_position(ctx, &mut property_positions, &property.name, position);
+                properties.insert(property.name, property.value);
+            }
+            TokenType::CloseBrace => break,
+            _ => {
+                return Err(NekoMaidParseError::UnexpectedToken {
+                    expected: vec![
+                        TokenType::Identifier.type_name().to_string(),
+                        TokenType::CloseBrace.type_name().to_string(),
+                    ],
+                    found: next.token_type.type_name().to_string(),
+                    position: next.position,
+                });
+            }
+        }
+    }
+
+    ctx.expect(TokenType::CloseBrace)?;
+    Ok(properties)
+}
+
+/// Parses a required property declaration, such as `property title: string;`,
+/// and returns the name and type of the property.
+fn parse_required_property(ctx: &mut ParseContext) -> NekoResult<(String, PropertyType)> {
+    ctx.expect(TokenType::PropertyKeyword)?;
+    let name = ctx.expect_as_string(TokenType::Identifier)?;
+    ctx.expect(TokenType::Colon)?;
+
+    let type_position = ctx.next_position().unwrap_or_default();
+    let type_name = ctx.expect_as_string(TokenType::Identifier)?;
+    let Some(property_type) = PropertyType::from_name(&type_name) else {
+        return Err(NekoMaidParseError::UnknownPropertyType {
+            name: type_name,
+            position: type_position,
+        });
+    };
+
+    ctx.expect(TokenType::Semicolon)?;
+    Ok((name, property_type))
+}
+
+/// Parses the name of the widget being extended by the widget `name` and
+/// returns a copy of its definition.
+///
+/// Only custom widgets that have already been defined or imported can be
+/// extended.
+fn parse_widget_base(ctx: &mut ParseContext, name: &str) -> NekoResult<CustomWidget> {
+    let position = ctx.next_position().unwrap_or_default();
+    let base = ctx.expect_as_string(TokenType::Identifier)?;
+
+    match ctx.get_widget(&base) {
+        Some(Widget::Custom(custom)) => Ok(custom.as_ref().clone()),
+        Some(Widget::Native(_)) => Err(NekoMaidParseError::InvalidWidgetBase {
+            widget: name.to_string(),
+            base,
+            position,
+        }),
+        None => Err(NekoMaidParseError::UnknownWidget {
+            widget: base,
+            position,
+        }),
+    }
 }
 
 /// Validates if layout does not contain duplicated slots and
 /// contains at least one slot.
+///
+/// These checks run where the widget is defined. How the slots are filled is
+/// checked wherever the widget is used, when its layout is parsed.
 pub(super) fn validate_layout_slots(
     layout: &Layout,
     widget: &String,
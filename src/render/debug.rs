@@ -0,0 +1,184 @@
+//! Developer outlines for diagnosing layouts.
+
+use bevy::prelude::*;
+
+use crate::components::NekoUINode;
+
+/// The class that enables the debug outline on a node.
+pub const DEBUG_CLASS: &str = "debug";
+
+/// A resource controlling the debug outlines of NekoMaid UI nodes.
+///
+/// While enabled, nodes with the `debug` class (or all nodes, with
+/// `all_nodes`) are drawn with a colored outline. Debug outlines are disabled
+/// by default, so the `debug` class has no effect unless they are enabled.
+#[derive(Debug, Clone, PartialEq, Resource)]
+pub struct NekoDebug {
+    /// Whether debug outlines are drawn.
+    pub enabled: bool,
+
+    /// Whether all nodes are outlined, instead of only those with the `debug`
+    /// class.
+    pub all_nodes: bool,
+
+    /// The color of the outlines and size labels.
+    pub color: Color,
+
+    /// Whether a label showing the computed size of the node is drawn in its
+    /// top-left corner. Labels are not drawn on text nodes.
+    pub show_size: bool,
+}
+
+impl Default for NekoDebug {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            all_nodes: false,
+            color: Color::srgb(1.0, 0.0, 1.0),
+            show_size: false,
+        }
+    }
+}
+
+/// A component added to nodes outlined for debugging.
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+pub struct DebugOutline {
+    /// The label showing the size of the node, if any.
+    label: Option<Entity>,
+
+    /// The outline the node had before it was outlined for debugging, restored
+    /// when the debug outline is removed.
+    previous: Option<Outline>,
+}
+
+/// A marker component for the size labels of debug outlines.
+///
+/// Labels are spawned as root nodes positioned over their node, so they never
+/// take part in the layout of the node's children.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+pub struct DebugSizeLabel;
+
+/// Adds or removes the debug outline of nodes, following the [`NekoDebug`]
+/// settings and the classes of the nodes.
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_debug_outlines(
+    settings: Res<NekoDebug>,
+    nodes: Query<(
+        Entity,
+        Ref<NekoUINode>,
+        Option<&DebugOutline>,
+        Option<&Outline>,
+        Has<Text>,
+    )>,
+    mut commands: Commands,
+) {
+    for (entity, node, outline, current, is_text) in nodes {
+        if !settings.is_changed() && !node.is_changed() {
+            continue;
+        }
+
+        let target = settings.enabled && (settings.all_nodes || node.has_class(DEBUG_CLASS));
+        if target && outline.is_some() && !settings.is_changed() {
+            continue;
+        }
+
+        let previous = match outline {
+            Some(outline) => outline.previous,
+            None => current.copied(),
+        };
+
+        if outline.is_some() {
+            commands.entity(entity).remove::<DebugOutline>();
+            match previous {
+                Some(previous) => commands.entity(entity).insert(previous),
+                None => commands.entity(entity).remove::<Outline>(),
+            };
+        }
+
+        if !target {
+            continue;
+        }
+
+        let label = (settings.show_size && !is_text).then(|| {
+            commands
+                .spawn((
+                    DebugSizeLabel,
+                    Text::default(),
+                    TextFont::from_font_size(10.0),
+                    TextColor(settings.color),
+                    Node {
+                        position_type: PositionType::Absolute,
+                        left: Val::ZERO,
+                        top: Val::ZERO,
+                        ..default()
+                    },
+                    GlobalZIndex(i32::MAX),
+                ))
+                .id()
+        });
+
+        commands.entity(entity).insert((
+            DebugOutline { label, previous },
+            Outline::new(Val::Px(1.0), Val::ZERO, settings.color),
+        ));
+    }
+}
+
+/// Despawns the size label of a debug outline when the outline is removed,
+/// including when its node is despawned.
+pub(crate) fn removed_debug_outline(
+    event: On<Remove, DebugOutline>,
+    outlines: Query<&DebugOutline>,
+    mut commands: Commands,
+) {
+    if let Ok(outline) = outlines.get(event.entity)
+        && let Some(label) = outline.label
+    {
+        commands.entity(label).try_despawn();
+    }
+}
+
+/// Updates the text, position and visibility of the size labels of debug
+/// outlines.
+///
+/// Labels are root nodes, so they are hidden by hand while their node is
+/// hidden.
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_debug_labels(
+    nodes: Query<
+        (
+            &DebugOutline,
+            &ComputedNode,
+            &UiGlobalTransform,
+            &InheritedVisibility,
+        ),
+        Or<(
+            Changed<DebugOutline>,
+            Changed<ComputedNode>,
+            Changed<UiGlobalTransform>,
+            Changed<InheritedVisibility>,
+        )>,
+    >,
+    mut labels: Query<(&mut Text, &mut Node, &mut Visibility), With<DebugSizeLabel>>,
+) {
+    for (outline, computed, transform, visibility) in nodes {
+        let Some((mut text, mut node, mut label_visibility)) =
+            outline.label.and_then(|label| labels.get_mut(label).ok())
+        else {
+            continue;
+        };
+
+        let target = match visibility.get() {
+            true => Visibility::Inherited,
+            false => Visibility::Hidden,
+        };
+        label_visibility.set_if_neq(target);
+
+        let scale = computed.inverse_scale_factor();
+        let size = computed.size() * scale;
+        let corner = (transform.translation - computed.size() / 2.0) * scale;
+        text.0 = format!("{}x{}", size.x.round(), size.y.round());
+        node.left = Val::Px(corner.x);
+        node.top = Val::Px(corner.y);
+    }
+}
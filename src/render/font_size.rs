@@ -0,0 +1,80 @@
+//! Font sizes relative to the font size of the parent text.
+
+use bevy::platform::collections::HashSet;
+use bevy::prelude::*;
+
+/// The font size used when a relative font size has no parent text to be
+/// relative to.
+pub(crate) const DEFAULT_FONT_SIZE: f32 = 20.0;
+
+/// A font size relative to the font size of the closest ancestor with a
+/// [`TextFont`], set with a percentage `font-size` property.
+///
+/// A value of `1.5` means one and a half times the font size of the parent.
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+pub struct RelativeFontSize(pub f32);
+
+/// Returns the effective font size of the given entity.
+///
+/// Relative font sizes are resolved recursively against their ancestors, so
+/// the result does not depend on the order in which nodes are updated.
+fn font_size(
+    entity: Entity,
+    fonts: &Query<&mut TextFont>,
+    relative: &Query<&RelativeFontSize>,
+    parents: &Query<&ChildOf>,
+) -> f32 {
+    let Ok(RelativeFontSize(scale)) = relative.get(entity) else {
+        return match fonts.get(entity) {
+            Ok(font) => font.font_size,
+            Err(_) => DEFAULT_FONT_SIZE,
+        };
+    };
+
+    let parent_size = parents
+        .iter_ancestors(entity)
+        .find(|ancestor| fonts.contains(*ancestor))
+        .map(|ancestor| font_size(ancestor, fonts, relative, parents))
+        .unwrap_or(DEFAULT_FONT_SIZE);
+
+    parent_size * scale
+}
+
+/// Resolves the font size of text nodes with a [`RelativeFontSize`].
+///
+/// Only the nodes whose own font size, or the font size of an ancestor,
+/// changed since the last run are resolved again.
+#[allow(clippy::type_complexity)]
+pub(crate) fn resolve_relative_font_sizes(
+    mut set: ParamSet<(
+        Query<Entity, Or<(Changed<TextFont>, Changed<RelativeFontSize>)>>,
+        Query<&mut TextFont>,
+    )>,
+    relative: Query<&RelativeFontSize>,
+    parents: Query<&ChildOf>,
+    children: Query<&Children>,
+) {
+    let mut dirty = HashSet::new();
+    for entity in &set.p0() {
+        if dirty.insert(entity) {
+            dirty.extend(children.iter_descendants(entity));
+        }
+    }
+
+    let fonts = set.p1();
+    let sizes = dirty
+        .into_iter()
+        .filter(|entity| relative.contains(*entity))
+        .map(|entity| (entity, font_size(entity, &fonts, &relative, &parents)))
+        .collect::<Vec<_>>();
+
+    let mut fonts = set.p1();
+    for (entity, size) in sizes {
+        let Ok(mut font) = fonts.get_mut(entity) else {
+            continue;
+        };
+        if font.font_size != size {
+            font.font_size = size;
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! Named grid areas, set with the `grid-template-areas` property on a grid
+//! container and the `grid-area` property on its items.
+
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+
+/// The named areas of a grid container.
+///
+/// Each row of the template is a string of whitespace-separated area names,
+/// with `.` marking an unnamed cell. For example:
+///
+/// ```text
+/// grid-template-areas: "header header" "sidebar main";
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Component)]
+pub struct GridTemplateAreas {
+    /// The row and column placement of each named area.
+    areas: HashMap<String, (GridPlacement, GridPlacement)>,
+}
+
+/// An error in the rows of a `grid-template-areas` property.
+#[derive(Debug, thiserror::Error, Clone, PartialEq, Eq)]
+pub enum GridTemplateError {
+    /// The template has no cells.
+    #[error("the template is empty")]
+    Empty,
+
+    /// A row has a different number of cells than the first row.
+    #[error("row {row} has {found} cells, but the first row has {expected}")]
+    UnevenRows {
+        /// The index of the row, starting at 1.
+        row: usize,
+
+        /// The number of cells of the first row.
+        expected: usize,
+
+        /// The number of cells of the row.
+        found: usize,
+    },
+
+    /// The cells of an area do not form a rectangle.
+    #[error("area '{0}' is not a rectangle")]
+    NotRectangle(String),
+}
+
+impl GridTemplateAreas {
+    /// Parses a grid template from its rows.
+    ///
+    /// Returns an error if the template is empty, if the rows do not all have
+    /// the same number of cells, or if an area does not form a rectangle.
+    pub fn new<'a>(rows: impl IntoIterator<Item = &'a str>) -> Result<Self, GridTemplateError> {
+        let cells = rows
+            .into_iter()
+            .map(|row| row.split_whitespace().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        let width = cells.first().map_or(0, Vec::len);
+        if width == 0 {
+            return Err(GridTemplateError::Empty);
+        }
+
+        if let Some((r, row)) = cells.iter().enumerate().find(|(_, row)| row.len() != width) {
+            return Err(GridTemplateError::UnevenRows {
+                row: r + 1,
+                expected: width,
+                found: row.len(),
+            });
+        }
+
+        // the first and last row and column of each area, and its cell count
+        let mut bounds = HashMap::<&str, (usize, usize, usize, usize, usize)>::new();
+        for (r, row) in cells.iter().enumerate() {
+            for (c, &name) in row.iter().enumerate() {
+                if name == "." {
+                    continue;
+                }
+
+                let area = bounds.entry(name).or_insert((r, r, c, c, 0));
+                area.0 = area.0.min(r);
+                area.1 = area.1.max(r);
+                area.2 = area.2.min(c);
+                area.3 = area.3.max(c);
+                area.4 += 1;
+            }
+        }
+
+        let mut areas = HashMap::new();
+        for (name, (r0, r1, c0, c1, count)) in bounds {
+            if count != (r1 - r0 + 1) * (c1 - c0 + 1) {
+                return Err(GridTemplateError::NotRectangle(name.to_string()));
+            }
+
+            let row = GridPlacement::start_end(r0 as i16 + 1, r1 as i16 + 2);
+            let column = GridPlacement::start_end(c0 as i16 + 1, c1 as i16 + 2);
+            areas.insert(name.to_string(), (row, column));
+        }
+
+        Ok(Self { areas })
+    }
+
+    /// Returns the row and column placement of the area with the given name.
+    pub fn get(&self, name: &str) -> Option<(GridPlacement, GridPlacement)> {
+        self.areas.get(name).copied()
+    }
+}
+
+/// The name of the area of the parent's [`GridTemplateAreas`] a grid item is
+/// placed in, set with the `grid-area` property.
+#[derive(Debug, Clone, PartialEq, Eq, Component)]
+pub struct GridArea(pub String);
+
+/// Places grid items with a [`GridArea`] in the matching area of their
+/// parent's [`GridTemplateAreas`].
+///
+/// Items placed in an unknown area, or whose parent no longer has a template,
+/// are placed automatically again.
+pub(crate) fn place_grid_areas(
+    mut items: Query<(Ref<GridArea>, &ChildOf, &mut Node)>,
+    templates: Query<Ref<GridTemplateAreas>>,
+    children: Query<&Children>,
+    mut removed: RemovedComponents<GridTemplateAreas>,
+) {
+    for parent in removed.read() {
+        let Ok(children) = children.get(parent) else {
+            continue;
+        };
+
+        for &child in children {
+            if let Ok((_, _, mut node)) = items.get_mut(child) {
+                node.grid_row = GridPlacement::default();
+                node.grid_column = GridPlacement::default();
+            }
+        }
+    }
+
+    for (area, child_of, mut node) in &mut items {
+        let Ok(template) = templates.get(child_of.parent()) else {
+            continue;
+        };
+
+        if !area.is_changed() && !template.is_changed() {
+            continue;
+        }
+
+        let Some((row, column)) = template.get(&area.0) else {
+            warn_once!("Unknown grid area: {}", area.0);
+            node.grid_row = GridPlacement::default();
+            node.grid_column = GridPlacement::default();
+            continue;
+        };
+
+        node.grid_row = row;
+        node.grid_column = column;
+    }
+}
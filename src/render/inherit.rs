@@ -0,0 +1,230 @@
+//! Text properties inherited from ancestor elements.
+
+use bevy::platform::collections::{HashMap, HashSet};
+use bevy::prelude::*;
+
+use crate::components::{NekoUINode, NekoUITree};
+use crate::font::{DefaultFont, FontFamilyRegistry, select_font};
+use crate::parse::value::{CURRENT_COLOR, FontStyle, PropertyValue};
+use crate::render::font_size::DEFAULT_FONT_SIZE;
+
+/// The properties a text node inherits from its closest ancestor defining them
+/// when it does not define them itself.
+///
+/// Relative font sizes are resolved against the parent text instead (see
+/// [`RelativeFontSize`](crate::render::font_size::RelativeFontSize)), so
+/// percentage font sizes are never inherited.
+pub const INHERITED_PROPERTIES: [&str; 4] = ["color", "font", "font-style", "font-size"];
+
+/// Returns the value of the given property on the given node, if the node
+/// defines it.
+fn own_property(
+    entity: Entity,
+    name: &str,
+    nodes: &mut Query<&mut NekoUINode>,
+    roots: &Query<&NekoUITree>,
+) -> Option<PropertyValue> {
+    let mut node = nodes.get_mut(entity).ok()?;
+    let root = roots.get(node.root).ok()?;
+
+    // reading a property only caches the active properties of the element, so
+    // it should not trigger an update of the node.
+    node.bypass_change_detection()
+        .element
+        .view_mut(&root.scope)
+        .get_property(name)
+        .cloned()
+}
+
+/// Returns whether the given value is the [`CURRENT_COLOR`] keyword.
+///
+/// Written on the `color` property itself, the keyword means that the color is
+/// inherited, just like when the property is not set.
+fn is_current_color(value: &PropertyValue) -> bool {
+    value.as_str() == Some(CURRENT_COLOR)
+}
+
+/// Returns the nodes that changed since the last run, along with all of their
+/// descendants, which may inherit from them.
+fn changed_subtrees(
+    changed: &Query<Entity, Changed<NekoUINode>>,
+    children: &Query<&Children>,
+) -> HashSet<Entity> {
+    let mut entities = HashSet::new();
+    for entity in changed {
+        if entities.insert(entity) {
+            entities.extend(children.iter_descendants(entity));
+        }
+    }
+    entities
+}
+
+/// Returns the value a node inherits for the given property, or `None` if no
+/// ancestor defines it.
+fn inherited_property(
+    entity: Entity,
+    name: &str,
+    nodes: &mut Query<&mut NekoUINode>,
+    roots: &Query<&NekoUITree>,
+    parents: &Query<&ChildOf>,
+) -> Option<PropertyValue> {
+    parents
+        .iter_ancestors(entity)
+        .filter_map(|ancestor| own_property(ancestor, name, nodes, roots))
+        .find(|value| !matches!(value, PropertyValue::Percent(_)) && !is_current_color(value))
+}
+
+/// Applies the [`INHERITED_PROPERTIES`] to the text nodes that do not define
+/// them.
+///
+/// Only the changed nodes and their descendants are updated again.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub(crate) fn inherit_text_properties(
+    mut set: ParamSet<(Query<Entity, Changed<NekoUINode>>, Query<&mut NekoUINode>)>,
+    roots: Query<&NekoUITree>,
+    parents: Query<&ChildOf>,
+    children: Query<&Children>,
+    mut texts: Query<
+        (Option<&mut TextColor>, Option<&mut TextFont>),
+        (With<NekoUINode>, Or<(With<Text>, With<TextSpan>)>),
+    >,
+    asset_server: Res<AssetServer>,
+    families: Res<FontFamilyRegistry>,
+    default_font: Res<DefaultFont>,
+) {
+    let entities = changed_subtrees(&set.p0(), &children);
+    let mut nodes = set.p1();
+
+    for entity in entities {
+        let Ok((color, font)) = texts.get_mut(entity) else {
+            continue;
+        };
+
+        // the value each property not defined by the node inherits, or `None`
+        // if no ancestor defines it either.
+        let mut inherited = HashMap::new();
+        for name in INHERITED_PROPERTIES {
+            if own_property(entity, name, &mut nodes, &roots)
+                .is_none_or(|value| is_current_color(&value))
+            {
+                let value = inherited_property(entity, name, &mut nodes, &roots, &parents);
+                inherited.insert(name, value);
+            }
+        }
+
+        if let Some(mut color) = color
+            && let Some(value) = inherited.get("color")
+        {
+            let value = value.as_ref().map(Color::from).unwrap_or(Color::WHITE);
+            color.set_if_neq(TextColor(value));
+        }
+
+        let Some(mut font) = font else {
+            continue;
+        };
+
+        if let Some(value) = inherited.get("font-size") {
+            let value = value.as_ref().map(f32::from).unwrap_or(DEFAULT_FONT_SIZE);
+            if font.font_size != value {
+                font.font_size = value;
+            }
+        }
+
+        // the face depends on both the family and the style, so it is selected
+        // again if either is inherited.
+        if inherited.contains_key("font") || inherited.contains_key("font-style") {
+            let mut value = |name: &str| match inherited.get(name) {
+                Some(value) => value.clone(),
+                None => own_property(entity, name, &mut nodes, &roots),
+            };
+            let path = value("font").and_then(|value| value.as_str().map(str::to_owned));
+            let style = value("font-style")
+                .map(|value| FontStyle::from(&value))
+                .unwrap_or_default();
+
+            let face = select_font(
+                path.as_deref(),
+                style,
+                &families,
+                &default_font,
+                &asset_server,
+            );
+            if font.font != face {
+                font.font = face;
+            }
+        }
+    }
+}
+
+/// Replaces the [`CURRENT_COLOR`] keyword in the background, tint and border
+/// colors of the nodes with their text color.
+///
+/// Only the changed nodes and their descendants are resolved again.
+#[allow(clippy::type_complexity)]
+pub(crate) fn resolve_current_color(
+    mut set: ParamSet<(Query<Entity, Changed<NekoUINode>>, Query<&mut NekoUINode>)>,
+    roots: Query<&NekoUITree>,
+    parents: Query<&ChildOf>,
+    children: Query<&Children>,
+    mut targets: Query<
+        (
+            Entity,
+            &mut BackgroundColor,
+            &mut BorderColor,
+            Option<&mut ImageNode>,
+        ),
+        With<NekoUINode>,
+    >,
+) {
+    let entities = changed_subtrees(&set.p0(), &children);
+    let mut nodes = set.p1();
+
+    for entity in entities {
+        let Ok((entity, mut background, mut border, image)) = targets.get_mut(entity) else {
+            continue;
+        };
+
+        let mut own = |name: &str| own_property(entity, name, &mut nodes, &roots);
+        let background_uses = own("background-color").is_some_and(|v| is_current_color(&v));
+        let tint_uses = own("tint").is_some_and(|v| is_current_color(&v));
+        let border_uses = own("border-color").is_some_and(|v| is_current_color(&v));
+        let sides = ["top", "left", "right", "bottom"]
+            .map(|side| own(&format!("border-color-{side}")).map(|v| is_current_color(&v)));
+
+        let border_sides = sides.map(|side| side.unwrap_or(border_uses));
+        if !background_uses && !tint_uses && !border_sides.contains(&true) {
+            continue;
+        }
+
+        let current = own_property(entity, "color", &mut nodes, &roots)
+            .filter(|value| !is_current_color(value))
+            .or_else(|| inherited_property(entity, "color", &mut nodes, &roots, &parents))
+            .and_then(|value| value.as_color())
+            .unwrap_or(Color::WHITE);
+
+        if background_uses {
+            background.set_if_neq(BackgroundColor(current));
+        }
+
+        if tint_uses
+            && let Some(mut image) = image
+            && image.color != current
+        {
+            image.color = current;
+        }
+
+        let mut resolved = *border;
+        let [top, left, right, bottom] = border_sides;
+        for (uses, side) in [
+            (top, &mut resolved.top),
+            (left, &mut resolved.left),
+            (right, &mut resolved.right),
+            (bottom, &mut resolved.bottom),
+        ] {
+            if uses {
+                *side = current;
+            }
+        }
+        border.set_if_neq(resolved);
+    }
+}
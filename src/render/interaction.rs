@@ -0,0 +1,61 @@
+//! Configures the classes NekoMaid applies to interactable elements.
+
+use bevy::prelude::*;
+
+use crate::components::NekoUINode;
+
+/// A resource naming the classes applied to elements when they are hovered or
+/// pressed.
+///
+/// By default, these are `hovered` and `pressed`. Setting a class to `None`
+/// disables it entirely. Insert the resource before adding the
+/// [`NekoMaidPlugin`](crate::NekoMaidPlugin) so the asset loader does not
+/// report style selectors using the renamed classes as unused.
+#[derive(Debug, Clone, PartialEq, Eq, Resource)]
+pub struct InteractionClasses {
+    /// The class applied while the pointer is over an element.
+    pub hovered: Option<String>,
+
+    /// The class applied while an element is being pressed.
+    pub pressed: Option<String>,
+}
+
+impl Default for InteractionClasses {
+    fn default() -> Self {
+        Self {
+            hovered: Some("hovered".to_string()),
+            pressed: Some("pressed".to_string()),
+        }
+    }
+}
+
+impl InteractionClasses {
+    /// Adds or removes the hovered class on the given node.
+    pub(crate) fn set_hovered(&self, node: &mut Mut<NekoUINode>, active: bool) {
+        set_class(node, self.hovered.as_deref(), active);
+    }
+
+    /// Adds or removes the pressed class on the given node.
+    pub(crate) fn set_pressed(&self, node: &mut Mut<NekoUINode>, active: bool) {
+        set_class(node, self.pressed.as_deref(), active);
+    }
+}
+
+/// Adds or removes the given class on the node, if the class is enabled.
+///
+/// The node is only marked as changed if the class is actually added or
+/// removed.
+fn set_class(node: &mut Mut<NekoUINode>, class: Option<&str>, active: bool) {
+    let Some(class) = class else {
+        return;
+    };
+
+    if node.has_class(class) == active {
+        return;
+    }
+
+    match active {
+        true => node.add_class(class.to_string()),
+        false => node.remove_class(class),
+    }
+}
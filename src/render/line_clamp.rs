@@ -0,0 +1,113 @@
+//! Limits the number of lines shown by text widgets with the `line-clamp`
+//! property.
+
+use bevy::prelude::*;
+use bevy::text::TextLayoutInfo;
+
+/// Limits a text widget to a number of lines, truncating the rest of its text.
+///
+/// Lines are counted after wrapping, so the text is truncated once it has been
+/// laid out. Only the text of the widget itself is truncated, not the text of
+/// its spans.
+#[derive(Debug, Clone, PartialEq, Component)]
+pub struct LineClamp {
+    /// The maximum number of lines to show.
+    pub(crate) lines: usize,
+
+    /// Whether an ellipsis is shown at the end of truncated text.
+    pub(crate) ellipsis: bool,
+
+    /// The full text of the widget, before truncation.
+    pub(crate) text: String,
+
+    /// The width of the widget when the text was last truncated.
+    pub(crate) width: f32,
+}
+
+impl LineClamp {
+    /// Creates a new line clamp for the given text.
+    pub(crate) fn new(lines: usize, ellipsis: bool, text: String) -> Self {
+        Self {
+            lines: lines.max(1),
+            ellipsis,
+            text,
+            width: 0.0,
+        }
+    }
+}
+
+/// Returns the byte offset of the first character that does not fit within
+/// the first `lines` lines of the given layout.
+///
+/// `text` is the text of the widget, which must be the first section of the
+/// layout.
+pub(crate) fn clamp_offset(info: &TextLayoutInfo, text: &str, lines: usize) -> Option<usize> {
+    let mut rows = info
+        .section_rects
+        .iter()
+        .map(|(_, rect)| rect.min.y)
+        .collect::<Vec<_>>();
+    rows.sort_by(f32::total_cmp);
+    rows.dedup();
+
+    let boundary = *rows.get(lines)?;
+    let glyph = info
+        .glyphs
+        .iter()
+        .find(|glyph| glyph.position.y >= boundary)?;
+    if glyph.span_index != 0 {
+        return None;
+    }
+
+    let line_start = std::iter::once(0)
+        .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+        .nth(glyph.line_index)?;
+    Some(line_start + glyph.byte_index)
+}
+
+/// Truncates the text of clamped text widgets once they have been laid out.
+///
+/// Truncating the text triggers a new layout, so this is repeated until the
+/// text fits, which takes more than one frame when an ellipsis is added. The
+/// full text is restored whenever the width of the widget changes.
+pub(crate) fn clamp_text_lines(
+    nodes: Query<(
+        &mut Text,
+        &mut LineClamp,
+        Ref<TextLayoutInfo>,
+        Ref<ComputedNode>,
+    )>,
+) {
+    for (mut text, mut clamp, info, computed) in nodes {
+        if !info.is_changed() && !computed.is_changed() {
+            continue;
+        }
+
+        if clamp.width != computed.size.x {
+            clamp.width = computed.size.x;
+            if text.0 != clamp.text {
+                text.0 = clamp.text.clone();
+                continue;
+            }
+        }
+
+        let Some(offset) = clamp_offset(&info, &text.0, clamp.lines) else {
+            continue;
+        };
+
+        let mut truncated = text
+            .0
+            .get(.. offset)
+            .unwrap_or_default()
+            .trim_end()
+            .to_string();
+        if clamp.ellipsis {
+            truncated.pop();
+            truncated = format!("{}…", truncated.trim_end());
+        }
+
+        if text.0 != truncated {
+            text.0 = truncated;
+        }
+    }
+}
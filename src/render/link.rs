@@ -0,0 +1,82 @@
+//! Hyperlinks within text widgets, created with the `link` property.
+
+use bevy::prelude::*;
+use bevy::text::TextLayoutInfo;
+use bevy::ui::RelativeCursorPosition;
+
+use crate::components::NekoUINode;
+use crate::render::interaction::InteractionClasses;
+use crate::render::selection::cursor_in_layout;
+
+/// A message sent when a text widget with a `link` property is clicked.
+#[derive(Debug, Clone, PartialEq, Eq, Message)]
+pub struct NekoLinkClicked {
+    /// The text widget that was clicked.
+    pub entity: Entity,
+
+    /// The value of the `link` property of the widget.
+    pub href: String,
+}
+
+/// The target of a text widget with a `link` property.
+///
+/// Spans do not receive interactions on their own, so the text block they are
+/// part of is made interactable instead, and the clicked span is found using
+/// the layout of the text block.
+#[derive(Debug, Clone, PartialEq, Eq, Component)]
+pub struct NekoLink(pub String);
+
+/// Makes the text block containing a newly added link interactable.
+pub(crate) fn added_link(
+    event: On<Add, NekoLink>,
+    parents: Query<&ChildOf>,
+    blocks: Query<(), With<Text>>,
+    mut commands: Commands,
+) {
+    let Some(block) = std::iter::once(event.entity)
+        .chain(parents.iter_ancestors(event.entity))
+        .find(|&entity| blocks.contains(entity))
+    else {
+        return;
+    };
+
+    commands
+        .entity(block)
+        .insert_if_new((Interaction::default(), RelativeCursorPosition::default()));
+}
+
+/// Updates the hovered class of links and sends a [`NekoLinkClicked`]
+/// message when a link is clicked.
+pub(crate) fn handle_links(
+    blocks: Query<(
+        Ref<Interaction>,
+        &RelativeCursorPosition,
+        &ComputedNode,
+        &TextLayoutInfo,
+    )>,
+    mut links: Query<(&NekoLink, &mut NekoUINode)>,
+    classes: Res<InteractionClasses>,
+    mut clicked: MessageWriter<NekoLinkClicked>,
+) {
+    for (interaction, cursor, computed, info) in &blocks {
+        let point = cursor_in_layout(cursor, computed);
+        let pressed = interaction.is_changed() && *interaction == Interaction::Pressed;
+
+        for (entity, rect) in &info.section_rects {
+            let Ok((link, mut node)) = links.get_mut(*entity) else {
+                continue;
+            };
+
+            let hovered = *interaction != Interaction::None
+                && point.is_some_and(|point| rect.contains(point));
+            classes.set_hovered(&mut node, hovered);
+
+            if hovered && pressed {
+                clicked.write(NekoLinkClicked {
+                    entity: *entity,
+                    href: link.0.clone(),
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,110 @@
+//! Caps the height of containers to a number of text lines with the
+//! `max-lines` property, scrolling their content instead of growing.
+
+use bevy::prelude::*;
+use bevy::text::LineHeight;
+
+use crate::components::NekoUINode;
+
+/// Caps the height of a container to a number of lines of its text.
+///
+/// Unlike [`LineClamp`](crate::render::line_clamp::LineClamp), no content is
+/// truncated. The container scrolls vertically once its content exceeds the
+/// given number of lines, and follows new content appended to its end as long
+/// as it is scrolled to the bottom, like a chat or a log.
+///
+/// A `max-height` in pixels lower than the lines still caps the container, and
+/// an `overflow-y` other than `visible` is kept.
+#[derive(Debug, Clone, PartialEq, Component)]
+pub struct MaxLines {
+    /// The maximum number of lines shown.
+    pub(crate) lines: usize,
+
+    /// The `max-height` of the container itself.
+    pub(crate) max_height: Val,
+
+    /// The largest scroll position of the container when it was last updated.
+    pub(crate) max_scroll: f32,
+}
+
+impl MaxLines {
+    /// Creates a new line cap for a container with the given `max-height`.
+    pub(crate) fn new(lines: usize, max_height: Val) -> Self {
+        Self {
+            lines: lines.max(1),
+            max_height,
+            max_scroll: 0.0,
+        }
+    }
+}
+
+/// Returns the height of a line of the given font, in logical pixels.
+fn line_height(font: &TextFont) -> f32 {
+    match font.line_height {
+        LineHeight::Px(px) => px,
+        LineHeight::RelativeToFont(scale) => scale * font.font_size,
+    }
+}
+
+/// Updates the height and scroll position of containers with [`MaxLines`].
+///
+/// The line height is taken from the first text of the container, which is
+/// either the container itself or its first text descendant.
+///
+/// Once the cap is removed, the container applies its own `max-height` and
+/// `overflow-y` again.
+pub(crate) fn scroll_max_lines(
+    mut nodes: Query<(
+        Entity,
+        &mut MaxLines,
+        &mut Node,
+        &ComputedNode,
+        &mut ScrollPosition,
+    )>,
+    fonts: Query<&TextFont>,
+    children: Query<&Children>,
+    mut removed: RemovedComponents<MaxLines>,
+    mut neko_nodes: Query<&mut NekoUINode>,
+) {
+    for entity in removed.read() {
+        if let Ok(mut node) = neko_nodes.get_mut(entity) {
+            node.updated_properties
+                .extend(["max-height".to_owned(), "overflow-y".to_owned()]);
+        }
+    }
+
+    for (entity, mut max_lines, mut node, computed, mut position) in &mut nodes {
+        let Some(font) = std::iter::once(entity)
+            .chain(children.iter_descendants(entity))
+            .find_map(|entity| fonts.get(entity).ok())
+        else {
+            continue;
+        };
+
+        let cap = max_lines.lines as f32 * line_height(font);
+        let max_height = match max_lines.max_height {
+            Val::Px(px) => Val::Px(px.min(cap)),
+            _ => Val::Px(cap),
+        };
+        if node.max_height != max_height {
+            node.max_height = max_height;
+        }
+        if node.overflow.y == OverflowAxis::Visible {
+            node.overflow.y = OverflowAxis::Scroll;
+        }
+
+        let max_scroll = (computed.content_size.y - computed.size.y + computed.scrollbar_size.y)
+            .max(0.0)
+            * computed.inverse_scale_factor;
+        if max_lines.max_scroll == max_scroll {
+            continue;
+        }
+
+        // only follow the new content if the container was scrolled to the
+        // bottom, so reading older lines is not interrupted.
+        if position.y >= max_lines.max_scroll {
+            position.y = max_scroll;
+        }
+        max_lines.max_scroll = max_scroll;
+    }
+}
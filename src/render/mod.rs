@@ -1,5 +1,21 @@
 //! This module implements the logic for spawning and updating UI trees.
 
+pub mod debug;
+pub mod font_size;
+pub mod grid_area;
+pub mod inherit;
+pub mod interaction;
+pub mod line_clamp;
+pub mod link;
+pub mod max_lines;
+pub mod order;
+pub mod scroll;
+pub mod selection;
+pub mod snapshot;
 pub mod spawn;
 pub mod systems;
 pub mod update;
+pub mod viewport;
+
+#[cfg(test)]
+mod tests;
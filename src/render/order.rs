@@ -0,0 +1,107 @@
+//! The visual order of sibling nodes, set with the `order` property.
+//!
+//! Ordering a node moves it among the children of its parent, so it changes
+//! both where the node is laid out and which siblings it is drawn on top of.
+//! Nodes are always drawn above the siblings that come before them.
+
+use bevy::platform::collections::HashSet;
+use bevy::prelude::*;
+
+/// The order of a node among its siblings, set with the `order` property.
+///
+/// Siblings are sorted in ascending order, and siblings of equal order keep
+/// the order they are written in. Nodes without an `Order` have an order of 0,
+/// so negative and fractional orders can be used to move a node before or
+/// between its siblings.
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+pub struct Order(pub f32);
+
+/// The children of a node in the order they are written in, recorded before
+/// they are reordered for the first time.
+///
+/// Children added afterwards are appended by [`child_inserted`], and removed
+/// by [`child_replaced`] when they leave the node.
+#[derive(Debug, Clone, PartialEq, Component)]
+pub(crate) struct SourceOrder(Vec<Entity>);
+
+/// Appends a new child to the [`SourceOrder`] of its parent.
+pub(crate) fn child_inserted(
+    event: On<Insert, ChildOf>,
+    parents: Query<&ChildOf>,
+    mut sources: Query<&mut SourceOrder>,
+) {
+    let Ok(child_of) = parents.get(event.entity) else {
+        return;
+    };
+    if let Ok(mut source) = sources.get_mut(child_of.parent())
+        && !source.0.contains(&event.entity)
+    {
+        source.0.push(event.entity);
+    }
+}
+
+/// Removes a child from the [`SourceOrder`] of the parent it leaves.
+pub(crate) fn child_replaced(
+    event: On<Replace, ChildOf>,
+    parents: Query<&ChildOf>,
+    mut sources: Query<&mut SourceOrder>,
+) {
+    let Ok(child_of) = parents.get(event.entity) else {
+        return;
+    };
+    if let Ok(mut source) = sources.get_mut(child_of.parent()) {
+        source.0.retain(|entity| *entity != event.entity);
+    }
+}
+
+/// Sorts the children of the nodes whose children had their [`Order`] changed,
+/// or whose [`SourceOrder`] changed after they were first reordered.
+pub(crate) fn order_children(
+    mut commands: Commands,
+    changed: Query<&ChildOf, Changed<Order>>,
+    added: Query<Entity, Changed<SourceOrder>>,
+    mut removed: RemovedComponents<Order>,
+    parents: Query<&ChildOf>,
+    mut children: Query<(&mut Children, Option<&SourceOrder>)>,
+    orders: Query<&Order>,
+) {
+    let mut dirty = changed.iter().map(ChildOf::parent).collect::<HashSet<_>>();
+    dirty.extend(added.iter());
+    dirty.extend(
+        removed
+            .read()
+            .filter_map(|entity| parents.get(entity).ok())
+            .map(ChildOf::parent),
+    );
+
+    for parent in dirty {
+        let Ok((mut children, source)) = children.get_mut(parent) else {
+            continue;
+        };
+
+        let source = match source {
+            Some(source) => source.0.clone(),
+            None => {
+                let source = children.to_vec();
+                commands.entity(parent).insert(SourceOrder(source.clone()));
+                source
+            }
+        };
+
+        let key = |entity: &Entity| {
+            let order = orders.get(*entity).map_or(0.0, |order| order.0);
+            let index = source.iter().position(|e| e == entity);
+            (order, index.unwrap_or(usize::MAX))
+        };
+        let compare = |a: &Entity, b: &Entity| {
+            let (a, b) = (key(a), key(b));
+            a.0.total_cmp(&b.0).then(a.1.cmp(&b.1))
+        };
+
+        // sorting marks the children as changed, so it is skipped when they are
+        // already in order.
+        if !children.is_sorted_by(|a, b| compare(a, b).is_le()) {
+            children.sort_by(compare);
+        }
+    }
+}
@@ -0,0 +1,70 @@
+//! Scrolls nodes with a `scroll` overflow with the mouse wheel.
+
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+
+/// The distance scrolled by one line of a mouse wheel, in logical pixels.
+pub const SCROLL_LINE_HEIGHT: f32 = 20.0;
+
+/// A component added to nodes that scroll with the mouse wheel, which are the
+/// nodes with `overflow: scroll` on either axis.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Component)]
+#[require(RelativeCursorPosition)]
+pub struct WheelScroll;
+
+/// Scrolls the node under the cursor when the mouse wheel is used.
+///
+/// If several scrollable nodes are under the cursor, the one drawn on top is
+/// scrolled. The scroll position is clamped so the content never scrolls past
+/// its edges. A vertical wheel scrolls a node that only overflows
+/// horizontally along its horizontal axis.
+pub(crate) fn scroll_with_wheel(
+    mut wheel: MessageReader<MouseWheel>,
+    mut nodes: Query<
+        (
+            &Node,
+            &ComputedNode,
+            &RelativeCursorPosition,
+            &mut ScrollPosition,
+        ),
+        With<WheelScroll>,
+    >,
+) {
+    let delta = wheel
+        .read()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => Vec2::new(event.x, event.y) * SCROLL_LINE_HEIGHT,
+            MouseScrollUnit::Pixel => Vec2::new(event.x, event.y),
+        })
+        .sum::<Vec2>();
+
+    if delta == Vec2::ZERO {
+        return;
+    }
+
+    let Some((node, computed, _, mut position)) = nodes
+        .iter_mut()
+        .filter(|(_, _, cursor, _)| cursor.cursor_over())
+        .max_by_key(|(_, computed, _, _)| computed.stack_index)
+    else {
+        return;
+    };
+
+    // moving the wheel down gives a negative delta, which should move the
+    // content up, increasing the scroll position.
+    let mut offset = -delta;
+    if node.overflow.y != OverflowAxis::Scroll {
+        offset = Vec2::new(offset.x + offset.y, 0.0);
+    }
+    if node.overflow.x != OverflowAxis::Scroll {
+        offset.x = 0.0;
+    }
+
+    let max = (computed.content_size - computed.size + computed.scrollbar_size).max(Vec2::ZERO)
+        * computed.inverse_scale_factor;
+    let scrolled = (position.0 + offset).clamp(Vec2::ZERO, max);
+    if position.0 != scrolled {
+        position.0 = scrolled;
+    }
+}
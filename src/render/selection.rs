@@ -0,0 +1,356 @@
+//! Text selection for text widgets with the `user-select: text` property.
+
+use std::ops::Range;
+
+use bevy::input::ButtonInput;
+use bevy::input::keyboard::KeyCode;
+use bevy::prelude::*;
+use bevy::text::{PositionedGlyph, TextLayoutInfo};
+use bevy::ui::RelativeCursorPosition;
+use bevy::ui::widget::TextUiReader;
+
+/// The color drawn over selected text.
+pub const SELECTION_COLOR: Color = Color::srgba(0.2, 0.4, 1.0, 0.35);
+
+/// The text currently selected within a text widget.
+///
+/// This component is added to text widgets with the `user-select: text`
+/// property. The selection is made by pressing the mouse over the text and
+/// dragging it, and starting a selection clears those of other widgets.
+/// Offsets are byte offsets into the full text of the block, that is, the text
+/// of the widget followed by the text of all of its spans.
+///
+/// The selected text is highlighted, and pressing `Ctrl+C` (or `Cmd+C`) sends
+/// a [`NekoTextCopied`] message with it.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Component)]
+#[require(Interaction, RelativeCursorPosition)]
+pub struct TextSelection {
+    /// The offset where the selection started.
+    anchor: Option<usize>,
+
+    /// The offset where the selection currently ends.
+    head: Option<usize>,
+
+    /// Whether the mouse button was pressed over this widget last frame.
+    dragging: bool,
+
+    /// The highlights drawn over the selected text, one per row.
+    highlights: Vec<Entity>,
+}
+
+/// A marker component for the highlights drawn over selected text.
+///
+/// Highlights are spawned as root nodes positioned over their text widget, so
+/// they never take part in the layout of the widget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+pub struct SelectionHighlight;
+
+/// A message sent when the selected text of a text widget is copied.
+///
+/// Bevy has no clipboard, so apps write the text to the clipboard themselves.
+#[derive(Debug, Clone, PartialEq, Eq, Message)]
+pub struct NekoTextCopied {
+    /// The text widget the text was selected in.
+    pub entity: Entity,
+
+    /// The selected text.
+    pub text: String,
+}
+
+impl TextSelection {
+    /// Returns the selected byte range, or `None` if nothing is selected.
+    pub fn range(&self) -> Option<Range<usize>> {
+        let (anchor, head) = (self.anchor?, self.head?);
+        if anchor == head {
+            return None;
+        }
+        Some(anchor.min(head) .. anchor.max(head))
+    }
+
+    /// Returns the selected part of the given text, which should be the full
+    /// text of the block.
+    ///
+    /// This is what should be written to the clipboard when the selection is
+    /// copied.
+    pub fn selected_text<'a>(&self, text: &'a str) -> &'a str {
+        self.range()
+            .and_then(|range| text.get(range))
+            .unwrap_or_default()
+    }
+
+    /// Clears the selection.
+    pub fn clear(&mut self) {
+        self.anchor = None;
+        self.head = None;
+    }
+}
+
+/// Converts the cursor position over a text widget into the coordinates of its
+/// text layout.
+pub(crate) fn cursor_in_layout(
+    cursor: &RelativeCursorPosition,
+    computed: &ComputedNode,
+) -> Option<Vec2> {
+    let inset = computed.content_inset();
+    Some((cursor.normalized? + 0.5) * computed.size() - Vec2::new(inset.left, inset.top))
+}
+
+/// Returns the full text of a text block: the text of the widget followed by
+/// the text of all of its spans.
+fn block_text(reader: &mut TextUiReader, entity: Entity) -> String {
+    reader.iter(entity).map(|(_, _, text, _, _)| text).collect()
+}
+
+/// Returns the byte offset of each line of the given text, since glyphs only
+/// store their offset within their line.
+fn line_starts(text: &str) -> Vec<usize> {
+    let mut starts = vec![0];
+    starts.extend(text.match_indices('\n').map(|(i, _)| i + 1));
+    starts
+}
+
+/// Splits the glyphs of a text layout into the rows they are drawn on.
+///
+/// A wrapped line spans several rows that share its line index, so the glyphs
+/// are split wherever the next glyph starts back left.
+fn rows(info: &TextLayoutInfo) -> impl Iterator<Item = &[PositionedGlyph]> {
+    info.glyphs
+        .chunk_by(|a, b| a.line_index == b.line_index && a.position.x < b.position.x)
+}
+
+/// Returns the byte offset of the line of the given row in the full text.
+fn row_start(row: &[PositionedGlyph], line_starts: &[usize]) -> usize {
+    line_starts
+        .get(row[0].line_index)
+        .copied()
+        .unwrap_or_default()
+}
+
+/// Returns the caret offset closest to the given point, in the coordinates of
+/// the text layout.
+///
+/// `line_starts` contains the byte offset of each line of the full text.
+pub(crate) fn caret_at(info: &TextLayoutInfo, line_starts: &[usize], point: Vec2) -> Option<usize> {
+    let distance = |row: &[PositionedGlyph]| {
+        row.iter()
+            .map(|glyph| (glyph.position.y - point.y).abs())
+            .fold(f32::INFINITY, f32::min)
+    };
+
+    let row = rows(info).min_by(|a, b| distance(a).total_cmp(&distance(b)))?;
+    let line_start = row_start(row, line_starts);
+    let mut caret = None;
+
+    for glyph in row {
+        if point.x < glyph.position.x {
+            return Some(line_start + glyph.byte_index);
+        }
+        caret = Some(line_start + glyph.byte_index + glyph.byte_length);
+    }
+
+    caret
+}
+
+/// Returns the rectangles covering the selected glyphs of each row, in the
+/// coordinates of the text layout.
+pub(crate) fn selection_rects(
+    info: &TextLayoutInfo,
+    line_starts: &[usize],
+    range: Range<usize>,
+) -> Vec<Rect> {
+    rows(info)
+        .filter_map(|row| {
+            let line_start = row_start(row, line_starts);
+            row.iter()
+                .filter(|glyph| range.contains(&(line_start + glyph.byte_index)))
+                .map(|glyph| Rect::from_center_size(glyph.position, glyph.size))
+                .reduce(|a, b| a.union(b))
+        })
+        .collect()
+}
+
+/// Updates the selection of text widgets being dragged over.
+pub(crate) fn update_text_selection(
+    mut nodes: Query<(
+        Entity,
+        &mut TextSelection,
+        &Interaction,
+        &RelativeCursorPosition,
+        &ComputedNode,
+        &TextLayoutInfo,
+    )>,
+    mut reader: TextUiReader,
+) {
+    let mut started = None;
+
+    for (entity, mut selection, interaction, cursor, computed, info) in &mut nodes {
+        if *interaction != Interaction::Pressed {
+            if selection.dragging {
+                selection.dragging = false;
+            }
+            continue;
+        }
+
+        let Some(point) = cursor_in_layout(cursor, computed) else {
+            continue;
+        };
+
+        let line_starts = line_starts(&block_text(&mut reader, entity));
+        let Some(caret) = caret_at(info, &line_starts, point) else {
+            continue;
+        };
+
+        if !selection.dragging {
+            selection.dragging = true;
+            selection.anchor = Some(caret);
+            started = Some(entity);
+        }
+        if selection.head != Some(caret) {
+            selection.head = Some(caret);
+        }
+    }
+
+    let Some(started) = started else {
+        return;
+    };
+
+    for (entity, mut selection, ..) in &mut nodes {
+        if entity != started && selection.anchor.is_some() {
+            selection.clear();
+        }
+    }
+}
+
+/// Sends a [`NekoTextCopied`] message for every text selection when `Ctrl+C`
+/// or `Cmd+C` is pressed.
+///
+/// Nothing is copied without Bevy's `InputPlugin`, which is part of the
+/// `DefaultPlugins`.
+pub(crate) fn copy_text_selection(
+    keys: Option<Res<ButtonInput<KeyCode>>>,
+    selections: Query<(Entity, &TextSelection)>,
+    mut reader: TextUiReader,
+    mut copied: MessageWriter<NekoTextCopied>,
+) {
+    let Some(keys) = keys else {
+        return;
+    };
+
+    let modifiers = [
+        KeyCode::ControlLeft,
+        KeyCode::ControlRight,
+        KeyCode::SuperLeft,
+        KeyCode::SuperRight,
+    ];
+    if !keys.any_pressed(modifiers) || !keys.just_pressed(KeyCode::KeyC) {
+        return;
+    }
+
+    for (entity, selection) in &selections {
+        if selection.range().is_none() {
+            continue;
+        }
+
+        let text = block_text(&mut reader, entity);
+        copied.write(NekoTextCopied {
+            entity,
+            text: selection.selected_text(&text).to_string(),
+        });
+    }
+}
+
+/// Despawns the highlights of a text selection when it is removed or
+/// replaced, including when its widget is despawned.
+pub(crate) fn replaced_text_selection(
+    event: On<Replace, TextSelection>,
+    selections: Query<&TextSelection>,
+    mut commands: Commands,
+) {
+    let Ok(selection) = selections.get(event.entity) else {
+        return;
+    };
+    for highlight in &selection.highlights {
+        commands.entity(*highlight).try_despawn();
+    }
+}
+
+/// Updates the highlights drawn over the selected text of text widgets.
+///
+/// Highlights are root nodes, so they do not inherit the visibility of their
+/// text widget. They are cleared instead while the widget is hidden, and drawn
+/// again once it is shown.
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_selection_highlights(
+    mut commands: Commands,
+    mut nodes: Query<
+        (
+            Entity,
+            &mut TextSelection,
+            &ComputedNode,
+            &UiGlobalTransform,
+            &TextLayoutInfo,
+            &InheritedVisibility,
+        ),
+        Or<(
+            Changed<TextSelection>,
+            Changed<ComputedNode>,
+            Changed<UiGlobalTransform>,
+            Changed<TextLayoutInfo>,
+            Changed<InheritedVisibility>,
+        )>,
+    >,
+    mut highlights: Query<&mut Node, With<SelectionHighlight>>,
+    mut reader: TextUiReader,
+) {
+    for (entity, mut selection, computed, transform, info, visibility) in &mut nodes {
+        let range = selection.range().filter(|_| visibility.get());
+        let rects = match range {
+            Some(range) => {
+                let line_starts = line_starts(&block_text(&mut reader, entity));
+                selection_rects(info, &line_starts, range)
+            }
+            None => vec![],
+        };
+
+        while selection.highlights.len() > rects.len() {
+            if let Some(highlight) = selection.highlights.pop() {
+                commands.entity(highlight).try_despawn();
+            }
+        }
+
+        let scale = computed.inverse_scale_factor();
+        let inset = computed.content_inset();
+        let origin =
+            transform.translation - computed.size() / 2.0 + Vec2::new(inset.left, inset.top);
+
+        for (i, rect) in rects.into_iter().enumerate() {
+            let node = Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px((origin.x + rect.min.x) * scale),
+                top: Val::Px((origin.y + rect.min.y) * scale),
+                width: Val::Px(rect.width() * scale),
+                height: Val::Px(rect.height() * scale),
+                ..default()
+            };
+
+            match selection.highlights.get(i) {
+                Some(highlight) => {
+                    if let Ok(mut highlight) = highlights.get_mut(*highlight) {
+                        highlight.set_if_neq(node);
+                    }
+                }
+                None => {
+                    let highlight = commands
+                        .spawn((
+                            SelectionHighlight,
+                            node,
+                            BackgroundColor(SELECTION_COLOR),
+                            GlobalZIndex(i32::MAX - 1),
+                        ))
+                        .id();
+                    selection.highlights.push(highlight);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! Flattened snapshots of spawned NekoMaid UI trees, for debugging and
+//! golden-file tests.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use bevy::prelude::*;
+
+use crate::components::{NekoUINode, NekoUITree};
+use crate::parse::scope::ScopeTree;
+use crate::parse::value::PropertyValue;
+
+/// A snapshot of a spawned node and its descendants.
+///
+/// Classes and properties are sorted by name, so two snapshots of the same tree
+/// always compare and print identically.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NekoNodeSnapshot {
+    /// The native widget type of the node.
+    pub widget: String,
+
+    /// The classes of the node.
+    pub classes: Vec<String>,
+
+    /// The resolved value of every property applied to the node.
+    pub properties: BTreeMap<String, PropertyValue>,
+
+    /// The snapshots of the child nodes.
+    pub children: Vec<NekoNodeSnapshot>,
+}
+
+impl NekoNodeSnapshot {
+    /// Takes a snapshot of each top-level node of the given [`NekoUITree`]
+    /// entity.
+    ///
+    /// Returns an empty list if the entity has no tree or the tree has not been
+    /// spawned yet.
+    pub fn of_tree(world: &World, tree: Entity) -> Vec<Self> {
+        let Some(root) = world.get::<NekoUITree>(tree) else {
+            return vec![];
+        };
+
+        let mut nodes = vec![];
+        collect_children(world, &root.scope, tree, &mut nodes);
+        nodes
+    }
+
+    /// Takes a snapshot of the given node entity.
+    fn of_node(world: &World, scopes: &ScopeTree, entity: Entity, node: &NekoUINode) -> Self {
+        let mut element = node.element.clone();
+        let mut classes = element.classes().iter().cloned().collect::<Vec<_>>();
+        classes.sort();
+
+        let mut view = element.view_mut(scopes);
+        view.update_active_properties();
+        let names = view.active_properties().cloned().collect::<Vec<_>>();
+
+        let mut properties = BTreeMap::new();
+        for name in names {
+            if let Some(value) = view.get_property(&name) {
+                properties.insert(name, value.clone());
+            }
+        }
+
+        let mut children = vec![];
+        collect_children(world, scopes, entity, &mut children);
+
+        Self {
+            widget: view.classpath().last().widget.clone(),
+            classes,
+            properties,
+            children,
+        }
+    }
+
+    /// Writes this snapshot, indented by the given depth.
+    fn write(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        let indent = "    ".repeat(depth);
+
+        write!(f, "{indent}{}", self.widget)?;
+        for class in &self.classes {
+            write!(f, " +{class}")?;
+        }
+        writeln!(f)?;
+
+        for (name, value) in &self.properties {
+            writeln!(f, "{indent}    {name}: {value};")?;
+        }
+
+        for child in &self.children {
+            child.write(f, depth + 1)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for NekoNodeSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+/// Takes a snapshot of each NekoMaid node below the given entity.
+///
+/// Entities without a [`NekoUINode`], such as those spawned internally by
+/// native widgets, are skipped, with their own children taking their place.
+fn collect_children(
+    world: &World,
+    scopes: &ScopeTree,
+    entity: Entity,
+    out: &mut Vec<NekoNodeSnapshot>,
+) {
+    let Some(children) = world.get::<Children>(entity) else {
+        return;
+    };
+
+    for &child in children {
+        match world.get::<NekoUINode>(child) {
+            Some(node) => out.push(NekoNodeSnapshot::of_node(world, scopes, child, node)),
+            None => collect_children(world, scopes, child, out),
+        }
+    }
+}
@@ -6,11 +6,17 @@ use bevy::asset::{AssetLoadFailedEvent, LoadState};
 use bevy::platform::collections::HashSet;
 use bevy::prelude::*;
 
-use crate::asset::NekoMaidUI;
+use crate::asset::{NekoMaidUI, NekoUILoaded};
 use crate::components::{NekoUINode, NekoUITree};
+use crate::diagnostics::NekoDiagnostics;
+use crate::font::{DefaultFont, FontFamilyRegistry};
 use crate::marker::MarkerRegistry;
+use crate::parse::class::CLASS_IF_PREFIX;
+use crate::parse::component::COMPONENT_PREFIX;
 use crate::parse::element::NekoElementBuilder;
-use crate::parse::scope::{ScopeId, ScopeNotificationMap};
+use crate::parse::scope::ScopeId;
+use crate::parse::widget::Widget;
+use crate::render::interaction::InteractionClasses;
 use crate::render::update::update_node;
 
 /// Listens for changes to the [`NekoUITree`] component and spawns the UI tree
@@ -20,6 +26,7 @@ pub(crate) fn spawn_tree(
     asset_server: Res<AssetServer>,
     assets: Res<Assets<NekoMaidUI>>,
     markers: Res<MarkerRegistry>,
+    mut diagnostics: ResMut<NekoDiagnostics>,
     roots: Query<
         (Entity, &mut NekoUITree, &mut Node),
         Or<(Added<NekoUITree>, Changed<NekoUITree>)>,
@@ -33,16 +40,20 @@ pub(crate) fn spawn_tree(
         let t = Instant::now();
 
         root.clear_dirty();
+        root.clear_indices();
         commands.entity(root_entity).despawn_children();
 
-        *node = Node {
-            width: Val::Percent(100.0),
-            height: Val::Percent(100.0),
-            ..default()
-        };
+        if let Some((width, height)) = root.root_size() {
+            node.width = width;
+            node.height = height;
+        }
 
-        let Some(asset) = assets.get(root.asset()) else {
-            match asset_server.get_load_state(root.asset()) {
+        if let Some(policy) = root.root_focus_policy() {
+            commands.entity(root_entity).insert(policy);
+        }
+
+        let Some(asset) = assets.get(root.active_asset()) else {
+            match asset_server.get_load_state(root.active_asset()) {
                 Some(LoadState::Loading) => {}
                 _ => error!("Failed to load NekoMaidUI asset for NekoUITree"),
             }
@@ -55,11 +66,12 @@ pub(crate) fn spawn_tree(
         }
         root.scope_notification.clear();
 
+        let mut element_count = 0;
         for element in &asset.elements {
-            spawn_element(
+            element_count += spawn_element(
                 &asset_server,
                 &markers,
-                &mut root.scope_notification,
+                &mut root,
                 &mut commands,
                 &element,
                 root_entity,
@@ -67,6 +79,10 @@ pub(crate) fn spawn_tree(
             );
         }
 
+        diagnostics.parse_time = asset.parse_time();
+        diagnostics.element_count = element_count;
+        diagnostics.spawn_time = t.elapsed();
+
         debug!(
             "Spawned tree {root_entity} in {} ms.",
             t.elapsed().as_millis()
@@ -74,70 +90,90 @@ pub(crate) fn spawn_tree(
     }
 }
 
-/// Recursively spawns a [`NekoElementBuilder`] and its children.
+/// Recursively spawns a [`NekoElementBuilder`] and its children, returning the
+/// number of spawned elements.
+///
+/// The nodes of static trees do not listen to changes, and all of their
+/// properties are applied once instead.
 fn spawn_element(
     asset_server: &Res<AssetServer>,
     markers: &MarkerRegistry,
-    scope_notification: &mut ScopeNotificationMap,
+    tree: &mut NekoUITree,
     commands: &mut Commands,
     element: &NekoElementBuilder,
     parent: Entity,
     root: Entity,
-) {
+) -> usize {
     let entity =
         (element.native_widget.spawn_func)(asset_server, commands, &element.element, parent);
 
-    scope_notification.register(element.element.scope_id(), entity);
+    let mut node_element = element.element.clone();
+    if let Some(viewport) = tree.viewport
+        && node_element.set_viewport(viewport)
+    {
+        node_element.update_active_styles();
+    }
+
+    if let Some(key) = node_element.view_mut(&tree.scope).get_as::<String>("key") {
+        tree.set_key(entity, Some(key));
+    }
+
+    let mut updated_properties = vec![];
+    match tree.is_static() {
+        false => tree
+            .scope_notification
+            .register(element.element.scope_id(), entity),
+        true => updated_properties.extend(node_element.active_properties().cloned()),
+    }
 
     commands.entity(entity).insert((NekoUINode {
         root,
-        element: element.element.clone(),
-        updated_properties: vec![],
+        element: node_element,
+        updated_properties,
+        conditional_classes: HashSet::new(),
     },));
 
+    let mut count = 1;
     for child in &element.children {
-        spawn_element(
-            asset_server,
-            markers,
-            scope_notification,
-            commands,
-            child,
-            entity,
-            root,
-        );
+        count += spawn_element(asset_server, markers, tree, commands, child, entity, root);
     }
+    count
 }
 
 /// Handle interactions on interactable elements.
-pub fn handle_interactions(nodes: Query<(&mut NekoUINode, &Interaction), Changed<Interaction>>) {
+pub fn handle_interactions(
+    classes: Res<InteractionClasses>,
+    nodes: Query<(&mut NekoUINode, &Interaction), Changed<Interaction>>,
+) {
     for (mut node, interaction) in nodes {
         match interaction {
             Interaction::Pressed => {
-                node.element.add_class("pressed".to_string());
+                classes.set_pressed(&mut node, true);
             }
             Interaction::Hovered => {
-                node.element.add_class("hovered".to_string());
-                node.element.remove_class("pressed");
+                classes.set_hovered(&mut node, true);
+                classes.set_pressed(&mut node, false);
             }
             Interaction::None => {
-                node.element.remove_class("hovered");
-                node.element.remove_class("pressed");
+                classes.set_hovered(&mut node, false);
+                classes.set_pressed(&mut node, false);
             }
         }
     }
 }
 
-/// Removes the `hovered` and `pressed` classes from elements that
-/// are no longer interactable.
+/// Removes the [`InteractionClasses`] from elements that are no longer
+/// interactable.
 pub fn removed_interactable(
     event: On<Remove, Interaction>,
+    classes: Res<InteractionClasses>,
     mut nodes: Query<&mut NekoUINode, With<Interaction>>,
 ) {
     let Ok(mut node) = nodes.get_mut(event.entity) else {
         return;
     };
-    node.element.remove_class("hovered");
-    node.element.remove_class("pressed");
+    classes.set_hovered(&mut node, false);
+    classes.set_pressed(&mut node, false);
 }
 
 /// Update class paths and class markers.
@@ -158,8 +194,6 @@ pub fn handle_class_changes(
     let t = Instant::now();
 
     let mut entities = vec![];
-    let mut added_classes = vec![];
-    let mut removed_classes = vec![];
 
     for &entity in &changed_nodes {
         let mut nodes = set.p1();
@@ -170,15 +204,30 @@ pub fn handle_class_changes(
             continue;
         }
 
-        for class in &node.element.added_classes {
-            markers.insert(commands.entity(entity), class);
-        }
-        for class in &node.element.removed_classes {
-            markers.remove(commands.entity(entity), class);
-        }
-
-        added_classes.extend(node.element.added_classes.drain(..));
-        removed_classes.extend(node.element.removed_classes.drain(..));
+        // a class may be both added and removed within the same frame, so only
+        // the final state of each changed class is propagated.
+        let element = &mut node.element;
+        let mut changed_classes = element
+            .added_classes
+            .drain(..)
+            .chain(element.removed_classes.drain(..))
+            .collect::<Vec<_>>();
+        changed_classes.sort();
+        changed_classes.dedup();
+        let changed_classes = changed_classes
+            .into_iter()
+            .map(|class| {
+                let present = element.classes().contains(&class);
+                (class, present)
+            })
+            .collect::<Vec<_>>();
+
+        markers.apply(
+            commands.entity(entity),
+            changed_classes
+                .iter()
+                .map(|(class, present)| (class.as_str(), *present)),
+        );
 
         let Some(children) = children else { continue };
         entities.extend(children.iter().map(|e| (e, 1)));
@@ -191,11 +240,12 @@ pub fn handle_class_changes(
             let Some(set) = node.element.classpath_mut().get_mut(i) else {
                 continue;
             };
-            for class in &added_classes {
-                set.classes.insert(class.clone());
-            }
-            for class in &removed_classes {
-                set.classes.remove(class);
+            for (class, present) in &changed_classes {
+                if *present {
+                    set.classes.insert(class.clone());
+                } else {
+                    set.classes.remove(class);
+                }
             }
 
             if let Some(children) = children {
@@ -236,6 +286,13 @@ pub fn update_styles(
             continue;
         };
 
+        // the properties of static nodes are all applied when spawned
+        if root.is_static() {
+            node.element.deactivated_styles.clear();
+            node.element.activated_styles.clear();
+            continue;
+        }
+
         for &i in &node.element.deactivated_styles {
             let Some(style) = node.element.styles.get(i) else {
                 continue;
@@ -277,10 +334,24 @@ pub fn update_styles(
     );
 }
 
+/// Applies the visibility of Neko UI trees to their root node.
+pub(crate) fn update_tree_visibility(
+    roots: Query<(&NekoUITree, &mut Visibility), Changed<NekoUITree>>,
+) {
+    for (root, mut visibility) in roots {
+        let target = match root.is_visible() {
+            true => Visibility::Inherited,
+            false => Visibility::Hidden,
+        };
+        visibility.set_if_neq(target);
+    }
+}
+
 /// Update scope of Neko UI trees.
 pub fn update_scope(
     mut roots: Query<(Entity, &mut NekoUITree), Changed<NekoUITree>>,
     mut nodes: Query<&mut NekoUINode>,
+    mut diagnostics: ResMut<NekoDiagnostics>,
 ) {
     for (entity, root) in roots.iter_mut() {
         if root.update_names.is_empty() {
@@ -337,6 +408,7 @@ pub fn update_scope(
         }
 
         root.update_names.clear();
+        diagnostics.scope_update_time = t.elapsed();
 
         debug!(
             "Updated scope of {entity} in {} ms.",
@@ -346,11 +418,17 @@ pub fn update_scope(
 }
 
 /// Update node properties.
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn update_nodes(
     asset_server: Res<AssetServer>,
+    markers: Res<MarkerRegistry>,
+    fonts: Res<FontFamilyRegistry>,
+    default_font: Res<DefaultFont>,
+    mut diagnostics: ResMut<NekoDiagnostics>,
     mut roots: Query<&mut NekoUITree>,
     q: Query<
         (
+            Entity,
             &mut NekoUINode,
             &mut Node,
             &mut BorderColor,
@@ -365,6 +443,7 @@ pub(crate) fn update_nodes(
         ),
         Changed<NekoUINode>,
     >,
+    mut commands: Commands,
 ) {
     if q.is_empty() {
         return;
@@ -373,6 +452,7 @@ pub(crate) fn update_nodes(
     let t = Instant::now();
 
     for (
+        entity,
         neko_node,
         mut node,
         mut border_color,
@@ -397,7 +477,7 @@ pub(crate) fn update_nodes(
             updated_properties,
             element,
             root,
-            ..
+            conditional_classes,
         } = neko_node.into_inner();
 
         let Ok(mut root) = roots.get_mut(*root) else {
@@ -406,8 +486,11 @@ pub(crate) fn update_nodes(
 
         update_node(
             &asset_server,
-            element.view_mut(&mut root.scope),
+            &fonts,
+            &default_font,
+            element.view_mut(&root.scope),
             updated_properties.iter(),
+            &mut commands.entity(entity),
             &mut node,
             &mut border_color,
             &mut border_radius,
@@ -420,12 +503,65 @@ pub(crate) fn update_nodes(
             &mut layout.map(|v| v.into_inner()),
         );
 
+        if updated_properties.iter().any(|name| name == "name") {
+            let id = element.view_mut(&root.scope).get_as("name");
+            root.set_id(entity, id);
+        }
+
+        if updated_properties.iter().any(|name| name == "key") {
+            let key = element.view_mut(&root.scope).get_as("key");
+            root.set_key(entity, key);
+        }
+
+        for name in updated_properties.iter() {
+            let Some(component) = name.strip_prefix(COMPONENT_PREFIX) else {
+                continue;
+            };
+            let value = element.view_mut(&root.scope).get_property(name).cloned();
+            markers.apply_component(commands.entity(entity), component, value);
+        }
+
+        for name in updated_properties.iter() {
+            let Some(class) = name.strip_prefix(CLASS_IF_PREFIX) else {
+                continue;
+            };
+            // classes from `class` statements or added at runtime are left
+            // untouched, whatever the condition.
+            match element.view_mut(&root.scope).get_as_or(name, false) {
+                true if !element.classes().contains(class) => {
+                    conditional_classes.insert(class.to_string());
+                    element.add_class(class.to_string());
+                }
+                false if conditional_classes.remove(class) => element.remove_class(class),
+                _ => {}
+            }
+        }
+
         updated_properties.clear();
     }
 
+    diagnostics.node_update_time = t.elapsed();
     debug!("Updated node properties in {} ms.", t.elapsed().as_millis());
 }
 
+/// Removes the [`NekoUINode`] component of the nodes of static trees once all
+/// of their properties are applied.
+///
+/// This runs after [`NekoMaidSystems::PropertiesApplied`](crate::NekoMaidSystems::PropertiesApplied),
+/// so that the newly spawned nodes still inherit the properties of their
+/// ancestors.
+pub(crate) fn strip_static_nodes(
+    nodes: Query<(Entity, &NekoUINode), Changed<NekoUINode>>,
+    roots: Query<&NekoUITree>,
+    mut commands: Commands,
+) {
+    for (entity, node) in nodes {
+        if roots.get(node.root).is_ok_and(|root| root.is_static()) {
+            commands.entity(entity).remove::<NekoUINode>();
+        }
+    }
+}
+
 /// Listens for changes to the [`NekoMaidUI`] asset and updates any existing UI
 /// trees accordingly.
 pub(crate) fn update_tree(
@@ -437,6 +573,9 @@ pub(crate) fn update_tree(
             AssetEvent::Modified { id } | AssetEvent::LoadedWithDependencies { id } => {
                 for mut root in roots.iter_mut() {
                     if root.asset().id() == *id {
+                        root.set_load_error(None);
+                        root.mark_dirty();
+                    } else if root.active_asset().id() == *id {
                         root.mark_dirty();
                     }
                 }
@@ -446,11 +585,43 @@ pub(crate) fn update_tree(
     }
 }
 
+/// Sends a [`NekoUILoaded`] message for every NekoMaid UI asset that finished
+/// loading.
+pub(crate) fn asset_loaded(
+    mut asset_updates: MessageReader<AssetEvent<NekoMaidUI>>,
+    assets: Res<Assets<NekoMaidUI>>,
+    mut loaded: MessageWriter<NekoUILoaded>,
+) {
+    for event in asset_updates.read() {
+        let AssetEvent::LoadedWithDependencies { id } = event else {
+            continue;
+        };
+        let Some(asset) = assets.get(*id) else {
+            continue;
+        };
+
+        loaded.write(NekoUILoaded {
+            id: *id,
+            widget_count: asset
+                .widgets
+                .values()
+                .filter(|widget| matches!(widget, Widget::Custom(_)))
+                .count(),
+            style_count: asset.styles.len(),
+        });
+    }
+}
+
 /// Listens for asset load failures and clears any existing UI trees that
-/// reference the failed asset.
+/// reference the failed asset, or spawns their fallback asset instead if they
+/// have one. The error is recorded on the tree, see
+/// [`NekoUITree::load_error`].
 ///
 /// (Having a UI tree suddenly disappear is a good indicator to the developer
 /// that something has gone wrong with their code.)
+///
+/// Trees created with [`NekoUITree::with_clear_on_failure`] disabled keep their
+/// nodes instead.
 pub(crate) fn asset_failure(
     mut asset_failures: MessageReader<AssetLoadFailedEvent<NekoMaidUI>>,
     mut roots: Query<&mut NekoUITree>,
@@ -458,7 +629,10 @@ pub(crate) fn asset_failure(
     for event in asset_failures.read() {
         for mut root in roots.iter_mut() {
             if root.asset().id() == event.id {
-                root.mark_dirty();
+                root.set_load_error(Some(event.error.to_string()));
+                if root.clear_on_failure() {
+                    root.mark_dirty();
+                }
             }
         }
     }
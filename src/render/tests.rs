@@ -0,0 +1,2793 @@
+//! Tests
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use bevy::asset::{AssetLoadError, AssetLoadFailedEvent, AssetPath, AssetPlugin, ron, uuid_handle};
+use bevy::input::ButtonInput;
+use bevy::input::keyboard::KeyCode;
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::input_focus::InputFocus;
+use bevy::input_focus::tab_navigation::{NavAction, TabIndex, TabNavigation};
+use bevy::math::Affine2;
+use bevy::prelude::*;
+use bevy::text::{GlyphAtlasInfo, GlyphAtlasLocation, LineHeight, PositionedGlyph, TextLayoutInfo};
+use bevy::time::TimeUpdateStrategy;
+use bevy::ui::{FocusPolicy, RelativeCursorPosition};
+use bevy::window::{PrimaryWindow, WindowResolution};
+use pretty_assertions::assert_eq;
+
+use crate::animation::AnimateVariable;
+use crate::asset::{NekoMaidSettings, NekoMaidUI, NekoUILoaded, resolve_import};
+use crate::bind::BindAppExt;
+use crate::components::{NekoUINode, NekoUITree};
+use crate::diagnostics::NekoDiagnostics;
+use crate::font::{DefaultFont, FontAppExt, FontFamily};
+use crate::marker::{MarkerAppExt, NekoMarker};
+use crate::native::NATIVE_WIDGETS;
+use crate::parse::NekoMaidParser;
+use crate::parse::value::PropertyValue;
+use crate::render::debug::{DebugSizeLabel, NekoDebug};
+use crate::render::grid_area::{GridTemplateAreas, GridTemplateError};
+use crate::render::interaction::InteractionClasses;
+use crate::render::link::NekoLinkClicked;
+use crate::render::scroll::{SCROLL_LINE_HEIGHT, WheelScroll};
+use crate::render::selection::{NekoTextCopied, SelectionHighlight, TextSelection, caret_at};
+use crate::render::snapshot::NekoNodeSnapshot;
+use crate::{NekoMaidPlugin, NekoMaidSystems};
+
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default(), NekoMaidPlugin));
+    app
+}
+
+fn load(app: &mut App, source: &str) -> Handle<NekoMaidUI> {
+    let mut parser = NekoMaidParser::tokenize(source).unwrap();
+    for native in NATIVE_WIDGETS.iter() {
+        parser.register_native_widget(native.clone());
+    }
+    let module = parser.finish().unwrap();
+
+    app.world_mut()
+        .resource_mut::<Assets<NekoMaidUI>>()
+        .add(NekoMaidUI {
+            module,
+            parse_time: Duration::ZERO,
+        })
+}
+
+fn spawn_tree(app: &mut App, source: &str) -> Entity {
+    let handle = load(app, source);
+    let tree = app.world_mut().spawn(NekoUITree::new(handle)).id();
+    app.update();
+    tree
+}
+
+fn child(app: &App, entity: Entity, index: usize) -> Entity {
+    app.world().get::<Children>(entity).unwrap()[index]
+}
+
+fn tree(app: &mut App, tree: Entity) -> Mut<'_, NekoUITree> {
+    app.world_mut().get_mut::<NekoUITree>(tree).unwrap()
+}
+
+/// Returns a 10px wide and 20px tall glyph with its top left corner at the
+/// given position.
+fn glyph(x: f32, y: f32, line_index: usize, byte_index: usize) -> PositionedGlyph {
+    PositionedGlyph {
+        position: Vec2::new(x + 5.0, y + 10.0),
+        size: Vec2::new(10.0, 20.0),
+        atlas_info: GlyphAtlasInfo {
+            texture: AssetId::default(),
+            texture_atlas: AssetId::default(),
+            location: GlyphAtlasLocation {
+                glyph_index: 0,
+                offset: IVec2::ZERO,
+            },
+        },
+        span_index: 0,
+        line_index,
+        byte_index,
+        byte_length: 1,
+    }
+}
+
+/// Lays out a node of the given size with the given text layout by hand, and
+/// marks it as visible, since the layout, text and visibility pipelines do not
+/// run in tests.
+///
+/// The content of the node grows to fit the glyphs and the sections.
+fn fake_text_layout(app: &mut App, entity: Entity, size: Vec2, info: TextLayoutInfo) {
+    let content_size = info
+        .glyphs
+        .iter()
+        .map(|glyph| glyph.position + glyph.size / 2.0)
+        .chain(info.section_rects.iter().map(|(_, rect)| rect.max))
+        .fold(size, Vec2::max);
+
+    let mut entity = app.world_mut().entity_mut(entity);
+    entity.insert((info, InheritedVisibility::VISIBLE));
+    *entity.get_mut::<ComputedNode>().unwrap() = ComputedNode {
+        size,
+        content_size,
+        inverse_scale_factor: 1.0,
+        ..default()
+    };
+}
+
+#[test]
+fn read_back_resolved_variable() {
+    const SOURCE: &str = r#"
+var size = 5;
+var width = $size;
+
+layout div {
+    width: $width;
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+
+    assert_eq!(
+        tree(&mut app, root).get_variable_resolved("width"),
+        Some(&PropertyValue::Number(5.0))
+    );
+
+    tree(&mut app, root).set_variable("size", PropertyValue::Number(10.0));
+    assert_eq!(
+        tree(&mut app, root).get_variable("size"),
+        Some(&PropertyValue::Number(10.0))
+    );
+
+    app.update();
+    assert_eq!(
+        tree(&mut app, root).get_variable_resolved("width"),
+        Some(&PropertyValue::Number(10.0))
+    );
+    assert_eq!(
+        tree(&mut app, root).get_variable("width"),
+        Some(&PropertyValue::Number(10.0))
+    );
+    assert_eq!(tree(&mut app, root).get_variable("height"), None);
+}
+
+#[test]
+fn reload_respawns_tree() {
+    const SOURCE: &str = r#"
+layout div {
+    with p {
+        text: "Hello";
+    }
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+
+    let children = |app: &mut App| -> Vec<Entity> {
+        app.world()
+            .get::<Children>(root)
+            .map(|c| c.to_vec())
+            .unwrap_or_default()
+    };
+    let before = children(&mut app);
+    assert_eq!(before.len(), 1);
+
+    tree(&mut app, root).reload();
+    assert!(tree(&mut app, root).is_dirty());
+    app.update();
+
+    let after = children(&mut app);
+    assert_eq!(after.len(), 1);
+    assert_ne!(before, after);
+    assert!(app.world().get_entity(before[0]).is_err());
+    assert!(!tree(&mut app, root).is_dirty());
+
+    let text = app
+        .world()
+        .get::<Children>(after[0])
+        .and_then(|c| app.world().get::<Text>(c[0]))
+        .unwrap();
+    assert_eq!(text.0, "Hello");
+}
+
+#[test]
+fn set_property_at_runtime() {
+    const SOURCE: &str = r#"
+style div +panel {
+    background-color: #ff0000;
+}
+
+layout div {
+    class panel;
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let div = child(&app, root, 0);
+    let background = |app: &App| app.world().get::<BackgroundColor>(div).unwrap().0;
+    assert_eq!(background(&app), Color::srgb(1.0, 0.0, 0.0));
+
+    app.world_mut()
+        .get_mut::<NekoUINode>(div)
+        .unwrap()
+        .set_property("background-color", Color::srgb(0.0, 0.0, 1.0).into());
+    app.update();
+    assert_eq!(background(&app), Color::srgb(0.0, 0.0, 1.0));
+
+    app.world_mut()
+        .get_mut::<NekoUINode>(div)
+        .unwrap()
+        .clear_property("background-color");
+    app.update();
+    assert_eq!(background(&app), Color::srgb(1.0, 0.0, 0.0));
+}
+
+#[test]
+fn add_class_from_system() {
+    const SOURCE: &str = r#"
+style div +active {
+    background-color: #ff0000;
+
+    with p {
+        color: #00ff00;
+    }
+}
+
+layout div {
+    with p {
+        text: "Hello";
+    }
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let div = child(&app, root, 0);
+    let p = child(&app, div, 0);
+    assert_eq!(
+        app.world().get::<BackgroundColor>(div).unwrap().0,
+        Color::NONE
+    );
+
+    app.add_systems(
+        Update,
+        (move |mut nodes: Query<&mut NekoUINode>| {
+            nodes.get_mut(div).unwrap().add_class("active".into());
+        })
+        .run_if(run_once),
+    );
+    app.update();
+    app.update();
+
+    assert!(
+        app.world()
+            .get::<NekoUINode>(div)
+            .unwrap()
+            .has_class("active")
+    );
+    assert_eq!(
+        app.world().get::<BackgroundColor>(div).unwrap().0,
+        Color::srgb(1.0, 0.0, 0.0)
+    );
+    assert_eq!(
+        app.world().get::<TextColor>(p).unwrap().0,
+        Color::srgb(0.0, 1.0, 0.0)
+    );
+
+    app.world_mut()
+        .get_mut::<NekoUINode>(div)
+        .unwrap()
+        .toggle_class("active");
+    app.update();
+
+    assert_eq!(
+        app.world().get::<BackgroundColor>(div).unwrap().0,
+        Color::NONE
+    );
+    assert_eq!(app.world().get::<TextColor>(p).unwrap().0, Color::WHITE);
+}
+
+#[test]
+fn animate_variable_over_time() {
+    const SOURCE: &str = r#"
+var size = 0;
+
+layout div {
+    width: $size;
+}
+    "#;
+
+    let mut app = app();
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+        250,
+    )));
+    let root = spawn_tree(&mut app, SOURCE);
+
+    let animation = app
+        .world_mut()
+        .spawn(AnimateVariable::new(root, "size", 10.0, 20.0, 0.5))
+        .id();
+
+    app.update();
+    assert_eq!(
+        tree(&mut app, root).get_variable("size"),
+        Some(&PropertyValue::Number(15.0))
+    );
+
+    for _ in 0 .. 3 {
+        app.update();
+    }
+    assert_eq!(
+        tree(&mut app, root).get_variable_resolved("size"),
+        Some(&PropertyValue::Number(20.0))
+    );
+    assert!(app.world().get_entity(animation).is_err());
+}
+
+#[test]
+fn slot_reads_widget_property() {
+    const SOURCE: &str = r#"
+def list-item {
+    var label = "Default";
+
+    layout div {
+        with div {
+            var item = $label;
+            output body;
+        }
+    }
+}
+
+layout list-item {
+    label: "Hello";
+
+    in body {
+        with p {
+            text: $item;
+        }
+    }
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let div = child(&app, root, 0);
+    let inner = child(&app, div, 0);
+    let p = child(&app, inner, 0);
+    assert_eq!(app.world().get::<Text>(p).unwrap().0, "Hello");
+}
+
+#[test]
+fn rtl_direction_mirrors_rows() {
+    const SOURCE: &str = r#"
+layout div {
+    direction: rtl;
+    justify-content: start;
+    justify-items: start;
+    justify-self: end;
+    align-content: start;
+
+    with div {
+        direction: rtl;
+        flex-direction: column;
+        justify-content: start;
+        align-items: start;
+        align-content: flex-end;
+    }
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let row = child(&app, root, 0);
+    let node = app.world().get::<Node>(row).unwrap();
+    assert_eq!(node.flex_direction, FlexDirection::RowReverse);
+    assert_eq!(node.justify_content, JustifyContent::End);
+    assert_eq!(node.justify_items, JustifyItems::End);
+    assert_eq!(node.justify_self, JustifySelf::Start);
+    // the cross axis of a row is vertical, so it is not mirrored
+    assert_eq!(node.align_content, AlignContent::Start);
+
+    let column = child(&app, row, 0);
+    let node = app.world().get::<Node>(column).unwrap();
+    assert_eq!(node.flex_direction, FlexDirection::Column);
+    assert_eq!(node.justify_content, JustifyContent::Start);
+    assert_eq!(node.align_items, AlignItems::End);
+    assert_eq!(node.align_content, AlignContent::FlexStart);
+
+    app.world_mut()
+        .get_mut::<NekoUINode>(row)
+        .unwrap()
+        .set_property("direction", "ltr".into());
+    app.update();
+
+    let node = app.world().get::<Node>(row).unwrap();
+    assert_eq!(node.flex_direction, FlexDirection::Row);
+    assert_eq!(node.justify_content, JustifyContent::Start);
+    assert_eq!(node.justify_items, JustifyItems::Start);
+    assert_eq!(node.justify_self, JustifySelf::End);
+}
+
+#[test]
+fn tab_index_order() {
+    const SOURCE: &str = r#"
+layout div {
+    with div {
+        class third;
+        tab-index: 3;
+    }
+
+    with div {
+        class first;
+        tab-index: 1;
+    }
+
+    with div {
+        class skipped;
+        tab-index: -1;
+    }
+
+    with div {
+        class second;
+        tab-index: 2;
+    }
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let div = child(&app, root, 0);
+    assert_eq!(
+        app.world().get::<TabIndex>(child(&app, div, 2)),
+        Some(&TabIndex(-1))
+    );
+
+    let mut visited = vec![];
+    let mut focus = InputFocus::default();
+    for _ in 0 .. 4 {
+        let next = app
+            .world_mut()
+            .run_system_cached_with(
+                |In(focus): In<InputFocus>, nav: TabNavigation| {
+                    nav.navigate(&focus, NavAction::Next).unwrap()
+                },
+                focus.clone(),
+            )
+            .unwrap();
+        focus.set(next);
+
+        let node = app.world().get::<NekoUINode>(next).unwrap();
+        let class = node.element.classes().iter().next().unwrap().clone();
+        visited.push(class);
+    }
+
+    assert_eq!(visited, vec!["first", "second", "third", "first"]);
+}
+
+#[test]
+fn drag_selects_text() {
+    const SOURCE: &str = r#"
+layout p {
+    text: "Hello";
+    user-select: text;
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let p = child(&app, root, 0);
+    assert_eq!(
+        app.world().get::<TextSelection>(p),
+        Some(&TextSelection::default())
+    );
+
+    let glyphs = (0 .. 5)
+        .map(|i| glyph(i as f32 * 10.0, 0.0, 0, i))
+        .collect();
+    fake_text_layout(
+        &mut app,
+        p,
+        Vec2::new(50.0, 20.0),
+        TextLayoutInfo {
+            glyphs,
+            ..default()
+        },
+    );
+
+    let drag = |app: &mut App, x: f32, interaction: Interaction| {
+        let mut entity = app.world_mut().entity_mut(p);
+        *entity.get_mut::<Interaction>().unwrap() = interaction;
+        entity
+            .get_mut::<RelativeCursorPosition>()
+            .unwrap()
+            .normalized = Some(Vec2::new(x / 50.0 - 0.5, 0.0));
+        app.update();
+    };
+
+    drag(&mut app, 12.0, Interaction::Pressed);
+    drag(&mut app, 38.0, Interaction::Pressed);
+    drag(&mut app, 38.0, Interaction::Hovered);
+
+    let selection = app.world().get::<TextSelection>(p).unwrap();
+    assert_eq!(selection.range(), Some(1 .. 4));
+    assert_eq!(selection.selected_text("Hello"), "ell");
+
+    // the node is centered at (25, 10), so its top-left corner is the origin
+    let transform = UiGlobalTransform::from(Affine2::from_translation(Vec2::new(25.0, 10.0)));
+    app.world_mut().entity_mut(p).insert(transform);
+    app.update();
+
+    let highlights = app
+        .world_mut()
+        .query_filtered::<&Node, With<SelectionHighlight>>()
+        .iter(app.world())
+        .map(|node| (node.left, node.top, node.width, node.height))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        highlights,
+        vec![(Val::Px(10.0), Val::ZERO, Val::Px(30.0), Val::Px(20.0))]
+    );
+
+    // highlights are cleared while the text is hidden, such as by a hidden tree
+    let count_highlights = |app: &mut App| {
+        app.world_mut()
+            .query_filtered::<(), With<SelectionHighlight>>()
+            .iter(app.world())
+            .count()
+    };
+    app.world_mut()
+        .entity_mut(p)
+        .insert(InheritedVisibility::HIDDEN);
+    app.update();
+    assert_eq!(count_highlights(&mut app), 0);
+
+    app.world_mut()
+        .entity_mut(p)
+        .insert(InheritedVisibility::VISIBLE);
+    app.update();
+    assert_eq!(count_highlights(&mut app), 1);
+
+    let mut keys = ButtonInput::<KeyCode>::default();
+    keys.press(KeyCode::ControlLeft);
+    keys.press(KeyCode::KeyC);
+    app.insert_resource(keys);
+    app.update();
+
+    let mut messages = app.world_mut().resource_mut::<Messages<NekoTextCopied>>();
+    assert_eq!(
+        messages.drain().collect::<Vec<_>>(),
+        vec![NekoTextCopied {
+            entity: p,
+            text: "ell".into(),
+        }]
+    );
+
+    app.world_mut().entity_mut(p).remove::<TextSelection>();
+    app.update();
+    assert_eq!(count_highlights(&mut app), 0);
+}
+
+#[test]
+fn caret_in_wrapped_line() {
+    // "abc def" wrapped onto two rows of the same line
+    let glyphs = (0 .. 6)
+        .map(|i| {
+            glyph(
+                (i % 3) as f32 * 10.0,
+                (i / 3) as f32 * 20.0,
+                0,
+                i / 3 * 4 + i % 3,
+            )
+        })
+        .collect();
+    let info = TextLayoutInfo {
+        glyphs,
+        ..default()
+    };
+
+    assert_eq!(caret_at(&info, &[0], Vec2::new(12.0, 30.0)), Some(5));
+    assert_eq!(caret_at(&info, &[0], Vec2::new(22.0, 30.0)), Some(6));
+    assert_eq!(caret_at(&info, &[0], Vec2::new(40.0, 30.0)), Some(7));
+    assert_eq!(caret_at(&info, &[0], Vec2::new(12.0, 5.0)), Some(1));
+}
+
+#[test]
+fn click_link_span() {
+    const SOURCE: &str = r#"
+layout p {
+    text: "Read the ";
+
+    with span {
+        text: "docs";
+        link: "https://docs.rs/neko-maid";
+    }
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let p = child(&app, root, 0);
+    let span = child(&app, p, 0);
+    assert!(app.world().get::<Interaction>(p).is_some());
+
+    fake_text_layout(
+        &mut app,
+        p,
+        Vec2::new(130.0, 20.0),
+        TextLayoutInfo {
+            section_rects: vec![
+                (p, Rect::new(0.0, 0.0, 90.0, 20.0)),
+                (span, Rect::new(90.0, 0.0, 130.0, 20.0)),
+            ],
+            ..default()
+        },
+    );
+
+    let click = |app: &mut App, x: f32| {
+        let mut entity = app.world_mut().entity_mut(p);
+        *entity.get_mut::<Interaction>().unwrap() = Interaction::Pressed;
+        entity
+            .get_mut::<RelativeCursorPosition>()
+            .unwrap()
+            .normalized = Some(Vec2::new(x / 130.0 - 0.5, 0.0));
+        app.update();
+        *app.world_mut().get_mut::<Interaction>(p).unwrap() = Interaction::Hovered;
+        app.update();
+
+        let mut messages = app.world_mut().resource_mut::<Messages<NekoLinkClicked>>();
+        messages.drain().collect::<Vec<_>>()
+    };
+
+    assert_eq!(click(&mut app, 40.0), vec![]);
+    assert_eq!(
+        click(&mut app, 100.0),
+        vec![NekoLinkClicked {
+            entity: span,
+            href: "https://docs.rs/neko-maid".into(),
+        }]
+    );
+    assert!(
+        app.world()
+            .get::<NekoUINode>(span)
+            .unwrap()
+            .has_class("hovered")
+    );
+}
+
+#[test]
+fn white_space() {
+    const SOURCE: &str = r#"
+layout div {
+    with p {
+        text: "a  b";
+    }
+
+    with p {
+        text: "a  b";
+        white-space: nowrap;
+    }
+
+    with p {
+        text: "a  b";
+        white-space: pre;
+    }
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let div = child(&app, root, 0);
+    let text = |app: &App, i: usize| {
+        let p = child(app, div, i);
+        let text = app.world().get::<Text>(p).unwrap().0.clone();
+        (text, app.world().get::<TextLayout>(p).unwrap().linebreak)
+    };
+
+    assert_eq!(text(&app, 0), ("a  b".into(), LineBreak::WordBoundary));
+    assert_eq!(text(&app, 1), ("a b".into(), LineBreak::NoWrap));
+    assert_eq!(text(&app, 2), ("a  b".into(), LineBreak::NoWrap));
+}
+
+#[test]
+fn line_clamp() {
+    const SOURCE: &str = r#"
+layout div {
+    with p {
+        text: "aaa bbb ccc";
+        line-clamp: 2;
+    }
+
+    with p {
+        text: "aaa bbb ccc";
+        line-clamp: 2;
+        text-overflow: ellipsis;
+    }
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let div = child(&app, root, 0);
+    for i in 0 .. 2 {
+        let p = child(&app, div, i);
+
+        // three words of three glyphs, each wrapped onto its own row
+        let glyphs = (0 .. 9)
+            .map(|i| {
+                glyph(
+                    (i % 3) as f32 * 10.0,
+                    (i / 3) as f32 * 20.0,
+                    0,
+                    i / 3 * 4 + i % 3,
+                )
+            })
+            .collect();
+        let section_rects = (0 .. 3)
+            .map(|row| {
+                (
+                    p,
+                    Rect::new(0.0, row as f32 * 20.0, 30.0, row as f32 * 20.0 + 20.0),
+                )
+            })
+            .collect();
+
+        fake_text_layout(
+            &mut app,
+            p,
+            Vec2::new(30.0, 60.0),
+            TextLayoutInfo {
+                glyphs,
+                section_rects,
+                ..default()
+            },
+        );
+    }
+    app.update();
+
+    let text = |app: &App, i: usize| {
+        let p = child(app, div, i);
+        app.world().get::<Text>(p).unwrap().0.clone()
+    };
+
+    assert_eq!(text(&app, 0), "aaa bbb");
+    assert_eq!(text(&app, 1), "aaa bb…");
+}
+
+#[test]
+fn max_lines_scrolls() {
+    const SOURCE: &str = r#"
+layout div {
+    max-lines: 2;
+
+    with p {
+        text: "a";
+        font-size: 10;
+    }
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let div = child(&app, root, 0);
+    let node = app.world().get::<Node>(div).unwrap();
+    assert_eq!(node.max_height, Val::Px(24.0));
+    assert_eq!(node.overflow.y, OverflowAxis::Scroll);
+
+    let append = |app: &mut App, height: f32| {
+        fake_text_layout(
+            app,
+            div,
+            Vec2::new(100.0, 24.0),
+            TextLayoutInfo {
+                section_rects: vec![(div, Rect::new(0.0, 0.0, 100.0, height))],
+                ..default()
+            },
+        );
+        app.update();
+        app.world().get::<ScrollPosition>(div).unwrap().y
+    };
+
+    assert_eq!(append(&mut app, 24.0), 0.0);
+    assert_eq!(append(&mut app, 48.0), 24.0);
+    assert_eq!(append(&mut app, 72.0), 48.0);
+
+    // scrolled away from the bottom, new lines do not move the content
+    app.world_mut().get_mut::<ScrollPosition>(div).unwrap().y = 12.0;
+    assert_eq!(append(&mut app, 96.0), 12.0);
+    assert_eq!(
+        app.world().get::<Node>(div).unwrap().max_height,
+        Val::Px(24.0)
+    );
+}
+
+#[test]
+fn max_lines_keeps_own_max_height() {
+    const SOURCE: &str = r#"
+layout div {
+    max-height: 100px;
+
+    with p {
+        text: "a";
+        font-size: 10;
+    }
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let div = child(&app, root, 0);
+    let max_lines = |app: &mut App, lines: f64| {
+        let mut node = app.world_mut().get_mut::<NekoUINode>(div).unwrap();
+        node.set_property("max-lines", PropertyValue::Number(lines));
+        app.update();
+        let node = app.world().get::<Node>(div).unwrap();
+        (node.max_height, node.overflow.y)
+    };
+
+    assert_eq!(
+        max_lines(&mut app, 2.0),
+        (Val::Px(24.0), OverflowAxis::Scroll)
+    );
+    assert_eq!(
+        max_lines(&mut app, 10.0),
+        (Val::Px(100.0), OverflowAxis::Scroll)
+    );
+
+    let mut node = app.world_mut().get_mut::<NekoUINode>(div).unwrap();
+    node.clear_property("max-lines");
+    app.update();
+    app.update();
+
+    let node = app.world().get::<Node>(div).unwrap();
+    assert_eq!(node.max_height, Val::Px(100.0));
+    assert_eq!(node.overflow.y, OverflowAxis::Visible);
+}
+
+#[test]
+fn font_style_selects_family_face() {
+    const SOURCE: &str = r#"
+layout div {
+    with p {
+        text: "a";
+        font: "body";
+        font-style: italic;
+    }
+
+    with p {
+        text: "a";
+        font: "mono";
+        font-style: italic;
+    }
+}
+    "#;
+
+    let mut app = app();
+    let body: Handle<Font> = uuid_handle!("00000000-0000-0000-0000-000000000001");
+    let body_italic: Handle<Font> = uuid_handle!("00000000-0000-0000-0000-000000000002");
+    let mono: Handle<Font> = uuid_handle!("00000000-0000-0000-0000-000000000003");
+    app.add_font_family(
+        "body",
+        FontFamily::new(body).with_italic(body_italic.clone()),
+    )
+    .add_font_family("mono", FontFamily::new(mono.clone()));
+
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let div = child(&app, root, 0);
+    let font = |app: &App, i: usize| {
+        let p = child(app, div, i);
+        app.world().get::<TextFont>(p).unwrap().font.clone()
+    };
+
+    assert_eq!(font(&app, 0), body_italic);
+    // the family has no italic face, so its normal face is used
+    assert_eq!(font(&app, 1), mono);
+}
+
+#[test]
+fn inherit_font_family() {
+    const SOURCE: &str = r#"
+layout div {
+    font: "body";
+    font-style: italic;
+
+    with p {
+        text: "a";
+    }
+
+    with p {
+        text: "a";
+        font-style: normal;
+    }
+}
+    "#;
+
+    let mut app = app();
+    let body: Handle<Font> = uuid_handle!("00000000-0000-0000-0000-000000000001");
+    let body_italic: Handle<Font> = uuid_handle!("00000000-0000-0000-0000-000000000002");
+    app.add_font_family(
+        "body",
+        FontFamily::new(body.clone()).with_italic(body_italic.clone()),
+    );
+
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let div = child(&app, root, 0);
+    let font = |app: &App, i: usize| {
+        let p = child(app, div, i);
+        app.world().get::<TextFont>(p).unwrap().font.clone()
+    };
+
+    assert_eq!(font(&app, 0), body_italic);
+    assert_eq!(font(&app, 1), body);
+}
+
+#[test]
+fn default_font() {
+    const SOURCE: &str = r#"
+layout p {
+    text: "a";
+    font: auto;
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let p = child(&app, root, 0);
+    assert_eq!(
+        app.world().get::<TextFont>(p).unwrap().font,
+        Handle::<Font>::default()
+    );
+
+    let body: Handle<Font> = uuid_handle!("00000000-0000-0000-0000-000000000001");
+    app.insert_resource(DefaultFont(body.clone()));
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let p = child(&app, root, 0);
+    assert_eq!(app.world().get::<TextFont>(p).unwrap().font, body);
+}
+
+#[test]
+fn font_style_without_font() {
+    const SOURCE: &str = r#"
+layout p {
+    text: "a";
+    font-style: italic;
+}
+    "#;
+
+    let mut app = app();
+    let body: Handle<Font> = uuid_handle!("00000000-0000-0000-0000-000000000001");
+    app.insert_resource(DefaultFont(body.clone()));
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let p = child(&app, root, 0);
+    assert_eq!(app.world().get::<TextFont>(p).unwrap().font, body);
+}
+
+#[test]
+fn wheel_scrolls_overflowing_node() {
+    const SOURCE: &str = r#"
+layout div {
+    height: 100px;
+    overflow-y: scroll;
+
+    with div {
+        height: 300px;
+    }
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let div = child(&app, root, 0);
+    assert!(app.world().get::<WheelScroll>(div).is_some());
+
+    let mut entity = app.world_mut().entity_mut(div);
+    *entity.get_mut::<ComputedNode>().unwrap() = ComputedNode {
+        size: Vec2::new(100.0, 100.0),
+        content_size: Vec2::new(100.0, 300.0),
+        inverse_scale_factor: 1.0,
+        ..default()
+    };
+    entity
+        .get_mut::<RelativeCursorPosition>()
+        .unwrap()
+        .cursor_over = true;
+
+    let scroll = |app: &mut App, lines: f32| {
+        app.world_mut().write_message(MouseWheel {
+            unit: MouseScrollUnit::Line,
+            x: 0.0,
+            y: lines,
+            window: Entity::PLACEHOLDER,
+        });
+        app.update();
+        app.world().get::<ScrollPosition>(div).unwrap().0
+    };
+
+    assert_eq!(
+        scroll(&mut app, -2.0),
+        Vec2::new(0.0, 2.0 * SCROLL_LINE_HEIGHT)
+    );
+    assert_eq!(scroll(&mut app, -100.0), Vec2::new(0.0, 200.0));
+    assert_eq!(scroll(&mut app, 100.0), Vec2::ZERO);
+}
+
+#[test]
+fn overflow_shorthand() {
+    const SOURCE: &str = r#"
+var axis = scroll;
+
+layout div {
+    with div {
+        overflow: hidden;
+    }
+
+    with div {
+        overflow: clip $axis;
+    }
+
+    with div {
+        overflow: visible scroll;
+        overflow-x: hidden;
+    }
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let div = child(&app, root, 0);
+    let overflow = |app: &App, i: usize| {
+        let node = child(app, div, i);
+        app.world().get::<Node>(node).unwrap().overflow
+    };
+
+    assert_eq!(overflow(&app, 0), Overflow::hidden());
+    assert_eq!(
+        overflow(&app, 1),
+        Overflow {
+            x: OverflowAxis::Clip,
+            y: OverflowAxis::Scroll,
+        }
+    );
+    assert_eq!(
+        overflow(&app, 2),
+        Overflow {
+            x: OverflowAxis::Hidden,
+            y: OverflowAxis::Scroll,
+        }
+    );
+}
+
+#[test]
+fn place_shorthands() {
+    const SOURCE: &str = r#"
+layout div {
+    with div {
+        place-items: center;
+        place-content: center;
+    }
+
+    with div {
+        place-items: start end;
+        place-content: end space-between;
+    }
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let div = child(&app, root, 0);
+    let node = |app: &App, i: usize| {
+        let node = child(app, div, i);
+        app.world().get::<Node>(node).unwrap().clone()
+    };
+
+    let single = node(&app, 0);
+    assert_eq!(single.align_items, AlignItems::Center);
+    assert_eq!(single.justify_items, JustifyItems::Center);
+    assert_eq!(single.align_content, AlignContent::Center);
+    assert_eq!(single.justify_content, JustifyContent::Center);
+
+    let pair = node(&app, 1);
+    assert_eq!(pair.align_items, AlignItems::Start);
+    assert_eq!(pair.justify_items, JustifyItems::End);
+    assert_eq!(pair.align_content, AlignContent::End);
+    assert_eq!(pair.justify_content, JustifyContent::SpaceBetween);
+}
+
+#[test]
+fn border_shorthand() {
+    const SOURCE: &str = r#"
+layout div {
+    with div {
+        border: 2px #000000;
+    }
+
+    with div {
+        border: 3px;
+    }
+
+    with div {
+        border: #ff0000;
+    }
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let div = child(&app, root, 0);
+    let border = |app: &App, i: usize| {
+        let node = child(app, div, i);
+        let thickness = app.world().get::<Node>(node).unwrap().border;
+        let color = *app.world().get::<BorderColor>(node).unwrap();
+        (thickness, color)
+    };
+
+    assert_eq!(
+        border(&app, 0),
+        (
+            UiRect::all(Val::Px(2.0)),
+            BorderColor::all(Color::srgb(0.0, 0.0, 0.0))
+        )
+    );
+    assert_eq!(
+        border(&app, 1),
+        (UiRect::all(Val::Px(3.0)), BorderColor::all(Color::NONE))
+    );
+    assert_eq!(
+        border(&app, 2),
+        (
+            UiRect::all(Val::Px(0.0)),
+            BorderColor::all(Color::srgb(1.0, 0.0, 0.0))
+        )
+    );
+}
+
+#[test]
+fn border_radius_shorthand() {
+    const SOURCE: &str = r#"
+layout div {
+    with div {
+        border-radius: 8px 4px;
+    }
+
+    with div {
+        border-radius: 1px 2px 3px 4px;
+    }
+
+    with div {
+        border-radius: 1px 2px 3px;
+        border-radius-top-left: 10px;
+    }
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let div = child(&app, root, 0);
+    let radius = |app: &App, i: usize| {
+        let node = child(app, div, i);
+        *app.world().get::<BorderRadius>(node).unwrap()
+    };
+
+    assert_eq!(radius(&app, 0), BorderRadius::px(8.0, 4.0, 8.0, 4.0));
+    assert_eq!(radius(&app, 1), BorderRadius::px(1.0, 2.0, 3.0, 4.0));
+    assert_eq!(radius(&app, 2), BorderRadius::px(10.0, 2.0, 3.0, 2.0));
+}
+
+#[test]
+fn diagnostics_after_spawn() {
+    const SOURCE: &str = r#"
+layout div {
+    with div {}
+    with p {
+        text: "Hello";
+    }
+}
+    "#;
+
+    let mut app = app();
+    spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let diagnostics = app.world().resource::<NekoDiagnostics>();
+    assert_eq!(diagnostics.element_count, 3);
+    assert!(diagnostics.spawn_time > Duration::ZERO);
+    assert!(diagnostics.scope_update_time > Duration::ZERO);
+    assert!(diagnostics.node_update_time > Duration::ZERO);
+}
+
+#[test]
+fn root_size() {
+    const SOURCE: &str = r#"
+layout div {}
+    "#;
+
+    let mut app = app();
+    let handle = load(&mut app, SOURCE);
+    let sized = app
+        .world_mut()
+        .spawn(NekoUITree::new(handle.clone()).with_root_size(Val::Px(200.0), Val::Px(100.0)))
+        .id();
+    let custom = app
+        .world_mut()
+        .spawn((
+            NekoUITree::new(handle.clone()).with_custom_root(),
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Px(50.0),
+                ..default()
+            },
+        ))
+        .id();
+    let default = app.world_mut().spawn(NekoUITree::new(handle)).id();
+    app.update();
+
+    let node = |app: &App, e: Entity| app.world().get::<Node>(e).unwrap().clone();
+
+    let sized = node(&app, sized);
+    assert_eq!(
+        (sized.width, sized.height),
+        (Val::Px(200.0), Val::Px(100.0))
+    );
+
+    let custom = node(&app, custom);
+    assert_eq!(custom.position_type, PositionType::Absolute);
+    assert_eq!((custom.width, custom.height), (Val::Px(50.0), Val::Auto));
+
+    let default = node(&app, default);
+    assert_eq!(
+        (default.width, default.height),
+        (Val::Percent(100.0), Val::Percent(100.0))
+    );
+}
+
+#[test]
+fn root_focus_policy() {
+    const SOURCE: &str = r#"
+layout div {}
+    "#;
+
+    let mut app = app();
+    let handle = load(&mut app, SOURCE);
+    let panel = app
+        .world_mut()
+        .spawn((
+            NekoUITree::new(handle.clone())
+                .with_custom_root()
+                .with_root_focus_policy(FocusPolicy::Pass),
+            FocusPolicy::Block,
+        ))
+        .id();
+    let default = app
+        .world_mut()
+        .spawn((NekoUITree::new(handle), FocusPolicy::Block))
+        .id();
+    app.update();
+
+    let node = app.world().get::<Node>(panel).unwrap();
+    assert_eq!((node.width, node.height), (Val::Auto, Val::Auto));
+    assert_eq!(
+        app.world().get::<FocusPolicy>(panel),
+        Some(&FocusPolicy::Pass)
+    );
+    assert_eq!(
+        app.world().get::<FocusPolicy>(default),
+        Some(&FocusPolicy::Block)
+    );
+}
+
+#[test]
+fn name_component() {
+    const SOURCE: &str = r#"
+layout div {
+    with div {
+        name: "play-button";
+    }
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let button = child(&app, child(&app, root, 0), 0);
+    assert_eq!(
+        app.world().get::<Name>(button).map(Name::as_str),
+        Some("play-button")
+    );
+}
+
+#[test]
+fn find_by_id() {
+    const SOURCE: &str = r#"
+layout div {
+    with div {
+        name: "save-button";
+    }
+
+    with div {
+        name: "item";
+    }
+
+    with div {
+        name: "item";
+    }
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let save = child(&app, child(&app, root, 0), 0);
+    let tree = app.world().get::<NekoUITree>(root).unwrap();
+    assert_eq!(tree.find_by_id("save-button"), Some(save));
+    assert_eq!(tree.find_by_id("item"), None);
+    assert_eq!(tree.find_by_id("missing"), None);
+}
+
+#[test]
+fn percent_font_size() {
+    const SOURCE: &str = r#"
+var size = 20;
+
+layout div {
+    with p {
+        text: "a";
+        font-size: $size;
+
+        with span {
+            text: "b";
+            font-size: 150%;
+
+            with span {
+                text: "c";
+                font-size: 50%;
+            }
+        }
+    }
+
+    with p {
+        text: "d";
+        font-size: 50%;
+    }
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let size = |app: &App, e: Entity| app.world().get::<TextFont>(e).unwrap().font_size;
+
+    let div = child(&app, root, 0);
+    let p = child(&app, div, 0);
+    let span = child(&app, p, 0);
+    let nested = child(&app, span, 0);
+
+    assert_eq!(size(&app, p), 20.0);
+    assert_eq!(size(&app, span), 30.0);
+    assert_eq!(size(&app, nested), 15.0);
+    // without parent text, sizes are relative to the default font size
+    assert_eq!(size(&app, child(&app, div, 1)), 10.0);
+
+    tree(&mut app, root).set_variable("size", PropertyValue::Number(40.0));
+    app.update();
+    assert_eq!(size(&app, span), 60.0);
+    assert_eq!(size(&app, nested), 30.0);
+}
+
+#[test]
+fn negative_lengths() {
+    const SOURCE: &str = r#"
+layout div {
+    with div {
+        margin: -8px;
+        left: -50%;
+        top: -10px;
+    }
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let div = child(&app, child(&app, root, 0), 0);
+    let node = app.world().get::<Node>(div).unwrap();
+    assert_eq!(node.margin, UiRect::all(Val::Px(-8.0)));
+    assert_eq!(node.left, Val::Percent(-50.0));
+    assert_eq!(node.top, Val::Px(-10.0));
+}
+
+#[test]
+fn hide_tree() {
+    const SOURCE: &str = r#"
+layout div {
+    with div {}
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    let div = child(&app, root, 0);
+
+    tree(&mut app, root).set_visible(false);
+    app.update();
+
+    assert_eq!(
+        app.world().get::<Visibility>(root),
+        Some(&Visibility::Hidden)
+    );
+    assert_eq!(child(&app, root, 0), div);
+    assert_eq!(app.world().get::<Children>(div).unwrap().len(), 1);
+
+    tree(&mut app, root).set_visible(true);
+    app.update();
+
+    assert_eq!(
+        app.world().get::<Visibility>(root),
+        Some(&Visibility::Inherited)
+    );
+}
+
+#[test]
+fn loaded_message() {
+    const SOURCE: &str = r#"
+def card {
+    layout div {
+        output;
+    }
+}
+
+def panel {
+    layout div {
+        output;
+    }
+}
+
+style div +active {
+    background-color: #ff0000;
+}
+
+style p {
+    font-size: 12;
+}
+    "#;
+
+    let mut app = app();
+    let handle = load(&mut app, SOURCE);
+    app.world_mut()
+        .write_message(AssetEvent::LoadedWithDependencies { id: handle.id() });
+    app.update();
+
+    let mut messages = app.world_mut().resource_mut::<Messages<NekoUILoaded>>();
+    assert_eq!(
+        messages.drain().collect::<Vec<_>>(),
+        vec![NekoUILoaded {
+            id: handle.id(),
+            widget_count: 2,
+            style_count: 2,
+        }]
+    );
+}
+
+#[test]
+fn intrinsic_sizes() {
+    const SOURCE: &str = r#"
+var size = 10px;
+
+layout div {
+    width: fit-content;
+    min-height: min-content;
+    max-width: max-content;
+    height: $size;
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let div = child(&app, root, 0);
+    let node = app.world().get::<Node>(div).unwrap();
+    assert_eq!(node.width, Val::Auto);
+    assert_eq!(node.min_height, Val::Auto);
+    assert_eq!(node.max_width, Val::Auto);
+    assert_eq!(node.height, Val::Px(10.0));
+
+    // unsupported keywords fall back to the default size
+    tree(&mut app, root).set_variable("size", "stretch".into());
+    app.update();
+
+    assert_eq!(app.world().get::<Node>(div).unwrap().height, Val::Auto);
+}
+
+#[test]
+fn auto_lengths() {
+    const SOURCE: &str = r#"
+var size = 10px;
+
+layout div {
+    flex-basis: $size;
+    max-width: $size;
+    min-height: $size;
+    row-gap: $size;
+    left: $size;
+    margin: $size;
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let div = child(&app, root, 0);
+    assert_eq!(
+        app.world().get::<Node>(div).unwrap().flex_basis,
+        Val::Px(10.0)
+    );
+
+    tree(&mut app, root).set_variable("size", "auto".into());
+    app.update();
+
+    let node = app.world().get::<Node>(div).unwrap();
+    assert_eq!(node.flex_basis, Val::Auto);
+    assert_eq!(node.max_width, Val::Auto);
+    assert_eq!(node.min_height, Val::Auto);
+    assert_eq!(node.row_gap, Val::Auto);
+    assert_eq!(node.left, Val::Auto);
+    assert_eq!(node.margin, UiRect::all(Val::Auto));
+}
+
+#[derive(Component)]
+struct Bold;
+
+impl NekoMarker for Bold {
+    fn new() -> Self {
+        Bold
+    }
+
+    fn id() -> &'static str {
+        "bold"
+    }
+}
+
+#[derive(Component)]
+struct Large;
+
+impl NekoMarker for Large {
+    fn new() -> Self {
+        Large
+    }
+
+    fn id() -> &'static str {
+        "large"
+    }
+}
+
+#[test]
+fn add_several_markers_at_once() {
+    const SOURCE: &str = r#"
+layout div {}
+    "#;
+
+    let mut app = app();
+    app.add_marker::<Bold>().add_marker::<Large>();
+    let root = spawn_tree(&mut app, SOURCE);
+    let div = child(&app, root, 0);
+
+    let mut node = app.world_mut().get_mut::<NekoUINode>(div).unwrap();
+    node.add_class("bold".into());
+    node.add_class("large".into());
+    app.update();
+
+    assert!(app.world().get::<Bold>(div).is_some());
+    assert!(app.world().get::<Large>(div).is_some());
+
+    let mut node = app.world_mut().get_mut::<NekoUINode>(div).unwrap();
+    node.remove_class("bold");
+    app.update();
+
+    assert!(app.world().get::<Bold>(div).is_none());
+    assert!(app.world().get::<Large>(div).is_some());
+}
+
+#[test]
+fn set_classes() {
+    const SOURCE: &str = r#"
+style div +bold {
+    background-color: #ff0000;
+}
+
+style div +large {
+    width: 200px;
+}
+
+layout div {}
+    "#;
+
+    let mut app = app();
+    app.add_marker::<Bold>().add_marker::<Large>();
+    let root = spawn_tree(&mut app, SOURCE);
+    let div = child(&app, root, 0);
+
+    let mut node = app.world_mut().get_mut::<NekoUINode>(div).unwrap();
+    node.set_classes(["bold".to_owned()].into_iter().collect());
+    app.update();
+
+    assert!(app.world().get::<Bold>(div).is_some());
+    assert!(app.world().get::<Large>(div).is_none());
+    assert_eq!(
+        app.world().get::<BackgroundColor>(div).unwrap().0,
+        Color::srgb(1.0, 0.0, 0.0)
+    );
+    assert_eq!(app.world().get::<Node>(div).unwrap().width, Val::Auto);
+
+    let mut node = app.world_mut().get_mut::<NekoUINode>(div).unwrap();
+    node.set_classes(["large".to_owned()].into_iter().collect());
+    app.update();
+
+    assert!(app.world().get::<Bold>(div).is_none());
+    assert!(app.world().get::<Large>(div).is_some());
+    assert_eq!(
+        app.world().get::<BackgroundColor>(div).unwrap().0,
+        Color::NONE
+    );
+    assert_eq!(app.world().get::<Node>(div).unwrap().width, Val::Px(200.0));
+}
+
+#[derive(Debug, PartialEq, Component)]
+struct Health(f32);
+
+#[derive(Component)]
+struct Enemy;
+
+#[test]
+fn registered_components() {
+    const SOURCE: &str = r#"
+var health = 100;
+
+layout div {
+    component health: $health;
+    component enemy;
+}
+    "#;
+
+    let mut app = app();
+    app.add_component("health", |value| value.as_f32().map(Health))
+        .add_component("enemy", |_| Some(Enemy));
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let div = child(&app, root, 0);
+    assert_eq!(app.world().get::<Health>(div), Some(&Health(100.0)));
+    assert!(app.world().get::<Enemy>(div).is_some());
+
+    tree(&mut app, root).set_number("health", 50.0);
+    app.update();
+    assert_eq!(app.world().get::<Health>(div), Some(&Health(50.0)));
+
+    tree(&mut app, root).set_string("health", "none");
+    app.update();
+    assert_eq!(app.world().get::<Health>(div), None);
+}
+
+#[test]
+fn typed_variable_setters() {
+    let mut tree = NekoUITree::new(Handle::default());
+    tree.set_number("number", 1.5);
+    tree.set_pixels("pixels", 4.0);
+    tree.set_percent("percent", 50.0);
+    tree.set_color("color", Color::WHITE);
+    tree.set_bool("bool", true);
+    tree.set_string("string", "hello");
+
+    assert_eq!(
+        tree.get_variable("number"),
+        Some(&PropertyValue::Number(1.5))
+    );
+    assert_eq!(
+        tree.get_variable("pixels"),
+        Some(&PropertyValue::Pixels(4.0))
+    );
+    assert_eq!(
+        tree.get_variable("percent"),
+        Some(&PropertyValue::Percent(50.0))
+    );
+    assert_eq!(
+        tree.get_variable("color"),
+        Some(&PropertyValue::Color(Color::WHITE))
+    );
+    assert_eq!(tree.get_variable("bool"), Some(&PropertyValue::Bool(true)));
+    assert_eq!(
+        tree.get_variable("string"),
+        Some(&PropertyValue::String("hello".into()))
+    );
+}
+
+#[test]
+fn line_height_units() {
+    const SOURCE: &str = r#"
+layout p {
+    text: "a";
+    line-height: 1.5;
+
+    with span {
+        text: "b";
+        line-height: 24px;
+    }
+
+    with span {
+        text: "c";
+    }
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let line_height = |app: &App, e: Entity| app.world().get::<TextFont>(e).unwrap().line_height;
+
+    let p = child(&app, root, 0);
+    assert_eq!(line_height(&app, p), LineHeight::RelativeToFont(1.5));
+    assert_eq!(line_height(&app, child(&app, p, 0)), LineHeight::Px(24.0));
+    // p and span share the same default
+    assert_eq!(
+        line_height(&app, child(&app, p, 1)),
+        TextFont::default().line_height
+    );
+}
+
+#[test]
+fn interaction_classes() {
+    let mut app = app();
+    app.insert_resource(InteractionClasses {
+        hovered: Some("hover".into()),
+        pressed: Some("active".into()),
+    });
+    let root = spawn_tree(&mut app, "layout div {}");
+    let div = child(&app, root, 0);
+
+    let mut interact = |interaction: Interaction| {
+        app.world_mut().entity_mut(div).insert(interaction);
+        app.update();
+        let node = app.world().get::<NekoUINode>(div).unwrap();
+        ["hover", "active", "hovered", "pressed"].map(|class| node.has_class(class))
+    };
+
+    assert_eq!(interact(Interaction::Hovered), [true, false, false, false]);
+    assert_eq!(interact(Interaction::Pressed), [true, true, false, false]);
+    assert_eq!(interact(Interaction::None), [false, false, false, false]);
+}
+
+#[test]
+fn tree_snapshot() {
+    const SOURCE: &str = r#"
+var accent = #ff0000;
+
+style p +title {
+    color: $accent;
+}
+
+layout div {
+    class card;
+    width: 50%;
+
+    with p {
+        class title;
+        text: "Hello";
+    }
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    let snapshot = NekoNodeSnapshot::of_tree(app.world(), root);
+
+    assert_eq!(
+        snapshot,
+        vec![NekoNodeSnapshot {
+            widget: "div".into(),
+            classes: vec!["card".into()],
+            properties: BTreeMap::from([("width".into(), PropertyValue::Percent(50.0))]),
+            children: vec![NekoNodeSnapshot {
+                widget: "p".into(),
+                classes: vec!["title".into()],
+                properties: BTreeMap::from([
+                    (
+                        "color".into(),
+                        PropertyValue::Color(Color::srgb(1.0, 0.0, 0.0))
+                    ),
+                    ("text".into(), PropertyValue::String("Hello".into())),
+                ]),
+                children: vec![],
+            }],
+        }]
+    );
+    assert_eq!(
+        snapshot[0].to_string(),
+        "div +card\n    width: 50%;\n    p +title\n        color: #FF0000;\n        text: \"Hello\";\n"
+    );
+}
+
+#[test]
+fn fallback_tree() {
+    let mut app = app();
+    let fallback = load(&mut app, "layout p {}");
+    let primary = app
+        .world()
+        .resource::<AssetServer>()
+        .load::<NekoMaidUI>("missing.neko_ui");
+    let root = app
+        .world_mut()
+        .spawn(NekoUITree::new(primary).with_fallback(fallback))
+        .id();
+
+    for _ in 0 .. 100 {
+        app.update();
+        if app.world().get::<Children>(root).is_some() {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    let snapshot = NekoNodeSnapshot::of_tree(app.world(), root);
+    assert_eq!(snapshot.len(), 1);
+    assert_eq!(snapshot[0].widget, "p");
+}
+
+#[test]
+fn tree_load_error() {
+    let mut app = app();
+    let handle = app
+        .world()
+        .resource::<AssetServer>()
+        .load::<NekoMaidUI>("invalid.neko_ui");
+    let root = app.world_mut().spawn(NekoUITree::new(handle)).id();
+
+    for _ in 0 .. 100 {
+        app.update();
+        if app
+            .world()
+            .get::<NekoUITree>(root)
+            .unwrap()
+            .load_error()
+            .is_some()
+        {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    let tree = app.world().get::<NekoUITree>(root).unwrap();
+    let error = tree
+        .load_error()
+        .expect("the tree should record the load error");
+    assert!(error.contains("Unknown widget 'missing-widget'"), "{error}");
+    assert!(app.world().get::<Children>(root).is_none());
+}
+
+#[test]
+fn keep_tree_on_failure() {
+    let mut app = app();
+    let handle = load(&mut app, "layout div {}");
+    let kept = app
+        .world_mut()
+        .spawn(NekoUITree::new(handle.clone()).with_clear_on_failure(false))
+        .id();
+    let cleared = app.world_mut().spawn(NekoUITree::new(handle.clone())).id();
+    app.update();
+
+    let div = child(&app, kept, 0);
+    let cleared_div = child(&app, cleared, 0);
+
+    app.world_mut().write_message(AssetLoadFailedEvent {
+        id: handle.id(),
+        path: AssetPath::from("broken.neko_ui"),
+        error: AssetLoadError::AssetMetaReadError,
+    });
+    app.update();
+
+    assert!(
+        app.world()
+            .get::<NekoUITree>(kept)
+            .unwrap()
+            .load_error()
+            .is_some()
+    );
+    assert_eq!(child(&app, kept, 0), div);
+    assert!(app.world().get_entity(cleared_div).is_err());
+}
+
+#[test]
+fn variable_override_setting() {
+    let mut app = app();
+    let handle = app
+        .world()
+        .resource::<AssetServer>()
+        .load_with_settings::<NekoMaidUI, NekoMaidSettings>(
+            "test.neko_ui",
+            |settings: &mut NekoMaidSettings| {
+                settings
+                    .variables
+                    .insert("text-color".into(), "#ff0000".into());
+            },
+        );
+    let root = app.world_mut().spawn(NekoUITree::new(handle)).id();
+
+    for _ in 0 .. 100 {
+        app.update();
+        if app.world().get::<Children>(root).is_some() {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    app.update();
+
+    let tree = app.world().get::<NekoUITree>(root).unwrap();
+    assert_eq!(
+        tree.get_variable_resolved("text-color"),
+        Some(&PropertyValue::Color(Color::srgb(1.0, 0.0, 0.0)))
+    );
+    assert_eq!(
+        tree.get_variable_resolved("p"),
+        Some(&PropertyValue::Number(20.0))
+    );
+}
+
+#[test]
+fn grid_template_areas() {
+    const SOURCE: &str = r#"
+style div +areas {
+    grid-template-areas: "header header" "sidebar main";
+}
+
+layout div {
+    display: grid;
+    class areas;
+
+    with div {
+        grid-area: main;
+    }
+
+    with div {
+        grid-area: header;
+    }
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    let grid = child(&app, root, 0);
+
+    let placement = |app: &App, i: usize| {
+        let node = app.world().get::<Node>(child(app, grid, i)).unwrap();
+        (node.grid_row, node.grid_column)
+    };
+
+    assert_eq!(
+        placement(&app, 0),
+        (
+            GridPlacement::start_end(2, 3),
+            GridPlacement::start_end(2, 3)
+        )
+    );
+    assert_eq!(
+        placement(&app, 1),
+        (
+            GridPlacement::start_end(1, 2),
+            GridPlacement::start_end(1, 3)
+        )
+    );
+
+    app.world_mut()
+        .get_mut::<NekoUINode>(grid)
+        .unwrap()
+        .remove_class("areas");
+    app.update();
+    app.update();
+
+    assert_eq!(
+        placement(&app, 0),
+        (GridPlacement::default(), GridPlacement::default())
+    );
+}
+
+#[test]
+fn invalid_grid_template_areas() {
+    assert_eq!(
+        GridTemplateAreas::new(["a a", "b a"]),
+        Err(GridTemplateError::NotRectangle("a".into()))
+    );
+    assert_eq!(
+        GridTemplateAreas::new(["a b", "c"]),
+        Err(GridTemplateError::UnevenRows {
+            row: 2,
+            expected: 2,
+            found: 1
+        })
+    );
+    assert_eq!(GridTemplateAreas::new([]), Err(GridTemplateError::Empty));
+    assert_eq!(GridTemplateAreas::new([" "]), Err(GridTemplateError::Empty));
+}
+
+#[test]
+fn root_layout_order() {
+    const SOURCE: &str = r#"
+layout div {
+    name: first;
+}
+
+layout p {
+    name: second;
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+
+    let names = app
+        .world()
+        .get::<Children>(root)
+        .unwrap()
+        .iter()
+        .map(|child| app.world().get::<Name>(child).unwrap().as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["first", "second"]);
+}
+
+#[test]
+fn conditional_class() {
+    const SOURCE: &str = r#"
+var selected = false;
+
+layout div {
+    class-if active: $selected;
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    let div = child(&app, root, 0);
+    app.update();
+    assert!(
+        !app.world()
+            .get::<NekoUINode>(div)
+            .unwrap()
+            .has_class("active")
+    );
+
+    let has_active = |app: &mut App, selected: bool| {
+        tree(app, root).set_bool("selected", selected);
+        app.update();
+        app.update();
+        app.world()
+            .get::<NekoUINode>(div)
+            .unwrap()
+            .has_class("active")
+    };
+
+    assert!(has_active(&mut app, true));
+    assert!(!has_active(&mut app, false));
+}
+
+#[test]
+fn conditional_class_keeps_other_classes() {
+    const SOURCE: &str = r#"
+var selected = false;
+
+layout div {
+    class active;
+    class-if active: $selected;
+    class-if open: $selected;
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    let div = child(&app, root, 0);
+    app.update();
+    app.world_mut()
+        .get_mut::<NekoUINode>(div)
+        .unwrap()
+        .add_class("open".to_string());
+
+    for selected in [true, false] {
+        tree(&mut app, root).set_bool("selected", selected);
+        app.update();
+        app.update();
+    }
+
+    let node = app.world().get::<NekoUINode>(div).unwrap();
+    assert!(node.has_class("active"));
+    assert!(node.has_class("open"));
+}
+
+#[test]
+fn import_paths() {
+    let path = AssetPath::from("ui/menus/main.neko_ui");
+    let resolve = |import: &str| resolve_import(&path, import).unwrap().to_string();
+
+    assert_eq!(resolve("/ui/common"), "ui/common.neko_ui");
+    assert_eq!(resolve("/ui/../theme"), "theme.neko_ui");
+    assert_eq!(resolve("buttons"), "ui/menus/buttons.neko_ui");
+    assert_eq!(resolve("../common"), "ui/common.neko_ui");
+}
+
+#[test]
+fn partial_settings() {
+    let settings: NekoMaidSettings = ron::de::from_str("(max_depth: Some(8))").unwrap();
+    assert_eq!(
+        settings,
+        NekoMaidSettings {
+            max_depth: Some(8),
+            ..default()
+        }
+    );
+
+    let settings: NekoMaidSettings =
+        ron::de::from_str(r#"(runtime_classes: ["selected"])"#).unwrap();
+    assert!(settings.variables.is_empty());
+    assert_eq!(settings.runtime_classes, vec!["selected"]);
+}
+
+#[test]
+fn static_tree() {
+    const SOURCE: &str = r#"
+var size = 10px;
+
+style p {
+    color: #ff0000;
+}
+
+layout div {
+    width: $size;
+
+    with p {
+        text: "Hello";
+    }
+}
+    "#;
+
+    let mut app = app();
+    let handle = load(&mut app, SOURCE);
+    let root = app
+        .world_mut()
+        .spawn(NekoUITree::new(handle).static_mode())
+        .id();
+    app.update();
+
+    let div = child(&app, root, 0);
+    let p = child(&app, div, 0);
+    assert_eq!(app.world().get::<Node>(div).unwrap().width, Val::Px(10.0));
+    assert_eq!(app.world().get::<Text>(p).unwrap().0, "Hello");
+    assert_eq!(
+        app.world().get::<TextColor>(p).unwrap().0,
+        Color::srgb(1.0, 0.0, 0.0)
+    );
+
+    assert!(app.world().get::<NekoUINode>(div).is_none());
+    assert!(app.world().get::<NekoUINode>(p).is_none());
+    assert!(tree(&mut app, root).scope_notification.is_empty());
+}
+
+#[test]
+fn debug_outline() {
+    const SOURCE: &str = r#"
+layout div {
+    class debug;
+
+    with div {}
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    let parent = child(&app, root, 0);
+    let inner = child(&app, parent, 0);
+    app.update();
+    assert!(app.world().get::<Outline>(parent).is_none());
+
+    app.world_mut().resource_mut::<NekoDebug>().enabled = true;
+    app.update();
+    assert!(app.world().get::<Outline>(parent).is_some());
+    assert!(app.world().get::<Outline>(inner).is_none());
+
+    app.world_mut().resource_mut::<NekoDebug>().all_nodes = true;
+    app.update();
+    assert!(app.world().get::<Outline>(inner).is_some());
+}
+
+#[test]
+fn debug_outline_overlay() {
+    const SOURCE: &str = r#"
+layout div {
+    with div {}
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    let parent = child(&app, root, 0);
+    let own = Outline::new(Val::Px(3.0), Val::ZERO, Color::WHITE);
+    app.world_mut().entity_mut(parent).insert(own);
+    app.update();
+
+    let mut debug = app.world_mut().resource_mut::<NekoDebug>();
+    debug.enabled = true;
+    debug.all_nodes = true;
+    debug.show_size = true;
+    app.update();
+
+    let children = app.world().get::<Children>(parent).unwrap().len();
+    assert_eq!(children, 1);
+    let labels = app
+        .world_mut()
+        .query_filtered::<Has<ChildOf>, With<DebugSizeLabel>>()
+        .iter(app.world())
+        .collect::<Vec<_>>();
+    assert_eq!(labels, vec![false, false]);
+    assert_ne!(app.world().get::<Outline>(parent), Some(&own));
+
+    // labels are hidden along with their node, such as by a hidden tree
+    let label_visibility = |app: &mut App| {
+        app.world_mut()
+            .query_filtered::<&Visibility, With<DebugSizeLabel>>()
+            .iter(app.world())
+            .copied()
+            .collect::<Vec<_>>()
+    };
+    let inner = child(&app, parent, 0);
+    for node in [parent, inner] {
+        app.world_mut()
+            .entity_mut(node)
+            .insert(InheritedVisibility::HIDDEN);
+    }
+    app.update();
+    assert_eq!(label_visibility(&mut app), vec![Visibility::Hidden; 2]);
+
+    for node in [parent, inner] {
+        app.world_mut()
+            .entity_mut(node)
+            .insert(InheritedVisibility::VISIBLE);
+    }
+    app.update();
+    assert_eq!(label_visibility(&mut app), vec![Visibility::Inherited; 2]);
+
+    app.world_mut().resource_mut::<NekoDebug>().enabled = false;
+    app.update();
+    assert_eq!(app.world().get::<Outline>(parent), Some(&own));
+    let labels = app
+        .world_mut()
+        .query_filtered::<(), With<DebugSizeLabel>>()
+        .iter(app.world())
+        .count();
+    assert_eq!(labels, 0);
+}
+
+#[test]
+fn variable_reassignment() {
+    const SOURCE: &str = r#"
+var greeting = "Hello";
+var greeting = "Hi";
+const name = "world";
+
+layout div {
+    const name = "there";
+
+    with p {
+        text: $greeting;
+    }
+
+    with p {
+        text: $name;
+    }
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+    let div = child(&app, root, 0);
+
+    let text = |i: usize| {
+        app.world()
+            .get::<Text>(child(&app, div, i))
+            .unwrap()
+            .0
+            .clone()
+    };
+    assert_eq!(text(0), "Hi");
+    assert_eq!(text(1), "there");
+}
+
+#[test]
+fn inherited_text_properties() {
+    const SOURCE: &str = r#"
+layout div {
+    color: #ff0000;
+    font-size: 32;
+
+    with p {
+        text: "Hello, ";
+
+        with span {
+            text: "world";
+            color: #00ff00;
+        }
+    }
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let div = child(&app, root, 0);
+    let p = child(&app, div, 0);
+    let span = child(&app, p, 0);
+
+    let red = Color::srgb(1.0, 0.0, 0.0);
+    assert_eq!(app.world().get::<TextColor>(p).unwrap().0, red);
+    assert_eq!(app.world().get::<TextFont>(p).unwrap().font_size, 32.0);
+    assert_eq!(
+        app.world().get::<TextColor>(span).unwrap().0,
+        Color::srgb(0.0, 1.0, 0.0)
+    );
+    assert_eq!(app.world().get::<TextFont>(span).unwrap().font_size, 32.0);
+
+    app.world_mut()
+        .get_mut::<NekoUINode>(div)
+        .unwrap()
+        .element
+        .set_property("color", PropertyValue::Color(Color::BLACK));
+    app.update();
+
+    assert_eq!(app.world().get::<TextColor>(p).unwrap().0, Color::BLACK);
+}
+
+#[test]
+fn static_tree_inheritance() {
+    const SOURCE: &str = r#"
+layout div {
+    color: #ff0000;
+    font-size: 32;
+
+    with p {
+        text: "Hello";
+        border-color: currentColor;
+    }
+
+    with p {
+        text: "World";
+        color: currentColor;
+    }
+}
+    "#;
+
+    let mut app = app();
+    let handle = load(&mut app, SOURCE);
+    let root = app
+        .world_mut()
+        .spawn(NekoUITree::new(handle).static_mode())
+        .id();
+    app.update();
+
+    let div = child(&app, root, 0);
+    let p = child(&app, div, 0);
+    let red = Color::srgb(1.0, 0.0, 0.0);
+    assert_eq!(app.world().get::<TextColor>(p).unwrap().0, red);
+    assert_eq!(app.world().get::<TextFont>(p).unwrap().font_size, 32.0);
+    assert_eq!(app.world().get::<BorderColor>(p).unwrap().left, red);
+    assert!(app.world().get::<NekoUINode>(p).is_none());
+
+    let current = child(&app, div, 1);
+    assert_eq!(app.world().get::<TextColor>(current).unwrap().0, red);
+}
+
+#[test]
+fn properties_applied_set() {
+    const SOURCE: &str = r#"
+layout p {
+    text: "a";
+    font-size: 50%;
+}
+    "#;
+
+    #[derive(Default, Resource)]
+    struct Observed(Vec<f32>);
+
+    let mut app = app();
+    app.init_resource::<Observed>().add_systems(
+        Update,
+        (|fonts: Query<&TextFont>, mut observed: ResMut<Observed>| {
+            observed.0.extend(fonts.iter().map(|font| font.font_size));
+        })
+        .after(NekoMaidSystems::PropertiesApplied),
+    );
+
+    let handle = load(&mut app, SOURCE);
+    app.world_mut().spawn(NekoUITree::new(handle));
+    app.update();
+
+    // the relative font size is resolved in the same frame the tree spawns
+    assert_eq!(app.world().resource::<Observed>().0, vec![10.0]);
+}
+
+#[test]
+fn bind_reflected_resource() {
+    const SOURCE: &str = r#"
+layout p {
+    text: $player.name;
+    font-size: $player.stats.level;
+}
+    "#;
+
+    #[derive(Reflect)]
+    struct Stats {
+        level: u32,
+    }
+
+    #[derive(Resource, Reflect)]
+    struct Player {
+        name: String,
+        stats: Stats,
+    }
+
+    let mut app = app();
+    app.insert_resource(Player {
+        name: "Neko".to_string(),
+        stats: Stats { level: 12 },
+    })
+    .bind_reflect::<Player, ()>("player");
+
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let p = child(&app, root, 0);
+    assert_eq!(app.world().get::<Text>(p).unwrap().0, "Neko");
+    assert_eq!(app.world().get::<TextFont>(p).unwrap().font_size, 12.0);
+
+    app.world_mut().resource_mut::<Player>().stats.level = 20;
+    app.update();
+
+    assert_eq!(
+        tree(&mut app, root).get_variable("player.stats.level"),
+        Some(&PropertyValue::Number(20.0))
+    );
+    assert_eq!(app.world().get::<TextFont>(p).unwrap().font_size, 20.0);
+}
+
+#[test]
+fn widget_default_style() {
+    const SOURCE: &str = r#"
+def button {
+    style {
+        padding: 4px;
+        background-color: #336699;
+    }
+
+    layout div {
+        output;
+    }
+}
+
+style div +large {
+    with button {
+        padding: 10px;
+    }
+}
+
+layout div {
+    with button {}
+
+    with div {
+        class large;
+
+        with button {}
+    }
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let div = child(&app, root, 0);
+    let small = app.world().get::<Node>(child(&app, div, 0)).unwrap();
+    assert_eq!(small.padding, UiRect::all(Val::Px(4.0)));
+
+    let large = child(&app, child(&app, div, 1), 0);
+    let node = app.world().get::<Node>(large).unwrap();
+    assert_eq!(node.padding, UiRect::all(Val::Px(10.0)));
+    assert_eq!(
+        app.world().get::<BackgroundColor>(large).unwrap().0,
+        Color::srgb_u8(0x33, 0x66, 0x99)
+    );
+}
+
+#[test]
+fn order_siblings() {
+    const SOURCE: &str = r#"
+var first = 1;
+
+layout div {
+    with p {
+        text: "a";
+        order: $first;
+    }
+
+    with p {
+        text: "b";
+    }
+
+    with p {
+        text: "c";
+        order: -0.5;
+    }
+}
+    "#;
+
+    fn texts(app: &App, div: Entity) -> Vec<String> {
+        let children = app.world().get::<Children>(div).unwrap();
+        children
+            .iter()
+            .map(|child| app.world().get::<Text>(child).unwrap().0.clone())
+            .collect()
+    }
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let div = child(&app, root, 0);
+    assert_eq!(texts(&app, div), vec!["c", "b", "a"]);
+
+    tree(&mut app, root).set_variable("first", PropertyValue::Number(-1.0));
+    app.update();
+    assert_eq!(texts(&app, div), vec!["a", "c", "b"]);
+
+    // children added later are ordered after the ones written before them.
+    tree(&mut app, root).set_variable("first", PropertyValue::Number(1.0));
+    app.update();
+    app.world_mut().spawn((Text::new("d"), ChildOf(div)));
+    app.update();
+    assert_eq!(texts(&app, div), vec!["c", "b", "d", "a"]);
+
+    app.world_mut().spawn((Text::new("e"), ChildOf(div)));
+    tree(&mut app, root).set_variable("first", PropertyValue::Number(0.0));
+    app.update();
+    assert_eq!(texts(&app, div), vec!["c", "a", "b", "d", "e"]);
+}
+
+#[test]
+fn current_color() {
+    const SOURCE: &str = r#"
+layout div {
+    color: #ff0000;
+
+    with p {
+        text: "Hello";
+        border-color: currentColor;
+        border-color-top: #0000ff;
+        background-color: currentColor;
+    }
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let div = child(&app, root, 0);
+    let p = child(&app, div, 0);
+
+    let text_color = app.world().get::<TextColor>(p).unwrap().0;
+    let border = app.world().get::<BorderColor>(p).unwrap();
+    assert_eq!(border.left, text_color);
+    assert_eq!(border.bottom, text_color);
+    assert_eq!(border.top, Color::srgb(0.0, 0.0, 1.0));
+    assert_eq!(app.world().get::<BackgroundColor>(p).unwrap().0, text_color);
+
+    app.world_mut()
+        .get_mut::<NekoUINode>(div)
+        .unwrap()
+        .element
+        .set_property("color", PropertyValue::Color(Color::BLACK));
+    app.update();
+
+    let border = app.world().get::<BorderColor>(p).unwrap();
+    assert_eq!(border.right, Color::BLACK);
+}
+
+#[test]
+fn current_color_text() {
+    const SOURCE: &str = r#"
+layout div {
+    color: #ff0000;
+
+    with p {
+        text: "Hello";
+        color: currentColor;
+        background-color: currentColor;
+    }
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let div = child(&app, root, 0);
+    let p = child(&app, div, 0);
+    let red = Color::srgb(1.0, 0.0, 0.0);
+    assert_eq!(app.world().get::<TextColor>(p).unwrap().0, red);
+    assert_eq!(app.world().get::<BackgroundColor>(p).unwrap().0, red);
+}
+
+#[test]
+fn element_keys() {
+    const SOURCE: &str = r#"
+var label = "Play";
+
+layout div {
+    with p {
+        key: "play-button";
+        text: $label;
+    }
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    let p = child(&app, child(&app, root, 0), 0);
+    assert_eq!(tree(&mut app, root).find_by_key("play-button"), Some(p));
+    assert_eq!(tree(&mut app, root).find_by_key("missing"), None);
+
+    tree(&mut app, root).set_variable("label", PropertyValue::String("Resume".into()));
+    tree(&mut app, root).mark_dirty();
+    app.update();
+
+    let respawned = child(&app, child(&app, root, 0), 0);
+    assert_ne!(respawned, p);
+    assert_eq!(
+        tree(&mut app, root).find_by_key("play-button"),
+        Some(respawned)
+    );
+    assert_eq!(app.world().get::<Text>(respawned).unwrap().0, "Resume");
+}
+
+#[test]
+fn element_keys_after_reload() {
+    const SOURCE: &str = r#"
+var id = "play";
+
+layout div {
+    with p {
+        key: "static-button";
+    }
+
+    with p {
+        key: $id;
+    }
+}
+    "#;
+
+    let mut app = app();
+    let root = spawn_tree(&mut app, SOURCE);
+    app.update();
+
+    // the nodes are kept until the tree is rebuilt, and so are their keys
+    let button = child(&app, child(&app, root, 0), 0);
+    tree(&mut app, root).reload();
+    assert_eq!(
+        tree(&mut app, root).find_by_key("static-button"),
+        Some(button)
+    );
+    app.update();
+
+    let div = child(&app, root, 0);
+    assert_eq!(
+        tree(&mut app, root).find_by_key("static-button"),
+        Some(child(&app, div, 0))
+    );
+    assert_eq!(
+        tree(&mut app, root).find_by_key("play"),
+        Some(child(&app, div, 1))
+    );
+}
+
+#[test]
+fn viewport_conditional_styles() {
+    const SOURCE: &str = r#"
+style div {
+    width: 300px;
+}
+
+@when width < 600px {
+    style div {
+        width: 100%;
+    }
+}
+    "#;
+    const LAYOUT: &str = "layout div {}";
+
+    let mut app = app();
+    let window = app
+        .world_mut()
+        .spawn((
+            Window {
+                resolution: WindowResolution::new(800, 600),
+                ..default()
+            },
+            PrimaryWindow,
+        ))
+        .id();
+    let root = spawn_tree(&mut app, &format!("{SOURCE}\n{LAYOUT}"));
+    app.update();
+
+    let div = child(&app, root, 0);
+    let width = |app: &App| app.world().get::<Node>(div).unwrap().width;
+    assert_eq!(width(&app), Val::Px(300.0));
+
+    let resize = |app: &mut App, width: f32| {
+        let mut window = app.world_mut().get_mut::<Window>(window).unwrap();
+        window.resolution.set(width, 600.0);
+        app.update();
+    };
+
+    resize(&mut app, 500.0);
+    assert_eq!(width(&app), Val::Percent(100.0));
+
+    resize(&mut app, 700.0);
+    assert_eq!(width(&app), Val::Px(300.0));
+}
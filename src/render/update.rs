@@ -1,17 +1,31 @@
 //! A module that defines the node update logic.
 
 use bevy::image::TRANSPARENT_IMAGE_HANDLE;
+use bevy::input_focus::tab_navigation::TabIndex;
 use bevy::prelude::*;
 
+use crate::font::{DefaultFont, FontFamilyRegistry, select_font};
 use crate::parse::element::NekoElementView;
-use crate::parse::value::PropertyValue;
+use crate::parse::value::{FontStyle, LayoutDirection, PropertyValue, WhiteSpace};
+use crate::render::font_size::{DEFAULT_FONT_SIZE, RelativeFontSize};
+use crate::render::grid_area::{GridArea, GridTemplateAreas};
+use crate::render::line_clamp::LineClamp;
+use crate::render::link::NekoLink;
+use crate::render::max_lines::MaxLines;
+use crate::render::order::Order;
+use crate::render::scroll::WheelScroll;
+use crate::render::selection::TextSelection;
 
 /// Partially updates the given components based on the current computed
 /// properties.
 pub fn update_node<'a>(
     asset_server: &Res<AssetServer>,
+    fonts: &FontFamilyRegistry,
+    default_font: &DefaultFont,
     mut element: NekoElementView<'a>,
     updated_properties: impl Iterator<Item = &'a String>,
+    // used for components that are only present for some property values
+    commands: &mut EntityCommands,
     // node
     node: &mut Node,
     border_color: &mut BorderColor,
@@ -31,6 +45,35 @@ pub fn update_node<'a>(
         match property.as_str() {
             // --- node ---
 
+            // debugging
+            "name" => match element.get_as::<String>("name") {
+                Some(name) => {
+                    commands.insert(Name::new(name));
+                }
+                None => {
+                    commands.remove::<Name>();
+                }
+            },
+
+            // focus
+            "tab-index" => match element.get_as::<f32>("tab-index") {
+                Some(index) => {
+                    commands.insert(TabIndex(index.round() as i32));
+                }
+                None => {
+                    commands.remove::<TabIndex>();
+                }
+            },
+
+            "order" => match element.get_as::<f32>("order") {
+                Some(order) => {
+                    commands.insert(Order(order));
+                }
+                None => {
+                    commands.remove::<Order>();
+                }
+            },
+
             // basic layout
             "display" => node.display = element.get_as("display").unwrap_or_default(),
             "box-sizing" => node.box_sizing = element.get_as("box-sizing").unwrap_or_default(),
@@ -38,8 +81,19 @@ pub fn update_node<'a>(
                 node.position_type = element.get_as("position-type").unwrap_or_default()
             }
             // overflow
-            "overflow-x" => node.overflow.x = element.get_as("overflow-x").unwrap_or_default(),
-            "overflow-y" => node.overflow.y = element.get_as("overflow-y").unwrap_or_default(),
+            "overflow-x" | "overflow-y" | "overflow" => {
+                let (x, y) = shorthand_pair(element.get_property("overflow"), "overflow");
+                node.overflow.x = element.get_as("overflow-x").or(x).unwrap_or_default();
+                node.overflow.y = element.get_as("overflow-y").or(y).unwrap_or_default();
+
+                if node.overflow.x == OverflowAxis::Scroll
+                    || node.overflow.y == OverflowAxis::Scroll
+                {
+                    commands.insert(WheelScroll);
+                } else {
+                    commands.remove::<WheelScroll>();
+                }
+            }
             "scrollbar-width" => {
                 node.scrollbar_width = element.get_as("scrollbar-width").unwrap_or_default()
             }
@@ -58,29 +112,63 @@ pub fn update_node<'a>(
             "right" => node.right = element.get_as("right").unwrap_or_default(),
             "bottom" => node.bottom = element.get_as("bottom").unwrap_or_default(),
             // sizing
-            "width" => node.width = element.get_as("width").unwrap_or_default(),
-            "height" => node.height = element.get_as("height").unwrap_or_default(),
-            "min-width" => node.min_width = element.get_as("min-width").unwrap_or_default(),
-            "min-height" => node.min_height = element.get_as("min-height").unwrap_or_default(),
-            "max-width" => node.max_width = element.get_as("max-width").unwrap_or_default(),
-            "max-height" => node.max_height = element.get_as("max-height").unwrap_or_default(),
+            "width" => node.width = size(&mut element, "width"),
+            "height" => node.height = size(&mut element, "height"),
+            "min-width" => node.min_width = size(&mut element, "min-width"),
+            "min-height" => node.min_height = size(&mut element, "min-height"),
+            "max-width" => node.max_width = size(&mut element, "max-width"),
+            "max-height" | "max-lines" => {
+                node.max_height = size(&mut element, "max-height");
+
+                match element.get_as::<f32>("max-lines") {
+                    Some(lines) => {
+                        let max_height = node.max_height;
+                        commands
+                            .entry::<MaxLines>()
+                            .and_modify(move |mut max_lines| {
+                                max_lines.lines = (lines as usize).max(1);
+                                max_lines.max_height = max_height;
+                            })
+                            .or_insert(MaxLines::new(lines as usize, max_height));
+                    }
+                    None => {
+                        commands.remove::<MaxLines>();
+                    }
+                }
+            }
             "aspect-ratio" => {
                 node.aspect_ratio = element.get_as("aspect-ratio").unwrap_or_default()
             }
             // alignment
-            "align-items" => node.align_items = element.get_as("align-items").unwrap_or_default(),
-            "justify-items" => {
-                node.justify_items = element.get_as("justify-items").unwrap_or_default()
-            }
             "align-self" => node.align_self = element.get_as("align-self").unwrap_or_default(),
-            "justify-self" => {
-                node.justify_self = element.get_as("justify-self").unwrap_or_default()
-            }
-            "align-content" => {
-                node.align_content = element.get_as("align-content").unwrap_or_default()
-            }
-            "justify-content" => {
-                node.justify_content = element.get_as("justify-content").unwrap_or_default()
+            // the direction mirrors all of these, so they are applied together
+            "align-items" | "justify-items" | "place-items" | "justify-self" | "align-content"
+            | "justify-content" | "place-content" | "flex-direction" | "direction" => {
+                let (align, justify) =
+                    shorthand_pair(element.get_property("place-items"), "place-items");
+                node.align_items = element.get_as("align-items").or(align).unwrap_or_default();
+                node.justify_items = element
+                    .get_as("justify-items")
+                    .or(justify)
+                    .unwrap_or_default();
+                node.justify_self = element.get_as("justify-self").unwrap_or_default();
+
+                let (align, justify) =
+                    shorthand_pair(element.get_property("place-content"), "place-content");
+                node.align_content = element
+                    .get_as("align-content")
+                    .or(align)
+                    .unwrap_or_default();
+                node.flex_direction = element.get_as("flex-direction").unwrap_or_default();
+                node.justify_content = element
+                    .get_as("justify-content")
+                    .or(justify)
+                    .unwrap_or_default();
+
+                let direction: LayoutDirection = element.get_as("direction").unwrap_or_default();
+                if direction == LayoutDirection::Rtl {
+                    mirror_node(node);
+                }
             }
             // margin
             "margin-top" | "margin-left" | "margin-right" | "margin-bottom" | "margin" => {
@@ -104,16 +192,10 @@ pub fn update_node<'a>(
             | "border-thickness-right"
             | "border-thickness-bottom"
             | "border-thickness" => {
-                let border = element.get_as("border-thickness").unwrap_or(Val::Px(0.0));
-                node.border.top = element.get_as_or("border-thickness-top", border);
-                node.border.left = element.get_as_or("border-thickness-left", border);
-                node.border.right = element.get_as_or("border-thickness-right", border);
-                node.border.bottom = element.get_as_or("border-thickness-bottom", border);
+                let (width, _) = border_shorthand(element.get_property("border"));
+                update_border_thickness(&mut element, node, width);
             }
             // flex
-            "flex-direction" => {
-                node.flex_direction = element.get_as("flex-direction").unwrap_or_default()
-            }
             "flex-wrap" => node.flex_wrap = element.get_as("flex-wrap").unwrap_or_default(),
             "flex-grow" => node.flex_grow = element.get_as("flex-grow").unwrap_or_default(),
             "flex-shrink" => node.flex_shrink = element.get_as("flex-shrink").unwrap_or(1.0),
@@ -125,6 +207,34 @@ pub fn update_node<'a>(
             "grid-auto-flow" => {
                 node.grid_auto_flow = element.get_as("grid-auto-flow").unwrap_or_default()
             }
+            "grid-template-areas" => {
+                let template = element.get_property("grid-template-areas").map(|value| {
+                    GridTemplateAreas::new(value.as_list().iter().filter_map(|row| row.as_str()))
+                });
+
+                match template {
+                    Some(Ok(template)) => {
+                        commands.insert(template);
+                    }
+                    Some(Err(error)) => {
+                        warn!("Invalid grid-template-areas: {error}");
+                        commands.remove::<GridTemplateAreas>();
+                    }
+                    None => {
+                        commands.remove::<GridTemplateAreas>();
+                    }
+                }
+            }
+            "grid-area" => match element.get_as::<String>("grid-area") {
+                Some(area) => {
+                    commands.insert(GridArea(area));
+                }
+                None => {
+                    commands.remove::<GridArea>();
+                    node.grid_row = GridPlacement::default();
+                    node.grid_column = GridPlacement::default();
+                }
+            },
 
             // --- border color ---
             "border-color-top"
@@ -132,11 +242,13 @@ pub fn update_node<'a>(
             | "border-color-right"
             | "border-color-bottom"
             | "border-color" => {
-                let color = element.get_as("border-color").unwrap_or(Color::NONE);
-                border_color.top = element.get_as_or("border-color-top", color);
-                border_color.left = element.get_as_or("border-color-left", color);
-                border_color.right = element.get_as_or("border-color-right", color);
-                border_color.bottom = element.get_as_or("border-color-bottom", color);
+                let (_, color) = border_shorthand(element.get_property("border"));
+                update_border_color(&mut element, border_color, color);
+            }
+            "border" => {
+                let (width, color) = border_shorthand(element.get_property("border"));
+                update_border_thickness(&mut element, node, width);
+                update_border_color(&mut element, border_color, color);
             }
 
             // --- border radius ---
@@ -145,11 +257,15 @@ pub fn update_node<'a>(
             | "border-radius-bottom-left"
             | "border-radius-bottom-right"
             | "border-radius" => {
-                let radius = element.get_as("border-radius").unwrap_or(Val::Px(0.0));
-                border_radius.top_left = element.get_as_or("border-radius-top-left", radius);
-                border_radius.top_right = element.get_as_or("border-radius-top-right", radius);
-                border_radius.bottom_left = element.get_as_or("border-radius-bottom-left", radius);
-                border_radius.bottom_right = element.get_as_or("border-radius-bottom-right", radius)
+                let radius = radius_shorthand(element.get_property("border-radius"));
+                border_radius.top_left =
+                    element.get_as_or("border-radius-top-left", radius.top_left);
+                border_radius.top_right =
+                    element.get_as_or("border-radius-top-right", radius.top_right);
+                border_radius.bottom_left =
+                    element.get_as_or("border-radius-bottom-left", radius.bottom_left);
+                border_radius.bottom_right =
+                    element.get_as_or("border-radius-bottom-right", radius.bottom_right)
             }
             // --- background color ---
             "background-color" => {
@@ -274,26 +390,59 @@ pub fn update_node<'a>(
             // --- text ---
 
             // text content
-            "text" => {
+            "text" | "white-space" | "line-clamp" | "text-overflow" => {
+                let mut content: String = element.get_as("text").unwrap_or_default();
+                let white_space: WhiteSpace = element.get_as("white-space").unwrap_or_default();
+                if white_space.collapses() {
+                    content = content.split_whitespace().collect::<Vec<_>>().join(" ");
+                }
+
+                let line_clamp: Option<f32> = element.get_as("line-clamp");
+                match line_clamp {
+                    Some(lines) if text.is_some() => {
+                        let overflow: String = element.get_as_or("text-overflow", String::new());
+                        let ellipsis = overflow == "ellipsis";
+                        commands.insert(LineClamp::new(lines as usize, ellipsis, content.clone()));
+                    }
+                    _ => {
+                        commands.remove::<LineClamp>();
+                    }
+                }
+
                 if let Some(text) = text {
-                    text.0 = element.get_as("text").unwrap_or_default();
+                    text.0 = content;
                 } else if let Some(span) = span {
-                    span.0 = element.get_as("text").unwrap_or_default();
+                    span.0 = content;
+                }
+
+                if let Some(layout) = layout {
+                    layout.linebreak = match white_space.wraps() {
+                        true => element.get_as("line-break").unwrap_or_default(),
+                        false => LineBreak::NoWrap,
+                    };
                 }
             }
             // font
-            "font" => {
+            "font" | "font-style" => {
                 if let Some(font) = font {
-                    let font_path: String = element.get_as("font").unwrap_or_default();
-                    font.font = match font_path.as_str() {
-                        "auto" => Handle::<Font>::default(),
-                        _ => asset_server.load(font_path),
-                    };
+                    let path = element.get_as::<String>("font");
+                    let style: FontStyle = element.get_as("font-style").unwrap_or_default();
+                    font.font =
+                        select_font(path.as_deref(), style, fonts, default_font, asset_server);
                 }
             }
             "font-size" => {
                 if let Some(font) = font {
-                    font.font_size = element.get_as("font-size").unwrap_or(20.0)
+                    match element.get_property("font-size") {
+                        Some(PropertyValue::Percent(percent)) => {
+                            commands.insert(RelativeFontSize(*percent as f32 / 100.0));
+                        }
+                        _ => {
+                            commands.remove::<RelativeFontSize>();
+                            font.font_size =
+                                element.get_as("font-size").unwrap_or(DEFAULT_FONT_SIZE)
+                        }
+                    }
                 }
             }
             "line-height" => {
@@ -312,7 +461,11 @@ pub fn update_node<'a>(
                     match property.as_str() {
                         "justify" => layout.justify = element.get_as("justify").unwrap_or_default(),
                         "line-break" => {
-                            layout.linebreak = element.get_as("line-break").unwrap_or_default()
+                            let white_space: WhiteSpace =
+                                element.get_as("white-space").unwrap_or_default();
+                            if white_space.wraps() {
+                                layout.linebreak = element.get_as("line-break").unwrap_or_default()
+                            }
                         }
                         _ => {}
                     }
@@ -324,8 +477,199 @@ pub fn update_node<'a>(
                     color.0 = element.get_as("color").unwrap_or(Color::WHITE)
                 }
             }
+            // link (Text and TextSpan only)
+            "link" => match element.get_as::<String>("link") {
+                Some(href) if text.is_some() || span.is_some() => {
+                    commands.insert(NekoLink(href));
+                }
+                _ => {
+                    commands.remove::<NekoLink>();
+                }
+            },
+            // selection (Text only)
+            "user-select" => {
+                let user_select: String = element.get_as("user-select").unwrap_or_default();
+                if text.is_some() && user_select == "text" {
+                    commands.insert(TextSelection::default());
+                } else {
+                    commands.remove::<TextSelection>();
+                }
+            }
 
             _ => {}
         }
     }
 }
+
+/// Expands a shorthand property that sets two properties at once.
+///
+/// A single value is used for both properties, while two values set the first
+/// and second property in order. Returns `None` for both if the shorthand is
+/// not set or has another number of values.
+fn shorthand_pair<A, B>(value: Option<&PropertyValue>, name: &str) -> (Option<A>, Option<B>)
+where
+    A: for<'a> From<&'a PropertyValue>,
+    B: for<'a> From<&'a PropertyValue>,
+{
+    match value.map(PropertyValue::as_list) {
+        Some([first, second]) => (Some(first.into()), Some(second.into())),
+        Some([both]) => (Some(both.into()), Some(both.into())),
+        Some(values) => {
+            warn!(
+                "Expected one or two values for {name}, found {}",
+                values.len()
+            );
+            (None, None)
+        }
+        None => (None, None),
+    }
+}
+
+/// Expands the `border` shorthand into its width and color.
+///
+/// The width and color may be given in any order, and either may be omitted,
+/// as in `border: 2px #000;`, `border: 2px;` or `border: #000;`.
+fn border_shorthand(value: Option<&PropertyValue>) -> (Option<Val>, Option<Color>) {
+    let values = value.map(PropertyValue::as_list).unwrap_or_default();
+    let width = values.iter().find_map(PropertyValue::as_val);
+    let color = values.iter().find_map(PropertyValue::as_color);
+
+    if values.len() > width.is_some() as usize + color.is_some() as usize {
+        warn!(
+            "Expected a width and a color for border, found {}",
+            value.unwrap()
+        );
+    }
+
+    (width, color)
+}
+
+/// The intrinsic sizing keywords accepted by the size properties.
+const INTRINSIC_SIZES: [&str; 3] = ["min-content", "max-content", "fit-content"];
+
+/// Returns the value of a size property, such as `width` or `max-height`.
+///
+/// Besides lengths, the intrinsic sizing keywords `min-content`, `max-content`
+/// and `fit-content` are accepted. Bevy has no equivalent for them, but sizes
+/// nodes with an automatic size to fit their content, so they all map to
+/// [`Val::Auto`].
+fn size(element: &mut NekoElementView, name: &str) -> Val {
+    match element.get_property(name) {
+        Some(PropertyValue::String(s)) if INTRINSIC_SIZES.contains(&s.as_str()) => Val::Auto,
+        _ => element.get_as(name).unwrap_or_default(),
+    }
+}
+
+/// Sets the border thickness of all sides of a node, falling back to the
+/// width given by the `border` shorthand.
+fn update_border_thickness(element: &mut NekoElementView, node: &mut Node, width: Option<Val>) {
+    let border = element
+        .get_as("border-thickness")
+        .or(width)
+        .unwrap_or(Val::Px(0.0));
+    node.border.top = element.get_as_or("border-thickness-top", border);
+    node.border.left = element.get_as_or("border-thickness-left", border);
+    node.border.right = element.get_as_or("border-thickness-right", border);
+    node.border.bottom = element.get_as_or("border-thickness-bottom", border);
+}
+
+/// Sets the border color of all sides of a node, falling back to the color
+/// given by the `border` shorthand.
+fn update_border_color(
+    element: &mut NekoElementView,
+    border_color: &mut BorderColor,
+    color: Option<Color>,
+) {
+    let color = element
+        .get_as("border-color")
+        .or(color)
+        .unwrap_or(Color::NONE);
+    border_color.top = element.get_as_or("border-color-top", color);
+    border_color.left = element.get_as_or("border-color-left", color);
+    border_color.right = element.get_as_or("border-color-right", color);
+    border_color.bottom = element.get_as_or("border-color-bottom", color);
+}
+
+/// Expands the `border-radius` shorthand into the radius of each corner.
+///
+/// Like in CSS, the values are given clockwise from the top-left corner, and
+/// missing corners take the value of their opposite corner:
+///
+/// - one value sets all corners,
+/// - two values set top-left and bottom-right, then top-right and bottom-left,
+/// - three values set top-left, then top-right and bottom-left, then
+///   bottom-right,
+/// - four values set top-left, top-right, bottom-right, then bottom-left.
+fn radius_shorthand(value: Option<&PropertyValue>) -> BorderRadius {
+    let values = value
+        .map(PropertyValue::as_list)
+        .unwrap_or_default()
+        .iter()
+        .map(Val::from)
+        .collect::<Vec<_>>();
+
+    match values[..] {
+        [] => BorderRadius::all(Val::Px(0.0)),
+        [all] => BorderRadius::all(all),
+        [a, b] => BorderRadius::new(a, b, a, b),
+        [a, b, c] => BorderRadius::new(a, b, c, b),
+        [a, b, c, d] => BorderRadius::new(a, b, c, d),
+        _ => {
+            warn!(
+                "Expected one to four values for border-radius, found {}",
+                values.len()
+            );
+            BorderRadius::all(Val::Px(0.0))
+        }
+    }
+}
+
+/// Mirrors the horizontal axis of a node, so its content flows from right to
+/// left.
+///
+/// Rows are reversed, and `flex-start` and `flex-end` follow the reversed row
+/// on their own, but `start` and `end` always refer to the physical left and
+/// right of the node and need to be swapped. This applies to the alignment on
+/// the horizontal axis only: `justify-content` in rows, `align-items` and
+/// `align-content` in columns, where the cross axis is horizontal, and the
+/// grid-only `justify-items` and `justify-self`.
+fn mirror_node(node: &mut Node) {
+    node.justify_items = match node.justify_items {
+        JustifyItems::Start => JustifyItems::End,
+        JustifyItems::End => JustifyItems::Start,
+        justify => justify,
+    };
+    node.justify_self = match node.justify_self {
+        JustifySelf::Start => JustifySelf::End,
+        JustifySelf::End => JustifySelf::Start,
+        justify => justify,
+    };
+
+    match node.flex_direction {
+        FlexDirection::Row => node.flex_direction = FlexDirection::RowReverse,
+        FlexDirection::RowReverse => node.flex_direction = FlexDirection::Row,
+        FlexDirection::Column | FlexDirection::ColumnReverse => {
+            node.align_items = match node.align_items {
+                AlignItems::Start => AlignItems::End,
+                AlignItems::End => AlignItems::Start,
+                AlignItems::FlexStart => AlignItems::FlexEnd,
+                AlignItems::FlexEnd => AlignItems::FlexStart,
+                align => align,
+            };
+            node.align_content = match node.align_content {
+                AlignContent::Start => AlignContent::End,
+                AlignContent::End => AlignContent::Start,
+                AlignContent::FlexStart => AlignContent::FlexEnd,
+                AlignContent::FlexEnd => AlignContent::FlexStart,
+                align => align,
+            };
+            return;
+        }
+    }
+
+    node.justify_content = match node.justify_content {
+        JustifyContent::Start => JustifyContent::End,
+        JustifyContent::End => JustifyContent::Start,
+        justify => justify,
+    };
+}
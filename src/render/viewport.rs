@@ -0,0 +1,49 @@
+//! Keeps the styles declared in `@when` blocks in sync with the size of the
+//! primary window.
+
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+use crate::components::{NekoUINode, NekoUITree};
+
+/// Updates the viewport of the trees and their nodes when the primary window
+/// is resized, activating and deactivating their conditional styles.
+///
+/// The nodes spawned afterwards are given the viewport of their tree when
+/// spawned.
+pub(crate) fn update_viewport(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut roots: Query<(Entity, &mut NekoUITree)>,
+    mut nodes: Query<&mut NekoUINode>,
+) {
+    let Ok(window) = windows.single() else {
+        return;
+    };
+    let viewport = window.size();
+
+    let mut resized = vec![];
+    for (entity, mut root) in roots.iter_mut() {
+        if root.viewport != Some(viewport) {
+            // the tree itself does not need to be updated.
+            root.bypass_change_detection().viewport = Some(viewport);
+            resized.push(entity);
+        }
+    }
+
+    if resized.is_empty() {
+        return;
+    }
+
+    for mut node in nodes.iter_mut() {
+        if !resized.contains(&node.root) {
+            continue;
+        }
+        if node
+            .bypass_change_detection()
+            .element
+            .set_viewport(viewport)
+        {
+            node.set_changed();
+        }
+    }
+}